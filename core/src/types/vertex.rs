@@ -119,6 +119,25 @@ impl AsAttribute for Tangent {
     const FORMAT: Format = Format::Rgba32Sfloat;
 }
 
+/// Type for bitangent attribute of vertex. Should match `cross(normal, tangent.xyz) * tangent.w`
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bitangent(pub [f32; 3]);
+impl<T> From<T> for Bitangent
+where
+    T: Into<[f32; 3]>,
+{
+    fn from(from: T) -> Self {
+        Bitangent(from.into())
+    }
+}
+
+impl AsAttribute for Bitangent {
+    const NAME: &'static str = "bitangent";
+    const FORMAT: Format = Format::Rgb32Sfloat;
+}
+
 /// Type for texture coord attribute of vertex
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -138,6 +157,25 @@ impl AsAttribute for TexCoord {
     const FORMAT: Format = Format::Rg32Sfloat;
 }
 
+/// Type for three-dimensional texture coord attribute of vertex, used with volume textures
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TexCoord3(pub [f32; 3]);
+impl<T> From<T> for TexCoord3
+where
+    T: Into<[f32; 3]>,
+{
+    fn from(from: T) -> Self {
+        TexCoord3(from.into())
+    }
+}
+
+impl AsAttribute for TexCoord3 {
+    const NAME: &'static str = "tex_coord3";
+    const FORMAT: Format = Format::Rgb32Sfloat;
+}
+
 /// Vertex format contains information to initialize graphics pipeline
 /// Attributes must be sorted by offset.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -0,0 +1,105 @@
+//! Benchmarks of obj loading and tangent computation on a large synthetic file.
+//!
+//! Run it with and without the `parallel` feature to compare:
+//! `cargo bench --features obj` then `cargo bench --features obj,parallel`.
+//! Criterion reports the change relative to the previous run.
+//! The general weld path is only benchmarked with the internal `bench` feature.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::fmt::Write;
+
+/// Generate obj with `objects` objects, each a `size` x `size` grid of quads
+/// split between two materials.
+///
+/// With `shared_indices` every vertex has a normal of its own and faces use equal
+/// position, texture coordinate and normal indices, as many exporters write them.
+fn synthetic_obj(objects: usize, size: usize, shared_indices: bool) -> String {
+    let mut obj = String::new();
+    let mut base = 1;
+    for object in 0..objects {
+        writeln!(obj, "o object{}", object).unwrap();
+        for y in 0..=size {
+            for x in 0..=size {
+                writeln!(obj, "v {} {} {}", x, y, object).unwrap();
+                writeln!(
+                    obj,
+                    "vt {} {}",
+                    x as f32 / size as f32,
+                    y as f32 / size as f32
+                )
+                .unwrap();
+                if shared_indices {
+                    writeln!(obj, "vn 0 0 1").unwrap();
+                }
+            }
+        }
+        if !shared_indices {
+            writeln!(obj, "vn 0 0 1").unwrap();
+        }
+        for y in 0..size {
+            if y % (size / 2).max(1) == 0 {
+                writeln!(obj, "usemtl material{}", y * 2 / size).unwrap();
+            }
+            for x in 0..size {
+                let i = base + y * (size + 1) + x;
+                let corners = [i, i + 1, i + size + 2, i + size + 1];
+                write!(obj, "f").unwrap();
+                for &corner in &corners {
+                    let normal = if shared_indices { corner } else { object + 1 };
+                    write!(obj, " {}/{}/{}", corner, corner, normal).unwrap();
+                }
+                writeln!(obj).unwrap();
+            }
+        }
+        base += (size + 1) * (size + 1);
+    }
+    obj
+}
+
+fn load_obj(c: &mut Criterion) {
+    let obj = synthetic_obj(32, 64, false);
+    let shared = synthetic_obj(32, 64, true);
+    let mut group = c.benchmark_group("obj");
+    group.sample_size(10);
+    group.bench_function("load_from_obj", |b| {
+        b.iter(|| rendy_mesh::obj::load_from_obj(black_box(obj.as_bytes())).unwrap())
+    });
+    // The same file welded by index and by the general path.
+    group.bench_function("load_from_obj_shared_indices", |b| {
+        b.iter(|| rendy_mesh::obj::load_from_obj(black_box(shared.as_bytes())).unwrap())
+    });
+    #[cfg(feature = "bench")]
+    {
+        let general = rendy_mesh::obj::ObjLoadOptions {
+            weld_by_index: false,
+            ..rendy_mesh::obj::ObjLoadOptions::default()
+        };
+        group.bench_function("load_from_obj_shared_indices_general_weld", |b| {
+            b.iter(|| {
+                rendy_mesh::obj::load_from_obj_with_options(black_box(shared.as_bytes()), general)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compute_tangents(c: &mut Criterion) {
+    let obj = synthetic_obj(1, 512, false);
+    let (builder, _) = rendy_mesh::obj::load_from_obj(obj.as_bytes())
+        .unwrap()
+        .remove(0);
+    let mut group = c.benchmark_group("tangents");
+    group.sample_size(10);
+    group.bench_function("recompute_tangents", |b| {
+        b.iter(|| {
+            let mut builder = black_box(builder.clone());
+            builder.recompute_tangents().unwrap();
+            builder
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load_obj, compute_tangents);
+criterion_main!(benches);
@@ -1,2 +1,23 @@
 #[cfg(feature = "obj")]
 pub mod obj;
+
+#[cfg(feature = "stl")]
+pub mod stl;
+
+#[cfg(feature = "ply")]
+pub mod ply;
+
+#[cfg(feature = "gltf")]
+pub mod gltf;
+
+#[cfg(feature = "collada")]
+pub mod collada;
+
+#[cfg(feature = "fbx")]
+pub mod fbx;
+
+#[cfg(any(feature = "obj", feature = "collada", feature = "fbx"))]
+mod weld;
+
+#[cfg(all(feature = "mmap", any(feature = "obj", feature = "ply")))]
+mod mmap;
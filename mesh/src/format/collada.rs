@@ -0,0 +1,609 @@
+//! Loading mesh data from COLLADA format.
+
+use {
+    self::xml::Element,
+    super::weld::weld,
+    crate::{mesh::MeshBuilder, Normal, Position, TexCoord},
+    log::warn,
+    std::collections::HashMap,
+};
+
+mod xml;
+
+/// COLLADA loading error.
+#[derive(Debug, PartialEq)]
+pub enum ColladaError {
+    /// The passed bytes were improper UTF-8 data.
+    InvalidUtf8 {
+        /// Offset of the first invalid byte.
+        position: usize,
+    },
+    /// The document is not well-formed XML.
+    Xml {
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// A geometry is malformed or uses unsupported features.
+    Invalid {
+        /// Name of the geometry.
+        geometry: String,
+        /// Description of the error.
+        message: String,
+    },
+    /// A geometry references source that doesn't exist.
+    MissingSource {
+        /// Name of the geometry.
+        geometry: String,
+        /// Id of the referenced source.
+        id: String,
+    },
+    /// A primitive references element of a source that doesn't exist.
+    IndexOutOfRange {
+        /// Name of the geometry.
+        geometry: String,
+        /// The invalid index.
+        index: usize,
+        /// Number of elements in the source.
+        len: usize,
+    },
+}
+
+impl std::error::Error for ColladaError {}
+impl std::fmt::Display for ColladaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColladaError::InvalidUtf8 { position } => {
+                write!(f, "Invalid UTF-8 at byte {} of COLLADA document", position)
+            }
+            ColladaError::Xml {
+                line_number,
+                message,
+            } => write!(
+                f,
+                "Error parsing COLLADA document at line {}: {}",
+                line_number, message
+            ),
+            ColladaError::Invalid { geometry, message } => {
+                write!(f, "Invalid geometry '{}': {}", geometry, message)
+            }
+            ColladaError::MissingSource { geometry, id } => write!(
+                f,
+                "Geometry '{}' references missing source '{}'",
+                geometry, id
+            ),
+            ColladaError::IndexOutOfRange {
+                geometry,
+                index,
+                len,
+            } => write!(
+                f,
+                "Geometry '{}' references element {} but its source has only {}",
+                geometry, index, len
+            ),
+        }
+    }
+}
+
+/// Mesh loaded from a primitive element of a COLLADA geometry.
+#[derive(Debug)]
+pub struct ColladaMesh {
+    /// Builder with the mesh data.
+    pub builder: MeshBuilder<'static>,
+    /// Name of the geometry, or its id if it has no name.
+    pub geometry_name: Option<String>,
+    /// Material symbol of the primitive element.
+    pub material: Option<String>,
+}
+
+/// Load mesh data from COLLADA.
+///
+/// A separate mesh is created for each `<triangles>`, `<polylist>` and `<polygons>` element
+/// of every `<mesh>` in the geometry library. Polygons are triangulated as a fan.
+/// Other primitives and geometry kinds are skipped.
+///
+/// `Position` is always loaded, `Normal` and `TexCoord` of the first texture coordinate set
+/// are loaded when the primitive has them.
+/// Unique combinations of position, normal and texture coordinate indices become vertices.
+/// Coordinates are loaded as they are, `<up_axis>` and `<unit>` are not applied.
+pub fn load_from_collada(bytes: &[u8]) -> Result<Vec<ColladaMesh>, ColladaError> {
+    let text = std::str::from_utf8(bytes).map_err(|e| ColladaError::InvalidUtf8 {
+        position: e.valid_up_to(),
+    })?;
+    let root = xml::parse(text).map_err(|e| ColladaError::Xml {
+        line_number: e.line_number,
+        message: e.message,
+    })?;
+
+    let mut meshes = Vec::new();
+    for geometry in root
+        .children("library_geometries")
+        .flat_map(|library| library.children("geometry"))
+    {
+        let name = geometry.attribute("name").or(geometry.attribute("id"));
+        let context = Context {
+            geometry: name.unwrap_or("").to_owned(),
+        };
+        let mesh = match geometry.child("mesh") {
+            Some(mesh) => mesh,
+            None => {
+                warn!("Skipping geometry '{}' without mesh", context.geometry);
+                continue;
+            }
+        };
+
+        let sources = mesh
+            .children("source")
+            .filter_map(|source| Some((source.attribute("id")?, source)))
+            .map(|(id, source)| Ok((id, context.source(source)?)))
+            .collect::<Result<HashMap<_, _>, ColladaError>>()?;
+        let vertices = mesh
+            .child("vertices")
+            .ok_or_else(|| context.invalid("No vertices element"))?;
+
+        for primitive in &mesh.children {
+            let polygons = match primitive.name.as_str() {
+                "triangles" => context.triangles(primitive)?,
+                "polylist" => context.polylist(primitive)?,
+                "polygons" => context.polygons(primitive)?,
+                "source" | "vertices" | "extra" => continue,
+                other => {
+                    warn!(
+                        "Skipping {} primitives of geometry '{}'",
+                        other, context.geometry
+                    );
+                    continue;
+                }
+            };
+            meshes.push(ColladaMesh {
+                builder: context.load_primitive(primitive, vertices, &sources, polygons)?,
+                geometry_name: name.map(str::to_owned),
+                material: primitive.attribute("material").map(str::to_owned),
+            });
+        }
+    }
+    Ok(meshes)
+}
+
+/// Array of floats read through an accessor.
+#[derive(Debug)]
+struct Source {
+    data: Vec<f32>,
+    offset: usize,
+    stride: usize,
+    count: usize,
+}
+
+impl Source {
+    /// Up to three values of element `index`, missing values being zero.
+    fn get(&self, index: usize, context: &Context) -> Result<[f32; 3], ColladaError> {
+        if index >= self.count {
+            return Err(ColladaError::IndexOutOfRange {
+                geometry: context.geometry.clone(),
+                index,
+                len: self.count,
+            });
+        }
+        let start = self.offset + index * self.stride;
+        let mut value = [0.0; 3];
+        for (i, v) in value.iter_mut().enumerate().take(self.stride) {
+            *v = self.data[start + i];
+        }
+        Ok(value)
+    }
+}
+
+/// Input of a primitive element resolved to a source.
+struct Input<'a> {
+    source: &'a Source,
+    /// Offset of the input in each corner of `<p>`.
+    offset: usize,
+}
+
+/// Polygons of a primitive element as corner counts and interleaved `<p>` indices.
+struct Polygons {
+    sizes: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+/// Name of the geometry being loaded, for error reporting.
+struct Context {
+    geometry: String,
+}
+
+impl Context {
+    fn invalid(&self, message: &str) -> ColladaError {
+        ColladaError::Invalid {
+            geometry: self.geometry.clone(),
+            message: message.to_owned(),
+        }
+    }
+
+    fn parse_list<T: std::str::FromStr>(
+        &self,
+        text: &str,
+        what: &str,
+    ) -> Result<Vec<T>, ColladaError> {
+        text.split_whitespace()
+            .map(|value| value.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| self.invalid(&format!("Invalid value in {}", what)))
+    }
+
+    fn parse_attribute(
+        &self,
+        element: &Element,
+        name: &str,
+    ) -> Result<Option<usize>, ColladaError> {
+        element
+            .attribute(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    self.invalid(&format!("Invalid {} attribute of {}", name, element.name))
+                })
+            })
+            .transpose()
+    }
+
+    fn source(&self, source: &Element) -> Result<Source, ColladaError> {
+        let data = match source.child("float_array") {
+            Some(array) => self.parse_list(&array.text, "float_array")?,
+            None => Vec::new(),
+        };
+        let accessor = source
+            .child("technique_common")
+            .and_then(|technique| technique.child("accessor"))
+            .ok_or_else(|| self.invalid("Source without accessor"))?;
+        let count = self.parse_attribute(accessor, "count")?.unwrap_or(0);
+        let offset = self.parse_attribute(accessor, "offset")?.unwrap_or(0);
+        let stride = self.parse_attribute(accessor, "stride")?.unwrap_or(1);
+        if count > 0 {
+            let end = (count - 1)
+                .checked_mul(stride)
+                .and_then(|last| last.checked_add(offset))
+                .and_then(|last| last.checked_add(stride.min(3)));
+            if stride == 0 || !matches!(end, Some(end) if end <= data.len()) {
+                return Err(self.invalid("Accessor reads past the end of float_array"));
+            }
+        }
+        Ok(Source {
+            data,
+            offset,
+            stride,
+            count,
+        })
+    }
+
+    fn triangles(&self, primitive: &Element) -> Result<Polygons, ColladaError> {
+        let count = self.parse_attribute(primitive, "count")?.unwrap_or(0);
+        let indices = match primitive.child("p") {
+            Some(p) => self.parse_list(&p.text, "p")?,
+            None => Vec::new(),
+        };
+        // The count is checked before allocating sizes for it.
+        let stride = self.stride(primitive)?.max(1);
+        let len = count.checked_mul(3).and_then(|len| len.checked_mul(stride));
+        if !matches!(len, Some(len) if len <= indices.len()) {
+            return Err(self.invalid("Not enough indices in triangles"));
+        }
+        Ok(Polygons {
+            sizes: vec![3; count],
+            indices,
+        })
+    }
+
+    fn polylist(&self, primitive: &Element) -> Result<Polygons, ColladaError> {
+        let sizes = match primitive.child("vcount") {
+            Some(vcount) => self.parse_list(&vcount.text, "vcount")?,
+            None => Vec::new(),
+        };
+        let indices = match primitive.child("p") {
+            Some(p) => self.parse_list(&p.text, "p")?,
+            None => Vec::new(),
+        };
+        Ok(Polygons { sizes, indices })
+    }
+
+    fn polygons(&self, primitive: &Element) -> Result<Polygons, ColladaError> {
+        let inputs = self.stride(primitive)?;
+        let mut polygons = Polygons {
+            sizes: Vec::new(),
+            indices: Vec::new(),
+        };
+        // Polygons with holes are in `<ph>` elements, which are skipped.
+        for p in primitive.children("p") {
+            let indices = self.parse_list::<usize>(&p.text, "p")?;
+            polygons.sizes.push(indices.len() / inputs.max(1));
+            polygons.indices.extend(indices);
+        }
+        Ok(polygons)
+    }
+
+    /// Number of indices per corner of a primitive element.
+    fn stride(&self, primitive: &Element) -> Result<usize, ColladaError> {
+        primitive
+            .children("input")
+            .map(|input| Ok(self.parse_attribute(input, "offset")?.unwrap_or(0) + 1))
+            .try_fold(0, |max, offset: Result<usize, ColladaError>| {
+                Ok(max.max(offset?))
+            })
+    }
+
+    /// Resolve `#id` reference to a source.
+    fn resolve<'a>(
+        &self,
+        sources: &'a HashMap<&str, Source>,
+        reference: Option<&str>,
+    ) -> Result<&'a Source, ColladaError> {
+        let reference = reference.ok_or_else(|| self.invalid("Input without source"))?;
+        let id = reference.trim_start_matches('#');
+        sources.get(id).ok_or_else(|| ColladaError::MissingSource {
+            geometry: self.geometry.clone(),
+            id: id.to_owned(),
+        })
+    }
+
+    fn load_primitive(
+        &self,
+        primitive: &Element,
+        vertices: &Element,
+        sources: &HashMap<&str, Source>,
+        polygons: Polygons,
+    ) -> Result<MeshBuilder<'static>, ColladaError> {
+        let stride = self.stride(primitive)?;
+        let mut vertex = None;
+        let mut position = None;
+        let mut normal = None;
+        let mut tex_coord = None;
+
+        // Inputs of `<vertices>` share the index of the `VERTEX` input.
+        for input in primitive.children("input") {
+            let offset = self.parse_attribute(input, "offset")?.unwrap_or(0);
+            match input.attribute("semantic") {
+                Some("VERTEX") => {
+                    vertex = Some(offset);
+                    for shared in vertices.children("input") {
+                        let source = self.resolve(sources, shared.attribute("source"))?;
+                        let input = Some(Input { source, offset });
+                        match shared.attribute("semantic") {
+                            Some("POSITION") => position = input,
+                            Some("NORMAL") => normal = normal.or(input),
+                            Some("TEXCOORD") => tex_coord = tex_coord.or(input),
+                            _ => {}
+                        }
+                    }
+                }
+                Some("NORMAL") => {
+                    let source = self.resolve(sources, input.attribute("source"))?;
+                    normal = Some(Input { source, offset });
+                }
+                Some("TEXCOORD") if tex_coord.is_none() => {
+                    let source = self.resolve(sources, input.attribute("source"))?;
+                    tex_coord = Some(Input { source, offset });
+                }
+                _ => {}
+            }
+        }
+        if vertex.is_none() {
+            return Err(self.invalid(&format!("{} without VERTEX input", primitive.name)));
+        }
+        let position = position.ok_or_else(|| self.invalid("Vertices without POSITION input"))?;
+
+        // Sizes come from `<vcount>`, so the sums may overflow.
+        let corner_count = polygons
+            .sizes
+            .iter()
+            .try_fold(0usize, |sum, &size| sum.checked_add(size));
+        let len = corner_count.and_then(|count| count.checked_mul(stride));
+        let corner_count = match (corner_count, len) {
+            (Some(corner_count), Some(len)) if len <= polygons.indices.len() => corner_count,
+            _ => {
+                return Err(self.invalid(&format!("Not enough indices in {}", primitive.name)));
+            }
+        };
+        let normal_offset = normal.as_ref().map(|input| input.offset);
+        let tex_coord_offset = tex_coord.as_ref().map(|input| input.offset);
+
+        // Corners are keyed by their position, normal and texture coordinate indices.
+        let mut corners = Vec::with_capacity(corner_count);
+        let mut first = 0;
+        for &size in &polygons.sizes {
+            let key = |i: usize| {
+                let start = (first + i) * stride;
+                (
+                    polygons.indices[start + position.offset],
+                    normal_offset.map(|offset| polygons.indices[start + offset]),
+                    tex_coord_offset.map(|offset| polygons.indices[start + offset]),
+                )
+            };
+            for i in 2..size {
+                corners.push(key(0));
+                corners.push(key(i - 1));
+                corners.push(key(i));
+            }
+            first += size;
+        }
+
+        let (unique, indices) = weld(&corners);
+        let mut builder = MeshBuilder::new();
+        builder.set_indices_auto(indices);
+        builder.add_vertices(
+            unique
+                .iter()
+                .map(|&&(p, _, _)| Ok(Position(position.source.get(p, self)?)))
+                .collect::<Result<Vec<_>, ColladaError>>()?,
+        );
+        if let Some(normal) = &normal {
+            builder.add_vertices(
+                unique
+                    .iter()
+                    .map(|&&(_, n, _)| Ok(Normal(normal.source.get(n.unwrap(), self)?)))
+                    .collect::<Result<Vec<_>, ColladaError>>()?,
+            );
+        }
+        if let Some(tex_coord) = &tex_coord {
+            builder.add_vertices(
+                unique
+                    .iter()
+                    .map(|&&(_, _, t)| {
+                        let [u, v, _] = tex_coord.source.get(t.unwrap(), self)?;
+                        Ok(TexCoord([u, v]))
+                    })
+                    .collect::<Result<Vec<_>, ColladaError>>()?,
+            );
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Quad made of two triangles sharing normals, and the same quad as a single polygon.
+    const QUAD: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <library_geometries>
+    <geometry id="quad-mesh" name="Quad">
+      <mesh>
+        <source id="positions">
+          <float_array id="positions-array" count="12">0 0 0 1 0 0 0 1 0 1 1 0</float_array>
+          <technique_common>
+            <accessor source="#positions-array" count="4" stride="3">
+              <param name="X" type="float"/><param name="Y" type="float"/><param name="Z" type="float"/>
+            </accessor>
+          </technique_common>
+        </source>
+        <source id="normals">
+          <float_array id="normals-array" count="3">0 0 1</float_array>
+          <technique_common>
+            <accessor source="#normals-array" count="1" stride="3"/>
+          </technique_common>
+        </source>
+        <source id="uvs">
+          <float_array id="uvs-array" count="8">0 0 1 0 0 1 1 1</float_array>
+          <technique_common>
+            <accessor source="#uvs-array" count="4" stride="2"/>
+          </technique_common>
+        </source>
+        <vertices id="vertices">
+          <input semantic="POSITION" source="#positions"/>
+        </vertices>
+        <triangles material="front" count="2">
+          <input semantic="VERTEX" source="#vertices" offset="0"/>
+          <input semantic="NORMAL" source="#normals" offset="1"/>
+          <input semantic="TEXCOORD" source="#uvs" offset="2" set="0"/>
+          <p>0 0 0 1 0 1 2 0 2 2 0 2 1 0 1 3 0 3</p>
+        </triangles>
+        <polylist material="back" count="1">
+          <input semantic="VERTEX" source="#vertices" offset="0"/>
+          <vcount>4</vcount>
+          <p>0 2 3 1</p>
+        </polylist>
+      </mesh>
+    </geometry>
+  </library_geometries>
+</COLLADA>
+"##;
+
+    #[test]
+    fn test_load_from_collada() {
+        let meshes = load_from_collada(QUAD.as_bytes()).unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].geometry_name.as_ref().unwrap(), "Quad");
+        assert_eq!(meshes[0].material.as_ref().unwrap(), "front");
+        assert_eq!(meshes[1].material.as_ref().unwrap(), "back");
+        for mesh in &meshes {
+            assert_eq!(
+                mesh.builder.bounding_box(),
+                Some(([0.0, 0.0, 0.0], [1.0, 1.0, 0.0]))
+            );
+        }
+        assert_eq!(
+            meshes[0]
+                .builder
+                .read_attribute::<TexCoord>()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(
+            meshes[0].builder.read_attribute::<Normal>().unwrap(),
+            vec![Normal([0.0, 0.0, 1.0]); 4]
+        );
+        assert!(meshes[1].builder.read_attribute::<Normal>().is_none());
+    }
+
+    #[test]
+    fn test_load_from_collada_errors() {
+        let missing = QUAD.replace("source=\"#normals\"", "source=\"#missing\"");
+        assert_eq!(
+            load_from_collada(missing.as_bytes()).unwrap_err(),
+            ColladaError::MissingSource {
+                geometry: "Quad".to_owned(),
+                id: "missing".to_owned(),
+            }
+        );
+        let out_of_range = QUAD.replace("<p>0 2 3 1</p>", "<p>0 2 3 4</p>");
+        assert_eq!(
+            load_from_collada(out_of_range.as_bytes()).unwrap_err(),
+            ColladaError::IndexOutOfRange {
+                geometry: "Quad".to_owned(),
+                index: 4,
+                len: 4,
+            }
+        );
+        let not_enough = |primitive: &str| ColladaError::Invalid {
+            geometry: "Quad".to_owned(),
+            message: format!("Not enough indices in {}", primitive),
+        };
+        let huge_count = QUAD.replace(
+            "<triangles material=\"front\" count=\"2\">",
+            "<triangles material=\"front\" count=\"4000000000\">",
+        );
+        assert_eq!(
+            load_from_collada(huge_count.as_bytes()).unwrap_err(),
+            not_enough("triangles")
+        );
+        let overflowing_count = QUAD.replace(
+            "<triangles material=\"front\" count=\"2\">",
+            &format!(
+                "<triangles material=\"front\" count=\"{}\">",
+                usize::MAX / 2
+            ),
+        );
+        assert_eq!(
+            load_from_collada(overflowing_count.as_bytes()).unwrap_err(),
+            not_enough("triangles")
+        );
+        let huge_vcount = QUAD.replace("<vcount>4</vcount>", "<vcount>4000000000</vcount>");
+        assert_eq!(
+            load_from_collada(huge_vcount.as_bytes()).unwrap_err(),
+            not_enough("polylist")
+        );
+        let overflowing_vcount = QUAD.replace(
+            "<vcount>4</vcount>",
+            &format!("<vcount>{} 4</vcount>", usize::MAX),
+        );
+        assert_eq!(
+            load_from_collada(overflowing_vcount.as_bytes()).unwrap_err(),
+            not_enough("polylist")
+        );
+        let huge_accessor = QUAD.replace(
+            "<accessor source=\"#uvs-array\" count=\"4\"",
+            &format!("<accessor source=\"#uvs-array\" count=\"{}\"", usize::MAX),
+        );
+        assert_eq!(
+            load_from_collada(huge_accessor.as_bytes()).unwrap_err(),
+            ColladaError::Invalid {
+                geometry: "Quad".to_owned(),
+                message: "Accessor reads past the end of float_array".to_owned(),
+            }
+        );
+        match load_from_collada(b"<COLLADA>\n<library_geometries>\n</COLLADA>").unwrap_err() {
+            ColladaError::Xml { line_number, .. } => assert_eq!(line_number, 3),
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+}
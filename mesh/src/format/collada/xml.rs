@@ -0,0 +1,273 @@
+//! Minimal XML parser, sufficient for COLLADA documents.
+//!
+//! Elements, attributes, text, CDATA sections and predefined or numeric entities are supported.
+//! Comments, processing instructions and document type declarations are skipped.
+//! Namespace prefixes are kept as part of the names.
+
+/// XML element with its attributes, child elements and concatenated text content.
+#[derive(Debug, Default)]
+pub(super) struct Element {
+    pub(super) name: String,
+    pub(super) attributes: Vec<(String, String)>,
+    pub(super) children: Vec<Element>,
+    pub(super) text: String,
+}
+
+impl Element {
+    /// Value of the attribute `name`.
+    pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Child elements named `name`.
+    pub(super) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// First child element named `name`.
+    pub(super) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+/// XML syntax error.
+#[derive(Debug, PartialEq)]
+pub(super) struct XmlError {
+    pub(super) line_number: usize,
+    pub(super) message: String,
+}
+
+/// Parse XML document, returning its root element.
+pub(super) fn parse(text: &str) -> Result<Element, XmlError> {
+    let mut parser = Parser { text, position: 0 };
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+
+    while parser.position < text.len() {
+        let rest = parser.rest();
+        if rest.starts_with("<!--") {
+            parser.skip_past("-->")?;
+        } else if rest.starts_with("<![CDATA[") {
+            parser.position += "<![CDATA[".len();
+            let start = parser.position;
+            parser.skip_past("]]>")?;
+            let data = &text[start..parser.position - "]]>".len()];
+            match stack.last_mut() {
+                Some(element) => element.text.push_str(data),
+                None => return Err(parser.error("CDATA outside of root element")),
+            }
+        } else if rest.starts_with("<?") {
+            parser.skip_past("?>")?;
+        } else if rest.starts_with("<!") {
+            parser.skip_past(">")?;
+        } else if rest.starts_with("</") {
+            parser.position += "</".len();
+            let name = parser.name()?;
+            parser.skip_whitespace();
+            parser.expect(">")?;
+            let element = match stack.pop() {
+                Some(element) if element.name == name => element,
+                _ => return Err(parser.error(&format!("Unexpected closing tag '{}'", name))),
+            };
+            parser.close(&mut stack, &mut root, element)?;
+        } else if rest.starts_with('<') {
+            parser.position += "<".len();
+            let (element, empty) = parser.start_tag()?;
+            if empty {
+                parser.close(&mut stack, &mut root, element)?;
+            } else {
+                stack.push(element);
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let data = &rest[..end];
+            match stack.last_mut() {
+                Some(element) => {
+                    let data = parser.decode(data)?;
+                    element.text.push_str(&data);
+                }
+                None if data.trim().is_empty() => {}
+                None => return Err(parser.error("Text outside of root element")),
+            }
+            parser.position += end;
+        }
+    }
+
+    if let Some(element) = stack.last() {
+        return Err(parser.error(&format!("Element '{}' is not closed", element.name)));
+    }
+    root.ok_or_else(|| parser.error("No root element"))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn error(&self, message: &str) -> XmlError {
+        XmlError {
+            line_number: self.text[..self.position].matches('\n').count() + 1,
+            message: message.to_owned(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Move past the next occurrence of `pattern`.
+    fn skip_past(&mut self, pattern: &str) -> Result<(), XmlError> {
+        match self.rest().find(pattern) {
+            Some(offset) => {
+                self.position += offset + pattern.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("Expected '{}'", pattern))),
+        }
+    }
+
+    fn expect(&mut self, pattern: &str) -> Result<(), XmlError> {
+        if self.rest().starts_with(pattern) {
+            self.position += pattern.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", pattern)))
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || "/>=".contains(c))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("Expected name"));
+        }
+        self.position += end;
+        Ok(&rest[..end])
+    }
+
+    /// Parse start tag after the opening `<`.
+    /// Returns the element and whether it is an empty-element tag.
+    fn start_tag(&mut self) -> Result<(Element, bool), XmlError> {
+        let mut element = Element {
+            name: self.name()?.to_owned(),
+            ..Element::default()
+        };
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += "/>".len();
+                return Ok((element, true));
+            }
+            if self.rest().starts_with('>') {
+                self.position += ">".len();
+                return Ok((element, false));
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ '"') | Some(quote @ '\'') => quote,
+                _ => return Err(self.error("Expected quoted attribute value")),
+            };
+            self.position += 1;
+            let rest = self.rest();
+            let end = rest
+                .find(quote)
+                .ok_or_else(|| self.error("Attribute value is not closed"))?;
+            let value = self.decode(&rest[..end])?;
+            self.position += end + 1;
+            element.attributes.push((name.to_owned(), value));
+        }
+    }
+
+    /// Attach finished element to its parent, or make it the root.
+    fn close(
+        &self,
+        stack: &mut [Element],
+        root: &mut Option<Element>,
+        element: Element,
+    ) -> Result<(), XmlError> {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(element),
+            None if root.is_none() => *root = Some(element),
+            None => return Err(self.error("Multiple root elements")),
+        }
+        Ok(())
+    }
+
+    /// Replace entity references in `data`.
+    fn decode(&self, data: &str) -> Result<String, XmlError> {
+        let mut decoded = String::with_capacity(data.len());
+        let mut rest = data;
+        while let Some(start) = rest.find('&') {
+            decoded.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find(';')
+                .ok_or_else(|| self.error("Entity reference is not closed"))?;
+            let entity = &rest[start + 1..start + end];
+            let c = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32),
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse().ok().and_then(std::char::from_u32)
+                }
+                _ => None,
+            };
+            match c {
+                Some(c) => decoded.push(c),
+                None => return Err(self.error(&format!("Unknown entity '&{};'", entity))),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        decoded.push_str(rest);
+        Ok(decoded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!-- comment -->\n\
+             <a x='1' y=\"&lt;2&gt;\"><b/>text &amp; <![CDATA[<raw>]]><b>3</b></a>\n",
+        )
+        .unwrap();
+        assert_eq!(root.name, "a");
+        assert_eq!(root.attribute("x"), Some("1"));
+        assert_eq!(root.attribute("y"), Some("<2>"));
+        assert_eq!(root.attribute("z"), None);
+        assert_eq!(root.children("b").count(), 2);
+        assert_eq!(root.children[1].text, "3");
+        assert_eq!(root.text, "text & <raw>");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("<a>\n<b>\n</a>").unwrap_err().line_number, 3);
+        assert!(parse("<a></a><b/>").is_err());
+        assert!(parse("<a>&unknown;</a>").is_err());
+        assert!(parse("<a x=1/>").is_err());
+        assert!(parse("<a>").is_err());
+    }
+}
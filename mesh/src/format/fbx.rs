@@ -0,0 +1,475 @@
+//! Loading mesh data from ASCII FBX format.
+
+use {
+    self::ascii::Node,
+    super::weld::weld,
+    crate::{mesh::MeshBuilder, Normal, Position, TexCoord},
+};
+
+mod ascii;
+
+/// FBX loading error.
+#[derive(Debug, PartialEq)]
+pub enum FbxError {
+    /// The data is binary FBX, which is not supported.
+    Binary,
+    /// The passed bytes were improper UTF-8 data.
+    InvalidUtf8 {
+        /// Offset of the first invalid byte.
+        position: usize,
+    },
+    /// Parsing of the document failed.
+    Parse {
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// A geometry is malformed or uses unsupported features.
+    Invalid {
+        /// Name of the geometry.
+        geometry: String,
+        /// Description of the error.
+        message: String,
+    },
+    /// A geometry references element of an array that doesn't exist.
+    IndexOutOfRange {
+        /// Name of the geometry.
+        geometry: String,
+        /// Name of the referenced array.
+        array: &'static str,
+        /// The invalid index.
+        index: usize,
+        /// Number of elements in the array.
+        len: usize,
+    },
+}
+
+impl std::error::Error for FbxError {}
+impl std::fmt::Display for FbxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FbxError::Binary => write!(f, "Binary FBX is not supported"),
+            FbxError::InvalidUtf8 { position } => {
+                write!(f, "Invalid UTF-8 at byte {} of FBX document", position)
+            }
+            FbxError::Parse {
+                line_number,
+                message,
+            } => write!(
+                f,
+                "Error parsing FBX document at line {}: {}",
+                line_number, message
+            ),
+            FbxError::Invalid { geometry, message } => {
+                write!(f, "Invalid geometry '{}': {}", geometry, message)
+            }
+            FbxError::IndexOutOfRange {
+                geometry,
+                array,
+                index,
+                len,
+            } => write!(
+                f,
+                "Geometry '{}' references element {} of {} but only {} are defined",
+                geometry, index, array, len
+            ),
+        }
+    }
+}
+
+/// Mesh loaded from a geometry of FBX document.
+#[derive(Debug)]
+pub struct FbxMesh {
+    /// Builder with the mesh data.
+    pub builder: MeshBuilder<'static>,
+    /// Name of the geometry without the `Geometry::` prefix.
+    pub geometry_name: Option<String>,
+}
+
+/// Load mesh data from ASCII FBX.
+///
+/// A mesh is created for each `Geometry` node, or `Model` node with vertices for FBX 6.
+/// Polygons are triangulated as a fan. `Position` is always loaded,
+/// `Normal` and `TexCoord` are loaded from the first normal and UV layers when present.
+/// Unique combinations of vertex index, normal and UV become vertices.
+///
+/// Positions are in the space of the geometry, model transforms are not applied.
+/// Materials assigned per polygon are not split into separate meshes.
+pub fn load_from_fbx_ascii(bytes: &[u8]) -> Result<Vec<FbxMesh>, FbxError> {
+    if bytes.starts_with(b"Kaydara FBX Binary") {
+        return Err(FbxError::Binary);
+    }
+    let text = std::str::from_utf8(bytes).map_err(|e| FbxError::InvalidUtf8 {
+        position: e.valid_up_to(),
+    })?;
+    let root = ascii::parse(text).map_err(|e| FbxError::Parse {
+        line_number: e.line_number,
+        message: e.message,
+    })?;
+
+    root.children("Objects")
+        .flat_map(|objects| objects.children.iter())
+        .filter(|node| {
+            (node.name == "Geometry" || node.name == "Model") && node.child("Vertices").is_some()
+        })
+        .map(load_geometry)
+        .collect()
+}
+
+fn load_geometry(node: &Node) -> Result<FbxMesh, FbxError> {
+    let name = node
+        .string()
+        .map(|name| name.rsplit("::").next().unwrap_or(name));
+    let geometry = Geometry {
+        name: name.unwrap_or("").to_owned(),
+    };
+
+    let vertices = geometry.numbers(node, "Vertices")?;
+    let vertex_count = vertices.len() / 3;
+    let polygon_vertex_index = geometry
+        .numbers(node, "PolygonVertexIndex")?
+        .into_iter()
+        .map(|index| index as i64)
+        .collect::<Vec<_>>();
+    let (polygon_vertices, sizes) = split_polygons(&polygon_vertex_index)
+        .ok_or_else(|| geometry.invalid("Last polygon is not terminated"))?;
+
+    let normals = geometry.layer(node, "LayerElementNormal", "Normals", "NormalsIndex")?;
+    let uvs = geometry.layer(node, "LayerElementUV", "UV", "UVIndex")?;
+
+    // Corners are keyed by their vertex index and the values of their normal and UV,
+    // since normals and UVs mapped by polygon vertex are usually stored for every corner.
+    let mut corner_keys = Vec::with_capacity(polygon_vertices.len());
+    let mut corner = 0;
+    for (polygon, &size) in sizes.iter().enumerate() {
+        for &vertex in &polygon_vertices[corner..corner + size] {
+            if vertex >= vertex_count {
+                return Err(geometry.out_of_range("Vertices", vertex, vertex_count));
+            }
+            let normal = match &normals {
+                Some(layer) => {
+                    let n = geometry.element(layer, corner, vertex, polygon, 3)?;
+                    Some([bits(n[0]), bits(n[1]), bits(n[2])])
+                }
+                None => None,
+            };
+            let uv = match &uvs {
+                Some(layer) => {
+                    let t = geometry.element(layer, corner, vertex, polygon, 2)?;
+                    Some([bits(t[0]), bits(t[1])])
+                }
+                None => None,
+            };
+            corner_keys.push((vertex, normal, uv));
+            corner += 1;
+        }
+    }
+
+    let mut triangles = Vec::with_capacity(corner_keys.len() * 3);
+    let mut first = 0;
+    for &size in &sizes {
+        for i in 2..size {
+            triangles.push(corner_keys[first]);
+            triangles.push(corner_keys[first + i - 1]);
+            triangles.push(corner_keys[first + i]);
+        }
+        first += size;
+    }
+
+    let (unique, indices) = weld(&triangles);
+    let mut builder = MeshBuilder::new();
+    builder.set_indices_auto(indices);
+    builder.add_vertices(
+        unique
+            .iter()
+            .map(|&&(v, _, _)| {
+                let p = &vertices[v * 3..v * 3 + 3];
+                Position([p[0] as f32, p[1] as f32, p[2] as f32])
+            })
+            .collect::<Vec<_>>(),
+    );
+    if normals.is_some() {
+        builder.add_vertices(
+            unique
+                .iter()
+                .map(|&&(_, n, _)| {
+                    let [x, y, z] = n.unwrap();
+                    Normal([f32::from_bits(x), f32::from_bits(y), f32::from_bits(z)])
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+    if uvs.is_some() {
+        builder.add_vertices(
+            unique
+                .iter()
+                .map(|&&(_, _, t)| {
+                    let [u, v] = t.unwrap();
+                    TexCoord([f32::from_bits(u), f32::from_bits(v)])
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    Ok(FbxMesh {
+        builder,
+        geometry_name: name.map(str::to_owned),
+    })
+}
+
+/// Bits of the value as `f32`, with negative zero folded into zero.
+fn bits(value: f64) -> u32 {
+    (value as f32 + 0.0).to_bits()
+}
+
+/// Split `PolygonVertexIndex` into vertex indices and polygon sizes.
+///
+/// The last index of each polygon is stored as `-(index + 1)`, i.e. bitwise negated.
+/// Returns `None` if the last polygon is not terminated by a negative index.
+fn split_polygons(polygon_vertex_index: &[i64]) -> Option<(Vec<usize>, Vec<usize>)> {
+    let mut vertices = Vec::with_capacity(polygon_vertex_index.len());
+    let mut sizes = Vec::new();
+    let mut size = 0;
+    for &index in polygon_vertex_index {
+        size += 1;
+        if index < 0 {
+            vertices.push(!index as usize);
+            sizes.push(size);
+            size = 0;
+        } else {
+            vertices.push(index as usize);
+        }
+    }
+    if size == 0 {
+        Some((vertices, sizes))
+    } else {
+        None
+    }
+}
+
+/// How layer elements are assigned to polygon vertices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mapping {
+    ByPolygonVertex,
+    ByVertex,
+    ByPolygon,
+    AllSame,
+}
+
+/// Layer element such as normals or UVs.
+struct Layer {
+    mapping: Mapping,
+    data_name: &'static str,
+    data: Vec<f64>,
+    /// Indices into `data` for `IndexToDirect` reference.
+    index: Option<Vec<f64>>,
+}
+
+/// Name of the geometry being loaded, for error reporting.
+struct Geometry {
+    name: String,
+}
+
+impl Geometry {
+    fn invalid(&self, message: &str) -> FbxError {
+        FbxError::Invalid {
+            geometry: self.name.clone(),
+            message: message.to_owned(),
+        }
+    }
+
+    fn out_of_range(&self, array: &'static str, index: usize, len: usize) -> FbxError {
+        FbxError::IndexOutOfRange {
+            geometry: self.name.clone(),
+            array,
+            index,
+            len,
+        }
+    }
+
+    fn numbers(&self, node: &Node, name: &str) -> Result<Vec<f64>, FbxError> {
+        node.child(name)
+            .ok_or_else(|| self.invalid(&format!("No {}", name)))?
+            .numbers()
+            .ok_or_else(|| self.invalid(&format!("Invalid value in {}", name)))
+    }
+
+    /// Read the first layer element named `name`, or `None` if there is none.
+    fn layer(
+        &self,
+        node: &Node,
+        name: &str,
+        data_name: &'static str,
+        index_name: &str,
+    ) -> Result<Option<Layer>, FbxError> {
+        let layer = match node.child(name) {
+            Some(layer) => layer,
+            None => return Ok(None),
+        };
+        let string = |name: &str| layer.child(name).and_then(Node::string);
+        let mapping = match string("MappingInformationType") {
+            Some("ByPolygonVertex") => Mapping::ByPolygonVertex,
+            Some("ByVertice") | Some("ByVertex") => Mapping::ByVertex,
+            Some("ByPolygon") => Mapping::ByPolygon,
+            Some("AllSame") => Mapping::AllSame,
+            other => {
+                return Err(self.invalid(&format!(
+                    "Unsupported mapping {:?} of {}",
+                    other.unwrap_or(""),
+                    name
+                )))
+            }
+        };
+        let index = match string("ReferenceInformationType") {
+            Some("Direct") => None,
+            Some("IndexToDirect") | Some("Index") => Some(self.numbers(layer, index_name)?),
+            other => {
+                return Err(self.invalid(&format!(
+                    "Unsupported reference {:?} of {}",
+                    other.unwrap_or(""),
+                    name
+                )))
+            }
+        };
+        Ok(Some(Layer {
+            mapping,
+            data_name,
+            data: self.numbers(layer, data_name)?,
+            index,
+        }))
+    }
+
+    /// Values of the layer element with `components` values for a polygon vertex.
+    fn element<'a>(
+        &self,
+        layer: &'a Layer,
+        corner: usize,
+        vertex: usize,
+        polygon: usize,
+        components: usize,
+    ) -> Result<&'a [f64], FbxError> {
+        let mapped = match layer.mapping {
+            Mapping::ByPolygonVertex => corner,
+            Mapping::ByVertex => vertex,
+            Mapping::ByPolygon => polygon,
+            Mapping::AllSame => 0,
+        };
+        let element = match &layer.index {
+            Some(index) => match index.get(mapped) {
+                Some(&element) if element >= 0.0 => element as usize,
+                Some(_) => return Err(self.invalid("Negative layer element index")),
+                None => return Err(self.out_of_range("layer element index", mapped, index.len())),
+            },
+            None => mapped,
+        };
+        let len = layer.data.len() / components;
+        if element >= len {
+            return Err(self.out_of_range(layer.data_name, element, len));
+        }
+        Ok(&layer.data[element * components..(element + 1) * components])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Quad and triangle sharing an edge, with per polygon vertex normals and indexed UVs.
+    const MESH: &str = r#"; FBX 7.4.0 project file
+FBXHeaderExtension:  {
+	FBXHeaderVersion: 1003
+}
+Objects:  {
+	Geometry: 1000, "Geometry::Shape", "Mesh" {
+		Vertices: *15 {
+			a: 0,0,0,1,0,0,1,1,0,0,1,0,2,0,0
+		}
+		PolygonVertexIndex: *7 {
+			a: 0,1,2,-4,1,4,-3
+		}
+		GeometryVersion: 124
+		LayerElementNormal: 0 {
+			Version: 101
+			Name: ""
+			MappingInformationType: "ByPolygonVertex"
+			ReferenceInformationType: "Direct"
+			Normals: *21 {
+				a: 0,0,1,0,0,1,0,0,1,0,0,1,0,0,1,0,0,1,0,0,1
+			}
+		}
+		LayerElementUV: 0 {
+			Version: 101
+			Name: "map1"
+			MappingInformationType: "ByPolygonVertex"
+			ReferenceInformationType: "IndexToDirect"
+			UV: *10 {
+				a: 0,0,1,0,1,1,0,1,2,0
+			}
+			UVIndex: *7 {
+				a: 0,1,2,3,1,4,2
+			}
+		}
+	}
+	Model: 2000, "Model::Shape", "Mesh" {
+		Version: 232
+	}
+}
+"#;
+
+    #[test]
+    fn test_split_polygons() {
+        assert_eq!(
+            split_polygons(&[0, 1, 2, -4, 1, 4, -3]),
+            Some((vec![0, 1, 2, 3, 1, 4, 2], vec![4, 3]))
+        );
+        // Vertex 0 terminating a polygon is stored as -1.
+        assert_eq!(split_polygons(&[2, 1, -1]), Some((vec![2, 1, 0], vec![3])));
+        assert_eq!(split_polygons(&[0, 1, 2]), None);
+        assert_eq!(split_polygons(&[]), Some((vec![], vec![])));
+    }
+
+    #[test]
+    fn test_load_from_fbx_ascii() {
+        let meshes = load_from_fbx_ascii(MESH.as_bytes()).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].geometry_name.as_ref().unwrap(), "Shape");
+        let builder = &meshes[0].builder;
+        assert_eq!(builder.read_indices().unwrap().len(), 9);
+        assert_eq!(builder.read_attribute::<Position>().unwrap().len(), 5);
+        assert_eq!(
+            builder.read_attribute::<Normal>().unwrap(),
+            vec![Normal([0.0, 0.0, 1.0]); 5]
+        );
+        assert_eq!(
+            builder.bounding_box(),
+            Some(([0.0, 0.0, 0.0], [2.0, 1.0, 0.0]))
+        );
+    }
+
+    #[test]
+    fn test_load_from_fbx_ascii_errors() {
+        assert_eq!(
+            load_from_fbx_ascii(b"Kaydara FBX Binary  \x00").unwrap_err(),
+            FbxError::Binary
+        );
+        let unterminated = MESH.replace("a: 0,1,2,-4,1,4,-3", "a: 0,1,2,-4,1,4,2");
+        assert_eq!(
+            load_from_fbx_ascii(unterminated.as_bytes()).unwrap_err(),
+            FbxError::Invalid {
+                geometry: "Shape".to_owned(),
+                message: "Last polygon is not terminated".to_owned(),
+            }
+        );
+        let out_of_range = MESH.replace("a: 0,1,2,3,1,4,2", "a: 0,1,2,3,1,5,2");
+        assert_eq!(
+            load_from_fbx_ascii(out_of_range.as_bytes()).unwrap_err(),
+            FbxError::IndexOutOfRange {
+                geometry: "Shape".to_owned(),
+                array: "UV",
+                index: 5,
+                len: 5,
+            }
+        );
+    }
+}
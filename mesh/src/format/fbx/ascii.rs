@@ -0,0 +1,243 @@
+//! Parser of the node tree of ASCII FBX documents.
+//!
+//! Every line `Name: value, value, ... { children }` is a node.
+//! Values may continue on the following lines after a trailing comma.
+//! Arrays of FBX 7 are written as `Name: *count { a: values }`,
+//! which `Node::numbers` reads the same way as inline values of FBX 6.
+
+/// Property value of a node.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Value {
+    Number(f64),
+    String(String),
+    /// Bare word that is not a number, such as `T` or `*24`.
+    Word(String),
+}
+
+/// Node of the document with its values and child nodes.
+#[derive(Debug, Default)]
+pub(super) struct Node {
+    pub(super) name: String,
+    pub(super) values: Vec<Value>,
+    pub(super) children: Vec<Node>,
+}
+
+impl Node {
+    /// Child nodes named `name`.
+    pub(super) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// First child node named `name`.
+    pub(super) fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// First string value.
+    pub(super) fn string(&self) -> Option<&str> {
+        self.values.iter().find_map(|value| match value {
+            Value::String(string) => Some(string.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Numbers of the node, read from the `a` child for FBX 7 arrays.
+    /// Returns `None` if any value is not a number.
+    pub(super) fn numbers(&self) -> Option<Vec<f64>> {
+        let node = self.child("a").unwrap_or(self);
+        node.values
+            .iter()
+            .filter(|value| !matches!(value, Value::Word(word) if word.starts_with('*')))
+            .map(|value| match value {
+                Value::Number(number) => Some(*number),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// FBX syntax error.
+#[derive(Debug, PartialEq)]
+pub(super) struct ParseError {
+    pub(super) line_number: usize,
+    pub(super) message: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// Word followed by `:`.
+    Name(String),
+    String(String),
+    Word(String),
+    Comma,
+    Open,
+    Close,
+}
+
+/// Parse document into a root node holding the top level nodes as children.
+pub(super) fn parse(text: &str) -> Result<Node, ParseError> {
+    let tokens = tokenize(text)?;
+    let mut tokens = tokens.into_iter().peekable();
+    let mut stack = vec![Node::default()];
+
+    while let Some((line_number, token)) = tokens.next() {
+        let error = |message: &str| ParseError {
+            line_number,
+            message: message.to_owned(),
+        };
+        match token {
+            Token::Name(name) => {
+                let mut node = Node {
+                    name,
+                    ..Node::default()
+                };
+                while let Some((_, Token::String(_))) | Some((_, Token::Word(_))) = tokens.peek() {
+                    node.values
+                        .push(match tokens.next().map(|(_, token)| token) {
+                            Some(Token::String(string)) => Value::String(string),
+                            Some(Token::Word(word)) => match word.parse() {
+                                Ok(number) => Value::Number(number),
+                                Err(_) => Value::Word(word),
+                            },
+                            _ => unreachable!(),
+                        });
+                    if tokens.peek().map(|(_, token)| token) == Some(&Token::Comma) {
+                        tokens.next();
+                    } else {
+                        break;
+                    }
+                }
+                if tokens.peek().map(|(_, token)| token) == Some(&Token::Open) {
+                    tokens.next();
+                    stack.push(node);
+                } else {
+                    stack.last_mut().unwrap().children.push(node);
+                }
+            }
+            Token::Close if stack.len() > 1 => {
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            Token::Close => return Err(error("Unexpected '}'")),
+            Token::Open => return Err(error("Unexpected '{'")),
+            Token::Comma => return Err(error("Unexpected ','")),
+            Token::String(_) | Token::Word(_) => return Err(error("Expected node name")),
+        }
+    }
+
+    if stack.len() > 1 {
+        return Err(ParseError {
+            line_number: text.lines().count(),
+            message: format!("Node '{}' is not closed", stack.last().unwrap().name),
+        });
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// Split text into tokens paired with their line numbers, skipping `;` comments.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            let c = rest.chars().next().unwrap();
+            let len = match c {
+                ';' => break,
+                ',' | '{' | '}' => {
+                    tokens.push((
+                        line_number,
+                        match c {
+                            ',' => Token::Comma,
+                            '{' => Token::Open,
+                            _ => Token::Close,
+                        },
+                    ));
+                    1
+                }
+                '"' => {
+                    let end = rest[1..].find('"').ok_or_else(|| ParseError {
+                        line_number,
+                        message: "String is not closed".to_owned(),
+                    })?;
+                    tokens.push((line_number, Token::String(rest[1..=end].to_owned())));
+                    end + 2
+                }
+                _ => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || ",{}\":;".contains(c))
+                        .unwrap_or(rest.len());
+                    if end == 0 {
+                        return Err(ParseError {
+                            line_number,
+                            message: format!("Unexpected '{}'", c),
+                        });
+                    }
+                    let word = rest[..end].to_owned();
+                    if rest[end..].starts_with(':') {
+                        tokens.push((line_number, Token::Name(word)));
+                        end + 1
+                    } else {
+                        tokens.push((line_number, Token::Word(word)));
+                        end
+                    }
+                }
+            };
+            rest = rest[len..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = parse(
+            "; comment\n\
+             Objects:  {\n\
+             \tGeometry: 140, \"Geometry::Cube\", \"Mesh\" {\n\
+             \t\tVertices: *6 {\n\
+             \t\t\ta: 0,1,2,\n\
+             3,4,5\n\
+             \t\t}\n\
+             \t\tGeometryVersion: 124\n\
+             \t\tShading: T\n\
+             \t}\n\
+             \tModel: \"Model::Old\", \"Mesh\" {\n\
+             \t\tVertices: 1.5,-2e1\n\
+             \t}\n\
+             }\n",
+        )
+        .unwrap();
+        let objects = root.child("Objects").unwrap();
+        let geometry = objects.child("Geometry").unwrap();
+        assert_eq!(geometry.string(), Some("Geometry::Cube"));
+        assert_eq!(
+            geometry.child("Vertices").unwrap().numbers(),
+            Some(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0])
+        );
+        assert_eq!(
+            geometry.child("Shading").unwrap().values,
+            [Value::Word("T".to_owned())]
+        );
+        assert_eq!(
+            objects
+                .child("Model")
+                .unwrap()
+                .child("Vertices")
+                .unwrap()
+                .numbers(),
+            Some(vec![1.5, -20.0])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("A: {\nB: 1\n}\n}").unwrap_err().line_number, 4);
+        assert!(parse("A: {\nB: 1\n").is_err());
+        assert!(parse("A: \"unclosed\n").is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! Loading mesh data from glTF 2.0 format.
+
+use {
+    crate::{mesh::MeshBuilder, Normal, Position, Tangent, TexCoord},
+    ::gltf::{buffer::Source, mesh::Mode, Gltf},
+    rendy_core::hal::pso::Primitive,
+};
+
+/// glTF loading error.
+#[derive(Debug)]
+pub enum GltfError {
+    /// Parsing of the glTF document failed.
+    Gltf(::gltf::Error),
+    /// Data of a buffer is not available.
+    MissingBuffer {
+        /// Index of the buffer in the document.
+        index: usize,
+    },
+    /// Data of a buffer is shorter than its declared `byteLength`.
+    BufferLength {
+        /// Index of the buffer in the document.
+        index: usize,
+        /// Declared length of the buffer.
+        expected: usize,
+        /// Length of the available data.
+        actual: usize,
+    },
+    /// The document contains no meshes.
+    NoMesh,
+    /// A primitive has no `POSITION` attribute.
+    MissingPositions {
+        /// Index of the primitive within the mesh.
+        primitive: usize,
+    },
+    /// Indices of a primitive reference a vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Index of the primitive within the mesh.
+        primitive: usize,
+        /// The invalid index.
+        index: usize,
+        /// Number of vertices of the primitive.
+        len: usize,
+    },
+}
+
+impl std::error::Error for GltfError {}
+impl std::fmt::Display for GltfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GltfError::Gltf(e) => write!(f, "Error parsing glTF document: {}", e),
+            GltfError::MissingBuffer { index } => {
+                write!(f, "Data of glTF buffer {} is not available", index)
+            }
+            GltfError::BufferLength {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "glTF buffer {} has {} bytes of data but declares {}",
+                index, actual, expected
+            ),
+            GltfError::NoMesh => write!(f, "glTF document contains no meshes"),
+            GltfError::MissingPositions { primitive } => {
+                write!(f, "glTF primitive {} has no positions", primitive)
+            }
+            GltfError::IndexOutOfRange {
+                primitive,
+                index,
+                len,
+            } => write!(
+                f,
+                "glTF primitive {} references vertex {} but only {} are defined",
+                primitive, index, len
+            ),
+        }
+    }
+}
+
+/// Single primitive of a glTF mesh.
+#[derive(Debug)]
+pub struct GltfPrimitive {
+    /// Builder with the primitive data.
+    pub builder: MeshBuilder<'static>,
+    /// Index of the material used by the primitive.
+    pub material: Option<usize>,
+}
+
+/// Load primitives of the first mesh from glTF.
+///
+/// Both `.gltf` json and binary `.glb` data are accepted.
+/// Buffers must be either embedded as data URIs or stored in the `.glb` binary chunk.
+/// Use `load_from_gltf_with_buffers` for documents referencing external files.
+pub fn load_from_gltf(bytes: &[u8]) -> Result<Vec<GltfPrimitive>, GltfError> {
+    load_from_gltf_with_buffers(bytes, |_| None)
+}
+
+/// Load primitives of the first mesh from glTF, resolving external buffers.
+///
+/// `buffer_resolver` is called with the URI of each buffer that is not embedded
+/// and should return the contents of that file, or `None` if it is not available.
+///
+/// `Position`, `Normal`, `Tangent` and `TexCoord` attributes are read from the accessors
+/// as they are, glTF indices are used directly.
+/// Buffers shorter than their declared length and indices referencing
+/// missing vertices are rejected.
+/// Triangle fans and line loops are converted to lists and strips respectively.
+pub fn load_from_gltf_with_buffers<F>(
+    bytes: &[u8],
+    mut buffer_resolver: F,
+) -> Result<Vec<GltfPrimitive>, GltfError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let Gltf { document, mut blob } = Gltf::from_slice(bytes).map_err(GltfError::Gltf)?;
+
+    let buffers = document
+        .buffers()
+        .map(|buffer| {
+            let data = match buffer.source() {
+                Source::Bin => blob.take(),
+                Source::Uri(uri) if uri.starts_with("data:") => decode_data_uri(uri),
+                Source::Uri(uri) => buffer_resolver(uri),
+            };
+            let data = data.ok_or(GltfError::MissingBuffer {
+                index: buffer.index(),
+            })?;
+            if data.len() < buffer.length() {
+                return Err(GltfError::BufferLength {
+                    index: buffer.index(),
+                    expected: buffer.length(),
+                    actual: data.len(),
+                });
+            }
+            Ok(data)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mesh = document.meshes().next().ok_or(GltfError::NoMesh)?;
+    mesh.primitives()
+        .enumerate()
+        .map(|(index, primitive)| {
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let positions = reader
+                .read_positions()
+                .ok_or(GltfError::MissingPositions { primitive: index })?
+                .map(Position)
+                .collect::<Vec<_>>();
+            let indices = reader
+                .read_indices()
+                .map(|indices| indices.into_u32().collect::<Vec<_>>());
+            let len = positions.len();
+            if let Some(&invalid) = indices
+                .iter()
+                .flatten()
+                .find(|&&vertex| vertex as usize >= len)
+            {
+                return Err(GltfError::IndexOutOfRange {
+                    primitive: index,
+                    index: invalid as usize,
+                    len,
+                });
+            }
+            let (prim, indices) = convert_mode(primitive.mode(), indices, positions.len());
+
+            let mut builder = MeshBuilder::new();
+            builder.set_prim_type(prim);
+            if let Some(indices) = indices {
+                builder.set_indices(indices);
+            }
+            builder.add_vertices(positions);
+            if let Some(normals) = reader.read_normals() {
+                builder.add_vertices(normals.map(Normal).collect::<Vec<_>>());
+            }
+            if let Some(tangents) = reader.read_tangents() {
+                builder.add_vertices(tangents.map(Tangent).collect::<Vec<_>>());
+            }
+            if let Some(tex_coords) = reader.read_tex_coords(0) {
+                builder.add_vertices(tex_coords.into_f32().map(TexCoord).collect::<Vec<_>>());
+            }
+
+            Ok(GltfPrimitive {
+                builder,
+                material: primitive.material().index(),
+            })
+        })
+        .collect()
+}
+
+/// Map glTF primitive mode to the closest supported primitive,
+/// rewriting indices of modes that have no equivalent.
+fn convert_mode(
+    mode: Mode,
+    indices: Option<Vec<u32>>,
+    vertex_count: usize,
+) -> (Primitive, Option<Vec<u32>>) {
+    let all_indices =
+        |indices: Option<Vec<u32>>| indices.unwrap_or_else(|| (0..vertex_count as u32).collect());
+    match mode {
+        Mode::Points => (Primitive::PointList, indices),
+        Mode::Lines => (Primitive::LineList, indices),
+        Mode::LineStrip => (Primitive::LineStrip, indices),
+        Mode::Triangles => (Primitive::TriangleList, indices),
+        Mode::TriangleStrip => (Primitive::TriangleStrip, indices),
+        Mode::LineLoop => {
+            let mut indices = all_indices(indices);
+            if let Some(&first) = indices.first() {
+                indices.push(first);
+            }
+            (Primitive::LineStrip, Some(indices))
+        }
+        Mode::TriangleFan => {
+            let indices = all_indices(indices);
+            let list = (2..indices.len())
+                .flat_map(|i| vec![indices[0], indices[i - 1], indices[i]])
+                .collect();
+            (Primitive::TriangleList, Some(list))
+        }
+    }
+}
+
+/// Decode base64 data URI such as `data:application/octet-stream;base64,AAAA`.
+fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    let data = &uri[uri.find(";base64,")? + ";base64,".len()..];
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for c in data.bytes().take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        accumulator = accumulator << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Single triangle with `u16` indices stored in a data URI.
+    const TRIANGLE: &str = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{
+            "byteLength": 44,
+            "uri": "data:application/octet-stream;base64,AAABAAIAAAAAAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAAAAAACAPwAAAAA="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 6, "target": 34963 },
+            { "buffer": 0, "byteOffset": 8, "byteLength": 36, "target": 34962 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5123, "count": 3, "type": "SCALAR" },
+            {
+                "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]
+            }
+        ],
+        "materials": [{}],
+        "meshes": [{
+            "primitives": [{ "attributes": { "POSITION": 1 }, "indices": 0, "material": 0 }]
+        }]
+    }"#;
+
+    #[test]
+    fn test_load_from_gltf() {
+        let primitives = load_from_gltf(TRIANGLE.as_bytes()).unwrap();
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].material, Some(0));
+        assert_eq!(
+            primitives[0].builder.bounding_box(),
+            Some(([0.0, 0.0, 0.0], [1.0, 1.0, 0.0]))
+        );
+    }
+
+    #[test]
+    fn test_load_from_gltf_truncated_buffer() {
+        let truncated = TRIANGLE.replace(
+            "AAABAAIAAAAAAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAAAAAACAPwAAAAA=",
+            "AAABAAIAAAAAAAAAAAAAAAAA",
+        );
+        match load_from_gltf(truncated.as_bytes()) {
+            Err(GltfError::BufferLength {
+                index,
+                expected,
+                actual,
+            }) => assert_eq!((index, expected, actual), (0, 44, 18)),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_from_gltf_index_out_of_range() {
+        // Indices 0, 1, 3 instead of 0, 1, 2.
+        let invalid = TRIANGLE.replace("base64,AAABAAIA", "base64,AAABAAMA");
+        match load_from_gltf(invalid.as_bytes()) {
+            Err(GltfError::IndexOutOfRange {
+                primitive,
+                index,
+                len,
+            }) => assert_eq!((primitive, index, len), (0, 3, 3)),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_data_uri() {
+        assert_eq!(
+            decode_data_uri("data:application/octet-stream;base64,AAECAwQ="),
+            Some(vec![0, 1, 2, 3, 4])
+        );
+        assert_eq!(
+            decode_data_uri("data:application/gltf-buffer;base64,aGVsbG8gd29ybGQ="),
+            Some(b"hello world".to_vec())
+        );
+        assert_eq!(decode_data_uri("data:text/plain,hello"), None);
+    }
+
+    #[test]
+    fn test_convert_mode() {
+        assert_eq!(
+            convert_mode(Mode::TriangleFan, None, 5),
+            (
+                Primitive::TriangleList,
+                Some(vec![0, 1, 2, 0, 2, 3, 0, 3, 4])
+            )
+        );
+        assert_eq!(
+            convert_mode(Mode::LineLoop, Some(vec![2, 1, 0]), 3),
+            (Primitive::LineStrip, Some(vec![2, 1, 0, 2]))
+        );
+        assert_eq!(
+            convert_mode(Mode::Triangles, None, 3),
+            (Primitive::TriangleList, None)
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! Memory mapping of mesh files.
+
+use std::{fs::File, io, path::Path};
+
+/// Map the file at `path` into memory.
+///
+/// The mapping reflects the file as it is on disk, so it must not be modified or truncated
+/// while mapped, which this crate can't prevent. Truncation typically crashes the process
+/// when the missing pages are accessed, other modifications may produce torn data.
+/// Loaders only read the mapping and copy everything they keep,
+/// so dropping it after loading is always fine.
+pub(super) fn map(path: &Path) -> io::Result<memmap2::Mmap> {
+    let file = File::open(path)?;
+    // Safety: the file is only read, see the caveats above.
+    unsafe { memmap2::Mmap::map(&file) }
+}
@@ -1,147 +1,3890 @@
 //! Loading mesh data from obj format.
 
-use log::trace;
+use log::{trace, warn};
 use {
-    crate::{mesh::MeshBuilder, Normal, Position, TexCoord},
+    super::weld::{weld, weld_by_index},
+    crate::{
+        mesh::{AttributeMask, MeshBuilder},
+        tangent::{add, compute_tangents, corner_angle, cross, normalize, scale, sub},
+        Bitangent, Color, Normal, Position, Tangent, TexCoord, TexCoord3,
+    },
+    rendy_core::hal::pso::Primitive,
+    std::{
+        borrow::Cow,
+        collections::{BTreeSet, HashMap},
+        ops::ControlFlow,
+    },
     wavefront_obj::obj,
 };
 
-/// Object loading error.Option
+mod mtl;
+mod path;
+mod preprocess;
+mod triangulate;
+
+pub use {
+    self::mtl::{Material, MaterialTextures},
+    self::path::PathResolver,
+    crate::tangent::TangentMode,
+    wavefront_obj::obj::VTNIndex,
+};
+
+/// Object loading error.
 #[derive(Debug, PartialEq)]
 pub enum ObjError {
     /// The passed bytes were improper UTF-8 data.
-    Utf8(std::str::Utf8Error),
+    InvalidUtf8 {
+        /// Offset of the first invalid byte.
+        position: usize,
+    },
+    /// Reading the obj data failed.
+    Io(std::io::ErrorKind),
     /// Parsing of the obj failed.
-    Parse(wavefront_obj::ParseError),
+    Parse {
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// Parsing of the material library failed.
+    Mtl {
+        /// Name of the material library.
+        library: String,
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// A face references vertex data that doesn't exist.
+    IndexOutOfRange {
+        /// Name of the object containing the face.
+        object: String,
+        /// Index of the face within its mesh.
+        face: usize,
+        /// Kind of the referenced data: `"vertex"`, `"texture vertex"` or `"normal"`.
+        kind: &'static str,
+        /// The invalid index.
+        index: usize,
+        /// Number of elements of that kind defined in the object.
+        len: usize,
+    },
+    /// Loading was cancelled by the progress callback.
+    Cancelled,
+    /// The obj contains geometry that can't be loaded into a mesh, such as curves or surfaces.
+    /// Only reported if `ObjLoadOptions::strict` is enabled.
+    UnsupportedFeature {
+        /// Keyword of the statement, such as `"curv"` or `"surf"`.
+        feature: String,
+        /// Line of the statement.
+        line: usize,
+    },
+    /// A face, line or point statement has too few vertices to form its primitive.
+    UnsupportedPrimitive {
+        /// Keyword of the statement: `"f"`, `"l"` or `"p"`.
+        keyword: String,
+        /// Number of vertices of the statement.
+        vertices: usize,
+        /// Line of the statement.
+        line: usize,
+    },
+    /// A mesh exceeds `ObjLoadOptions::max_vertices` or `ObjLoadOptions::max_indices`.
+    TooLarge {
+        /// Name of the object containing the mesh.
+        object: String,
+        /// Exceeded limit: `"vertices"` or `"indices"`.
+        kind: &'static str,
+        /// Number of vertices or indices of the mesh.
+        count: usize,
+        /// The limit.
+        limit: usize,
+    },
+    /// A sandboxed `PathResolver` rejected a path outside of its base directory.
+    PathOutsideBase {
+        /// The path as written in the file.
+        path: String,
+    },
+}
+
+impl From<std::str::Utf8Error> for ObjError {
+    fn from(error: std::str::Utf8Error) -> Self {
+        ObjError::InvalidUtf8 {
+            position: error.valid_up_to(),
+        }
+    }
 }
 
 impl std::error::Error for ObjError {}
 impl std::fmt::Display for ObjError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ObjError::Utf8(e) => write!(f, "{}", e),
-            ObjError::Parse(e) => write!(
+            ObjError::InvalidUtf8 { position } => {
+                write!(f, "Invalid UTF-8 at byte {} of object file", position)
+            }
+            ObjError::Io(kind) => write!(f, "Error reading object file: {:?}", kind),
+            ObjError::Parse {
+                line_number,
+                message,
+            } => write!(
                 f,
                 "Error parsing object file at line {}: {}",
-                e.line_number, e.message
+                line_number, message
+            ),
+            ObjError::Mtl {
+                library,
+                line_number,
+                message,
+            } => write!(
+                f,
+                "Error parsing material library '{}' at line {}: {}",
+                library, line_number, message
+            ),
+            ObjError::IndexOutOfRange {
+                object,
+                face,
+                kind,
+                index,
+                len,
+            } => write!(
+                f,
+                "Face {} of object '{}' references {} {} but only {} are defined",
+                face, object, kind, index, len
+            ),
+            ObjError::Cancelled => write!(f, "Loading of object file was cancelled"),
+            ObjError::UnsupportedFeature { feature, line } => write!(
+                f,
+                "Unsupported statement '{}' in object file at line {}",
+                feature, line
+            ),
+            ObjError::UnsupportedPrimitive {
+                keyword,
+                vertices,
+                line,
+            } => write!(
+                f,
+                "Unsupported '{}' statement with {} vertices in object file at line {}",
+                keyword, vertices, line
+            ),
+            ObjError::TooLarge {
+                object,
+                kind,
+                count,
+                limit,
+            } => write!(
+                f,
+                "Mesh of object '{}' has {} {}, more than the limit of {}",
+                object, count, kind, limit
             ),
+            ObjError::PathOutsideBase { path } => {
+                write!(f, "Path '{}' is outside of the base directory", path)
+            }
+        }
+    }
+}
+
+/// Mesh loaded from obj together with its material and origin in the source hierarchy.
+#[derive(Debug)]
+pub struct ObjMesh {
+    /// Builder with the mesh data.
+    pub builder: MeshBuilder<'static>,
+    /// Primitive type of the mesh: `TriangleList`, `LineList` or `PointList`.
+    pub primitive_topology: Primitive,
+    /// Name of the object (`o` statement) the mesh belongs to.
+    pub object_name: Option<String>,
+    /// Name of the group (`g` statement) the mesh belongs to.
+    /// Names of faces assigned to multiple groups are separated by spaces.
+    pub group_name: Option<String>,
+    /// Name of the material used by the mesh.
+    pub material_name: Option<String>,
+    /// Material definition, if it was found in the referenced material library.
+    pub material: Option<Material>,
+    /// Offset subtracted from positions of the mesh.
+    /// It is zero unless `ObjLoadOptions::recenter` is enabled.
+    pub offset: [f64; 3],
+    /// Smoothing group (`s` statement) of each triangle, in the order of the indices.
+    /// Zero for faces without a smoothing group. Empty for line and point meshes.
+    pub smoothing_groups: Vec<u32>,
+    /// Whether the builder lacks `Normal` and `Tangent` attributes because the file
+    /// has no normals for the mesh, see `ObjLoadOptions::omit_missing_normals`.
+    pub normals_missing: bool,
+    /// Whether the builder lacks texture coordinates because the file has none
+    /// for the mesh, see `ObjLoadOptions::omit_missing_tex_coords`.
+    pub tex_coords_missing: bool,
+}
+
+/// Meshes loaded from obj, as returned by `load_from_obj_with_report`.
+#[derive(Debug)]
+pub struct ObjLoadReport {
+    /// Loaded meshes.
+    pub meshes: Vec<ObjMesh>,
+    /// Number of curve and surface statements that were skipped.
+    pub skipped_statements: usize,
+}
+
+/// Object loaded from obj as a single mesh, as returned by `load_from_obj_submeshes`.
+#[derive(Debug)]
+pub struct ObjObject {
+    /// Builder with the triangles of all groups and materials of the object.
+    pub builder: MeshBuilder<'static>,
+    /// Name of the object (`o` statement).
+    pub object_name: Option<String>,
+    /// Offset subtracted from positions of the mesh.
+    /// It is zero unless `ObjLoadOptions::recenter` is enabled.
+    pub offset: [f64; 3],
+    /// Ranges of the indices drawn with each material, in the order of the indices.
+    pub submeshes: Vec<ObjSubmesh>,
+    /// Whether the builder lacks `Normal` and `Tangent` attributes because the file
+    /// has no normals for the object, see `ObjLoadOptions::omit_missing_normals`.
+    pub normals_missing: bool,
+    /// Whether the builder lacks texture coordinates because the file has none
+    /// for the object, see `ObjLoadOptions::omit_missing_tex_coords`.
+    pub tex_coords_missing: bool,
+}
+
+/// Range of indices of an `ObjObject` sharing a group and material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjSubmesh {
+    /// Name of the group (`g` statement) of the submesh.
+    /// Names of faces assigned to multiple groups are separated by spaces.
+    pub name: Option<String>,
+    /// Name of the material used by the submesh.
+    pub material_name: Option<String>,
+    /// Material definition, if it was found in the referenced material library.
+    pub material: Option<Material>,
+    /// First index of the submesh.
+    pub index_start: u32,
+    /// Number of indices of the submesh.
+    pub index_count: u32,
+}
+
+/// Object of an obj file with its data as listed in the file,
+/// as returned by `load_from_obj_raw`.
+#[derive(Clone, Debug)]
+pub struct RawObjObject {
+    /// Name of the object (`o` statement).
+    pub name: Option<String>,
+    /// Positions of the `v` statements of the object, in order.
+    pub positions: Vec<Position>,
+    /// Texture coordinates of the `vt` statements of the object, in order.
+    pub tex_coords: Vec<TexCoord3>,
+    /// Normals of the `vn` statements of the object, in order.
+    pub normals: Vec<Normal>,
+    /// Corners of each triangle, as zero-based indices into the lists of the object.
+    /// Polygonal faces are split into triangles by the parser.
+    pub triangles: Vec<[VTNIndex; 3]>,
+    /// Corners of each line segment.
+    pub lines: Vec<[VTNIndex; 2]>,
+    /// Corner of each point.
+    pub points: Vec<VTNIndex>,
+}
+
+/// Materials referenced by an obj file, as reported by `obj_material_names`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObjMaterialNames {
+    /// File names of the material libraries (`mtllib` statements).
+    pub libraries: Vec<String>,
+    /// Names of the materials used by faces (`usemtl` statements).
+    pub materials: Vec<String>,
+}
+
+/// Size of a mesh in an obj file, as reported by `obj_stats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjGeometryStats {
+    /// Name of the object (`o` statement) the mesh belongs to.
+    pub object_name: Option<String>,
+    /// Name of the group (`g` statement) the mesh belongs to.
+    pub group_name: Option<String>,
+    /// Name of the material used by the mesh.
+    pub material_name: Option<String>,
+    /// Primitive type of the mesh: `TriangleList`, `LineList` or `PointList`.
+    pub primitive_topology: Primitive,
+    /// Number of primitive corners, which is the number of indices of the loaded mesh.
+    pub vertices: usize,
+    /// Number of unique vertices, which is the number of vertices of the loaded mesh.
+    pub unique_vertices: usize,
+    /// Number of triangles. Zero for line and point meshes.
+    pub triangles: usize,
+}
+
+/// Mesh about to be streamed by `load_from_obj_streaming`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjMeshInfo<'a> {
+    /// Name of the object (`o` statement) the mesh belongs to.
+    pub object_name: Option<&'a str>,
+    /// Name of the group (`g` statement) the mesh belongs to.
+    pub group_name: Option<&'a str>,
+    /// Name of the material used by the mesh.
+    pub material_name: Option<&'a str>,
+    /// Primitive type of the mesh: `TriangleList`, `LineList` or `PointList`.
+    pub primitive_topology: Primitive,
+    /// Total number of vertices of the mesh.
+    pub vertex_count: usize,
+    /// Total number of indices of the mesh.
+    pub index_count: usize,
+}
+
+/// Consecutive vertices of a mesh streamed by `load_from_obj_streaming`.
+///
+/// All slices have the same length. Attributes that `load_from_obj` leaves out
+/// of a mesh, such as tangents of lines and points, are `None`.
+#[derive(Clone, Copy, Debug)]
+pub struct ObjVertices<'a> {
+    /// Positions of the vertices.
+    pub positions: &'a [Position],
+    /// Normals of the vertices.
+    pub normals: Option<&'a [Normal]>,
+    /// Texture coordinates of the vertices.
+    pub tex_coords: Option<&'a [TexCoord]>,
+    /// Tangents of the vertices.
+    pub tangents: Option<&'a [Tangent]>,
+    /// Colors of the vertices, see `ObjLoadOptions::attributes`.
+    pub colors: Option<&'a [Color]>,
+}
+
+/// Receiver of the meshes streamed by `load_from_obj_streaming`.
+pub trait ObjVisitor {
+    /// Called before the vertices and indices of each mesh.
+    fn begin_mesh(&mut self, mesh: &ObjMeshInfo<'_>);
+
+    /// Called with the next chunk of vertices of the current mesh.
+    fn vertices(&mut self, vertices: ObjVertices<'_>);
+
+    /// Called with the next chunk of indices of the current mesh,
+    /// after all of its vertices.
+    fn indices(&mut self, indices: &[u32]);
+}
+
+/// Maximum number of vertices or indices streamed in one chunk.
+const STREAM_CHUNK: usize = 4096;
+
+/// Options for loading obj files.
+#[derive(Clone, Copy, Debug)]
+pub struct ObjLoadOptions {
+    /// Generate normals for vertices that don't have one.
+    ///
+    /// Generated normals are smooth: the normals of all faces sharing a vertex position
+    /// and smoothing group are averaged, weighted as chosen by `normal_weighting`.
+    /// Vertices used by faces of different smoothing groups are split, so edges between
+    /// the groups stay sharp. Faces without a smoothing group are smoothed together.
+    pub generate_normals: bool,
+    /// How face normals are weighted when generating normals.
+    pub normal_weighting: NormalWeighting,
+    /// Generate normals as if all faces belonged to this smoothing group,
+    /// ignoring `s` statements, for files meant to be smooth that don't have them.
+    ///
+    /// `ObjMesh::smoothing_groups` still reports the groups of the file.
+    pub force_smoothing_group: Option<u32>,
+    /// Normal of vertices that don't have one, unless normals are generated.
+    /// Zero by default.
+    pub default_normal: [f32; 3],
+    /// Leave out the `Normal` attribute of meshes without any normals in the file,
+    /// instead of filling it with `default_normal`.
+    ///
+    /// Applies only when normals are neither generated nor computed for unwelded meshes.
+    /// Tangents and bitangents depend on normals and are left out as well.
+    /// Such meshes are marked by `ObjMesh::normals_missing`, so that normals
+    /// can be computed later, for example on the GPU.
+    pub omit_missing_normals: bool,
+    /// Texture coordinate of vertices that don't have one. Zero by default.
+    ///
+    /// Stored as is, `flip_u`, `flip_v` and `tex_coord_map` don't apply to it.
+    /// The `w` component of `TexCoord3` is zero.
+    pub default_tex_coord: [f32; 2],
+    /// Leave out `TexCoord` and `TexCoord3` attributes of meshes without any
+    /// texture coordinates in the file, instead of filling them with `default_tex_coord`.
+    ///
+    /// Tangents are still computed if requested, as for vertices without texture coordinates.
+    /// Such meshes are marked by `ObjMesh::tex_coords_missing`.
+    pub omit_missing_tex_coords: bool,
+    /// Merge all geometries of an object into a single mesh.
+    ///
+    /// Vertices are deduplicated across groups and materials, producing one mesh per object.
+    /// Material and group names are kept only if they are the same for the whole object.
+    pub merge_geometries: bool,
+    /// Move each mesh so that the center of its bounding box is at the origin.
+    ///
+    /// Keeps `f32` positions precise for models far from the origin.
+    /// The subtracted offset is reported in `ObjMesh::offset`.
+    pub recenter: bool,
+    /// How polygonal faces are split into triangles.
+    pub polygon_mode: PolygonMode,
+    /// Drop triangles that reference the same position twice or have zero area.
+    ///
+    /// Vertices used only by dropped triangles are not loaded.
+    pub drop_degenerate: bool,
+    /// Share vertices between triangles using the same position, texture coordinate and normal.
+    ///
+    /// When disabled, every triangle gets three vertices of its own
+    /// with the face normal replacing normals from the file, for flat shading.
+    /// Indices are then sequential. Enabled by default.
+    pub weld: bool,
+    /// Weld in linear time when every position index is used with a single texture
+    /// coordinate and normal, with the same result as the general path.
+    ///
+    /// Only disabled by tests and benchmarks comparing the two paths on the same file,
+    /// available with the internal `bench` feature.
+    #[cfg(any(test, feature = "bench"))]
+    #[doc(hidden)]
+    pub weld_by_index: bool,
+    /// Store `1.0 - v` as the second texture coordinate.
+    ///
+    /// Obj texture coordinates have the origin at the bottom left of the image,
+    /// flipping them moves it to the top left as most graphics APIs expect.
+    pub flip_v: bool,
+    /// Store `1.0 - u` as the first texture coordinate.
+    pub flip_u: bool,
+    /// Also load three-dimensional texture coordinates as `TexCoord3`, in addition to `TexCoord`.
+    ///
+    /// The `w` component defaults to `0.0` when a texture vertex doesn't specify it.
+    pub tex_coords_3d: bool,
+    /// How tangents are computed.
+    pub tangent_mode: TangentMode,
+    /// Also store `Bitangent` computed as `cross(normal, tangent.xyz) * tangent.w`.
+    pub emit_bitangent: bool,
+    /// Matrix applied to positions, in column-major order with translation in the last column.
+    ///
+    /// Normals from the file are transformed by the inverse transpose and stay unit length,
+    /// generated normals and tangents are computed from transformed positions.
+    /// Faces of mirroring transforms are reversed to keep their winding.
+    /// Recentering applies to transformed positions. See `z_up_to_y_up`.
+    pub transform: Option<[[f32; 4]; 4]>,
+    /// Coordinate system the mesh is loaded into. Obj files are right-handed.
+    ///
+    /// `LeftHanded` negates the Z coordinate of positions and normals after `transform`
+    /// is applied, and reverses faces so that they keep facing out.
+    pub handedness: Handedness,
+    /// Reverse the winding order of triangles, for files with clockwise front faces.
+    ///
+    /// Normals from the file are kept, generated normals and tangents follow the new winding.
+    pub flip_winding: bool,
+    /// Conversion of positions to `f32`, for swizzling axes or converting units.
+    ///
+    /// Called with each position after `transform` and recentering are applied.
+    /// Generated normals and tangents are computed from the converted positions.
+    /// Winding is left as is, so maps mirroring the mesh, such as swapping two axes,
+    /// should be combined with `flip_winding`.
+    pub position_map: Option<CoordinateMap>,
+    /// Conversion of normals from the file to `f32`, called after `transform` is applied.
+    ///
+    /// Should match `position_map`. Generated normals are not converted.
+    pub normal_map: Option<CoordinateMap>,
+    /// Conversion of texture coordinates `(u, v, w)` to `f32`,
+    /// called after `flip_u` and `flip_v` are applied.
+    ///
+    /// `TexCoord` is made of the first two components of the result.
+    pub tex_coord_map: Option<CoordinateMap>,
+    /// Fail with `ObjError::UnsupportedFeature` on curves and surfaces.
+    ///
+    /// Otherwise free-form geometry is skipped, and the number of skipped statements
+    /// is reported by `load_from_obj_with_report`.
+    pub strict: bool,
+    /// Maximum number of vertices of each mesh.
+    ///
+    /// Loading fails with `ObjError::TooLarge` before the vertex data of a larger mesh
+    /// is created, which guards against untrusted files exhausting memory.
+    pub max_vertices: Option<usize>,
+    /// Maximum number of indices of each mesh, checked before any of them are collected.
+    pub max_indices: Option<usize>,
+    /// Attributes stored in the loaded meshes. All of them by default.
+    ///
+    /// Attributes that are not requested aren't computed,
+    /// unless they are needed for other ones, such as normals for tangents.
+    /// `Color` is only stored for meshes using vertices with colors appended
+    /// to their `v` statements as `v x y z r g b`, an extension written by some tools.
+    /// Alpha is one, and vertices without a color in such meshes are white.
+    pub attributes: AttributeMask,
+}
+
+impl Default for ObjLoadOptions {
+    fn default() -> Self {
+        ObjLoadOptions {
+            generate_normals: false,
+            normal_weighting: NormalWeighting::default(),
+            force_smoothing_group: None,
+            default_normal: [0.0; 3],
+            omit_missing_normals: false,
+            default_tex_coord: [0.0; 2],
+            omit_missing_tex_coords: false,
+            merge_geometries: false,
+            recenter: false,
+            polygon_mode: PolygonMode::default(),
+            drop_degenerate: false,
+            weld: true,
+            #[cfg(any(test, feature = "bench"))]
+            weld_by_index: true,
+            flip_v: false,
+            flip_u: false,
+            tex_coords_3d: false,
+            tangent_mode: TangentMode::default(),
+            emit_bitangent: false,
+            transform: None,
+            handedness: Handedness::default(),
+            flip_winding: false,
+            position_map: None,
+            normal_map: None,
+            tex_coord_map: None,
+            strict: false,
+            max_vertices: None,
+            max_indices: None,
+            attributes: AttributeMask::default(),
         }
     }
 }
 
+/// Conversion of three coordinates read from obj to `f32`,
+/// see `ObjLoadOptions::position_map`.
+pub type CoordinateMap = fn(f64, f64, f64) -> [f32; 3];
+
+/// Transform for `ObjLoadOptions::transform` converting Z-up models to Y-up.
+///
+/// Rotates by -90 degrees around the X axis, so `(x, y, z)` becomes `(x, z, -y)`.
+pub fn z_up_to_y_up() -> [[f32; 4]; 4] {
+    [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, -1.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+/// Triangulation method for faces with more than three vertices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PolygonMode {
+    /// Split faces into a fan of triangles sharing one vertex.
+    /// Fast, but only correct for convex faces.
+    #[default]
+    Fan,
+    /// Clip ears of faces projected along their normal.
+    /// Handles concave faces, skipping zero-area triangles.
+    EarClip,
+}
+
+/// Weight of each face normal in the average forming a generated vertex normal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NormalWeighting {
+    /// Weight by face area, so large faces dominate.
+    #[default]
+    Area,
+    /// Weight by the interior angle of the face at the vertex.
+    /// Gives the same normal regardless of how the surface around the vertex is tessellated.
+    Angle,
+    /// Weight every face the same.
+    Equal,
+}
+
+/// Handedness of the coordinate system of loaded meshes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Handedness {
+    /// Keep the right-handed coordinates of obj files.
+    #[default]
+    RightHanded,
+    /// Convert to left-handed coordinates by negating Z.
+    LeftHanded,
+}
+
 /// Load mesh data from obj.
+///
+/// A separate mesh is created for each material and group within each object.
+/// Every `usemtl` statement starts a new mesh, even within a single group.
+/// Polygonal faces are split into triangles.
+/// Line and point primitives are loaded into separate meshes
+/// with `LineList` and `PointList` primitive types.
 pub fn load_from_obj(
     bytes: &[u8],
 ) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
-    let string = std::str::from_utf8(bytes).map_err(ObjError::Utf8)?;
-    obj::parse(string)
-        .and_then(load_from_data)
-        .map_err(ObjError::Parse)
+    load_from_obj_with_options(bytes, ObjLoadOptions::default())
 }
 
-fn load_from_data(
+/// Load mesh data from obj using specified options.
+///
+/// Output is deterministic: loading the same bytes with the same options
+/// produces byte-identical vertex and index buffers.
+pub fn load_from_obj_with_options(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
+    let (meshes, _) = load_obj(bytes, &options, |_| None, None)?;
+    Ok(meshes
+        .into_iter()
+        .map(|(mesh, _)| (mesh.builder, mesh.material_name))
+        .collect())
+}
+
+/// Load mesh data of the object named `name` (`o` statement) from obj,
+/// as `load_from_obj` would load it.
+///
+/// The whole file is parsed, but geometries of other objects are not converted.
+/// Returns meshes of all objects with that name, or none if there is no such object.
+pub fn load_named_object_from_obj(
+    bytes: &[u8],
+    name: &str,
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
+    let parsed = parse_obj(bytes, false)?;
+    let options = ObjLoadOptions::default();
+    let jobs = mesh_jobs(&parsed, &options, &[]);
+    let mut meshes = Vec::new();
+    for job in jobs.into_iter().filter(|job| job.object.name == name) {
+        let loaded = job.load(&options)?;
+        meshes.extend(
+            loaded
+                .into_iter()
+                .map(|(mesh, _)| (mesh.builder, mesh.material_name)),
+        );
+    }
+    Ok(meshes)
+}
+
+/// Load mesh data from several obj files into a single list, for batching static scenes.
+///
+/// Meshes of all files using the same material are merged into one, see `MeshBuilder::merge`,
+/// so each material name appears once unless its meshes have different primitive types
+/// or attributes. Meshes without a material are kept as they are.
+/// Meshes are listed in the order their first part appears in `inputs`.
+pub fn load_scene_from_objs(
+    inputs: &[&[u8]],
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
+    let mut scene: Vec<(MeshBuilder<'static>, Option<String>)> = Vec::new();
+    for bytes in inputs {
+        for (builder, material_name) in load_from_obj(bytes)? {
+            let merged = material_name.is_some()
+                && scene.iter_mut().any(|(merged, merged_material)| {
+                    *merged_material == material_name && merged.merge(&builder).is_ok()
+                });
+            if !merged {
+                scene.push((builder, material_name));
+            }
+        }
+    }
+    Ok(scene)
+}
+
+/// Load mesh data from obj, keeping the object and group names of each mesh.
+pub fn load_from_obj_grouped(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+) -> Result<Vec<ObjMesh>, ObjError> {
+    let (meshes, _) = load_obj(bytes, &options, |_| None, None)?;
+    Ok(meshes.into_iter().map(|(mesh, _)| mesh).collect())
+}
+
+/// Load mesh data from obj, also reporting what was skipped.
+///
+/// Curves and surfaces are counted in `ObjLoadReport::skipped_statements`,
+/// unless `ObjLoadOptions::strict` turns them into errors.
+pub fn load_from_obj_with_report(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+) -> Result<ObjLoadReport, ObjError> {
+    let (meshes, skipped_statements) = load_obj(bytes, &options, |_| None, None)?;
+    Ok(ObjLoadReport {
+        meshes: meshes.into_iter().map(|(mesh, _)| mesh).collect(),
+        skipped_statements,
+    })
+}
+
+/// Load mesh data from obj, together with the source of each vertex.
+///
+/// For each mesh returns the position, texture coordinate and normal indices
+/// each of its vertices was created from, in the order of the vertex buffers.
+/// Indices are zero-based and point into the lists of the object containing the mesh.
+/// Without `ObjLoadOptions::weld` each corner of every face has an entry of its own.
+pub fn load_from_obj_with_remap(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+) -> Result<Vec<(ObjMesh, Vec<VTNIndex>)>, ObjError> {
+    let (meshes, _) = load_obj(bytes, &options, |_| None, None)?;
+    Ok(meshes)
+}
+
+/// Load mesh data from obj read from `reader`.
+///
+/// Same as `load_from_obj` but accepts any `Read` implementation,
+/// such as a `BufReader<File>` or a decompressing stream.
+pub fn load_from_obj_reader<R>(
+    mut reader: R,
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError>
+where
+    R: std::io::Read,
+{
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| ObjError::Io(e.kind()))?;
+    load_from_obj(&bytes)
+}
+
+/// Load mesh data from the obj file at `path`.
+///
+/// Same as `load_from_obj`, but parses the memory-mapped file instead of reading it first.
+/// The file must not be modified or truncated by other processes while it is loaded.
+/// This can't be enforced, and truncation may crash the process.
+/// Loaded meshes own their data and don't borrow the mapping.
+#[cfg(feature = "mmap")]
+pub fn load_from_obj_path(
+    path: &std::path::Path,
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
+    let map = super::mmap::map(path).map_err(|e| ObjError::Io(e.kind()))?;
+    load_from_obj(&map)
+}
+
+/// Load mesh data from gzip compressed obj, such as the contents of an `.obj.gz` file.
+///
+/// Same as `load_from_obj_reader` with a `flate2::read::GzDecoder`.
+/// Corrupted compressed data is reported as `ObjError::Io`.
+#[cfg(feature = "gzip")]
+pub fn load_from_obj_gz(
+    bytes: &[u8],
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError> {
+    load_from_obj_reader(flate2::read::GzDecoder::new(bytes))
+}
+
+/// Load mesh data from obj together with materials.
+///
+/// `mtl_resolver` is called with the name of each material library referenced by the obj
+/// and should return the contents of that file, or `None` if it is not available.
+/// Libraries may be listed in one or several `mtllib` statements. If multiple libraries
+/// define a material with the same name, the one referenced last is used.
+pub fn load_from_obj_with_materials<F>(
+    bytes: &[u8],
+    mtl_resolver: F,
+) -> Result<Vec<ObjMesh>, ObjError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let (meshes, _) = load_obj(bytes, &ObjLoadOptions::default(), mtl_resolver, None)?;
+    Ok(meshes.into_iter().map(|(mesh, _)| mesh).collect())
+}
+
+/// Load mesh data from obj together with materials read from files.
+///
+/// Material libraries are read from the paths `paths` resolves them to,
+/// libraries that don't exist are skipped. Texture paths of the materials are
+/// resolved relative to their library and replaced with the resolved paths.
+/// Fails with `ObjError::PathOutsideBase` if a sandboxed `paths` rejects a library
+/// or texture path.
+pub fn load_from_obj_with_base_dir(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+    paths: &PathResolver,
+) -> Result<Vec<ObjMesh>, ObjError> {
+    let parsed = parse_obj(bytes, options.strict)?;
+    let mut materials = Vec::new();
+    for library in &parsed.material_libraries {
+        let bytes = match std::fs::read(paths.resolve(library)?) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                trace!("Material library '{}' is not found", library);
+                continue;
+            }
+            Err(error) => return Err(ObjError::Io(error.kind())),
+        };
+        let mut library_materials = mtl::parse(library, &bytes)?;
+        for material in &mut library_materials {
+            paths.resolve_textures(library, &mut material.textures)?;
+        }
+        add_materials(&mut materials, library, library_materials);
+    }
+    let meshes = load_from_data(&parsed, &options, &materials, None)?;
+    Ok(meshes.into_iter().map(|(mesh, _)| mesh).collect())
+}
+
+/// Load each object of obj into a single mesh split into submeshes.
+///
+/// Submeshes are the meshes `load_from_obj_with_materials` would produce for the object,
+/// stored as consecutive index ranges so they share one vertex buffer.
+/// Vertices are welded across submeshes, so `ObjLoadOptions::merge_geometries` has no effect.
+/// Only triangles are loaded. Objects without triangles are skipped.
+pub fn load_from_obj_submeshes<F>(
+    bytes: &[u8],
+    options: ObjLoadOptions,
+    mtl_resolver: F,
+) -> Result<Vec<ObjObject>, ObjError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let parsed = parse_obj(bytes, options.strict)?;
+    let materials = load_materials(&parsed.material_libraries, mtl_resolver)?;
+    let options = ObjLoadOptions {
+        merge_geometries: false,
+        ..options
+    };
+    let jobs = mesh_jobs(&parsed, &options, &materials);
+
+    let mut objects = Vec::new();
+    let mut rest = &jobs[..];
+    while let Some(first) = rest.first() {
+        let count = rest
+            .iter()
+            .take_while(|job| std::ptr::eq(job.object, first.object))
+            .count();
+        let (object_jobs, tail) = rest.split_at(count);
+        rest = tail;
+        objects.extend(load_submeshes(object_jobs, &options)?);
+    }
+    Ok(objects)
+}
+
+/// Load mesh data from obj, reporting progress to `callback`.
+///
+/// `callback` is called with the number of geometries converted so far and the total number
+/// of geometries after each one is converted. A geometry is converted into one mesh
+/// for each of its primitive types. Returning `ControlFlow::Break` stops loading
+/// and fails with `ObjError::Cancelled`, dropping the meshes converted so far.
+/// Meshes are converted one at a time even if the `parallel` feature is enabled.
+pub fn load_from_obj_with_progress<F>(
+    bytes: &[u8],
+    mut callback: F,
+) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, ObjError>
+where
+    F: FnMut(usize, usize) -> ControlFlow<()>,
+{
+    let (meshes, _) = load_obj(
+        bytes,
+        &ObjLoadOptions::default(),
+        |_| None,
+        Some(&mut callback),
+    )?;
+    Ok(meshes
+        .into_iter()
+        .map(|(mesh, _)| (mesh.builder, mesh.material_name))
+        .collect())
+}
+
+/// Load mesh data from obj using specified options, passing it to `visitor` in chunks
+/// instead of building meshes.
+///
+/// Produces the same meshes in the same order as `load_from_obj_with_options`,
+/// so that they can be written directly into mapped buffers without `MeshBuilder`.
+/// Each mesh is still loaded completely before it is passed on, and only one mesh
+/// is held at a time, so memory use is that of the largest mesh rather than of all of them.
+/// Meshes are converted one at a time even if the `parallel` feature is enabled.
+pub fn load_from_obj_streaming<V>(
+    bytes: &[u8],
+    options: &ObjLoadOptions,
+    visitor: &mut V,
+) -> Result<(), ObjError>
+where
+    V: ObjVisitor + ?Sized,
+{
+    let parsed = parse_obj(bytes, options.strict)?;
+    for job in mesh_jobs(&parsed, options, &[]) {
+        for &topology in &TOPOLOGIES {
+            let data = load_geometry(job.object, &job.shapes, topology, job.face_sizes, options)?;
+            if data.indices.is_empty() {
+                continue;
+            }
+            let colors = job.colors(&data.remap, options);
+            visitor.begin_mesh(&ObjMeshInfo {
+                object_name: job.object_name.as_deref(),
+                group_name: job.group_name.as_deref(),
+                material_name: job.material_name.as_deref(),
+                primitive_topology: topology,
+                vertex_count: data.remap.len(),
+                index_count: data.indices.len(),
+            });
+            let has = |attribute| data.attributes.contains(attribute);
+            for start in (0..data.remap.len()).step_by(STREAM_CHUNK) {
+                let chunk = start..data.remap.len().min(start + STREAM_CHUNK);
+                visitor.vertices(ObjVertices {
+                    positions: &data.positions[chunk.clone()],
+                    normals: Some(&data.normals[..])
+                        .filter(|_| has(AttributeMask::NORMAL))
+                        .map(|normals| &normals[chunk.clone()]),
+                    tex_coords: Some(&data.tex_coords[..])
+                        .filter(|_| has(AttributeMask::TEX_COORD))
+                        .map(|tex_coords| &tex_coords[chunk.clone()]),
+                    tangents: Some(&data.tangents[..])
+                        .filter(|_| has(AttributeMask::TANGENT))
+                        .map(|tangents| &tangents[chunk.clone()]),
+                    colors: colors.as_ref().map(|colors| &colors[chunk]),
+                });
+            }
+            for indices in data.indices.chunks(STREAM_CHUNK) {
+                visitor.indices(indices);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Load obj data without welding vertices, keeping the indices of the file.
+///
+/// For editors that need to map loaded data back to the statements of the file.
+/// Positions, texture coordinates and normals are converted to attribute types
+/// but otherwise kept as listed, except that homogeneous positions are divided by `w`.
+/// Relative indices are resolved. Groups, materials and smoothing groups are not loaded.
+pub fn load_from_obj_raw(bytes: &[u8]) -> Result<Vec<RawObjObject>, ObjError> {
+    let ParsedObj { obj_set, .. } = parse_obj(bytes, false)?;
+    obj_set
+        .objects
+        .iter()
+        .map(|object| {
+            let mut raw = RawObjObject {
+                name: Some(object.name.clone()).filter(|name| !name.is_empty()),
+                positions: object
+                    .vertices
+                    .iter()
+                    .map(|v| Position([v.x as f32, v.y as f32, v.z as f32]))
+                    .collect(),
+                tex_coords: object
+                    .tex_vertices
+                    .iter()
+                    .map(|t| TexCoord3([t.u as f32, t.v as f32, t.w as f32]))
+                    .collect(),
+                normals: object
+                    .normals
+                    .iter()
+                    .map(|n| Normal([n.x as f32, n.y as f32, n.z as f32]))
+                    .collect(),
+                triangles: Vec::new(),
+                lines: Vec::new(),
+                points: Vec::new(),
+            };
+            let shapes = object.geometry.iter().flat_map(|geometry| &geometry.shapes);
+            for (face, shape) in shapes.enumerate() {
+                let corners: &[VTNIndex] = match shape.primitive {
+                    obj::Primitive::Triangle(v1, v2, v3) => {
+                        raw.triangles.push([v1, v2, v3]);
+                        raw.triangles.last().unwrap()
+                    }
+                    obj::Primitive::Line(v1, v2) => {
+                        raw.lines.push([v1, v2]);
+                        raw.lines.last().unwrap()
+                    }
+                    obj::Primitive::Point(v1) => {
+                        raw.points.push(v1);
+                        std::slice::from_ref(raw.points.last().unwrap())
+                    }
+                };
+                for index in corners {
+                    check_index(object, face, index)?;
+                }
+            }
+            Ok(raw)
+        })
+        .collect()
+}
+
+/// Count vertices and triangles of obj meshes without loading their data.
+///
+/// Reports one entry for each mesh `load_from_obj` would produce, in the same order.
+pub fn obj_stats(bytes: &[u8]) -> Result<Vec<ObjGeometryStats>, ObjError> {
+    let parsed = parse_obj(bytes, false)?;
+    let mut stats = Vec::new();
+    for job in mesh_jobs(&parsed, &ObjLoadOptions::default(), &[]) {
+        for &topology in &TOPOLOGIES {
+            let corners = primitive_corners(job.object, &job.shapes, topology)?;
+            if corners.is_empty() {
+                continue;
+            }
+            stats.push(ObjGeometryStats {
+                object_name: job.object_name.clone(),
+                group_name: job.group_name.clone(),
+                material_name: job.material_name.clone(),
+                primitive_topology: topology,
+                vertices: corners.len(),
+                unique_vertices: corners.iter().collect::<BTreeSet<_>>().len(),
+                triangles: if topology == Primitive::TriangleList {
+                    corners.len() / 3
+                } else {
+                    0
+                },
+            });
+        }
+    }
+    Ok(stats)
+}
+
+/// Find the material libraries and materials an obj references, without parsing its geometry.
+///
+/// Only `mtllib` and `usemtl` statements are read, so errors in other statements
+/// are not reported. Names are listed once each, in the order of their first reference.
+pub fn obj_material_names(bytes: &[u8]) -> Result<ObjMaterialNames, ObjError> {
+    let string = std::str::from_utf8(bytes)?;
+    let string = preprocess::normalize_line_endings(string);
+    let string = preprocess::join_continued_lines(&string);
+    let (_, libraries) = preprocess::strip_material_libraries(&string);
+    let materials = string.lines().filter_map(|line| {
+        let mut words = line.split('#').next().unwrap_or("").split_whitespace();
+        match words.next() {
+            Some("usemtl") => words.next(),
+            _ => None,
+        }
+    });
+
+    Ok(ObjMaterialNames {
+        libraries: distinct(libraries),
+        materials: distinct(materials),
+    })
+}
+
+/// Write mesh data as obj.
+///
+/// Emits `v`, `vt` and `vn` statements for `Position`, `TexCoord` and `Normal` attributes,
+/// followed by `f`, `l` or `p` statements for triangle, line or point lists.
+/// Faces reference all attributes present in the mesh.
+/// Fails with `InvalidInput` if the mesh has no positions or other primitive type,
+/// or if it doesn't pass `MeshBuilder::validate`.
+pub fn write_to_obj<W>(builder: &MeshBuilder<'_>, out: &mut W) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+    let (statement, corners) = match builder.prim_type() {
+        Primitive::TriangleList => ("f", 3),
+        Primitive::LineList => ("l", 2),
+        Primitive::PointList => ("p", 1),
+        _ => {
+            return Err(invalid(
+                "Only triangle, line and point lists can be written",
+            ))
+        }
+    };
+    builder
+        .validate()
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+    let positions = builder
+        .read_attribute::<Position>()
+        .ok_or_else(|| invalid("Mesh has no positions"))?;
+    let tex_coords = builder.read_attribute::<TexCoord>();
+    let normals = builder.read_attribute::<Normal>();
+
+    for Position([x, y, z]) in &positions {
+        writeln!(out, "v {} {} {}", x, y, z)?;
+    }
+    for TexCoord([u, v]) in tex_coords.iter().flatten() {
+        writeln!(out, "vt {} {}", u, v)?;
+    }
+    for Normal([x, y, z]) in normals.iter().flatten() {
+        writeln!(out, "vn {} {} {}", x, y, z)?;
+    }
+
+    let indices = builder
+        .read_indices()
+        .unwrap_or_else(|| (0..positions.len() as u32).collect());
+    for primitive in indices.chunks_exact(corners) {
+        write!(out, "{}", statement)?;
+        for &index in primitive {
+            // Obj indices start at 1, and all attributes share the index of the vertex.
+            let index = index + 1;
+            match (&tex_coords, &normals) {
+                (None, None) => write!(out, " {}", index)?,
+                (Some(_), None) => write!(out, " {}/{}", index, index)?,
+                (None, Some(_)) => write!(out, " {}//{}", index, index)?,
+                (Some(_), Some(_)) => write!(out, " {}/{}/{}", index, index, index)?,
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Callback receiving the number of converted meshes and the total number of meshes.
+type Progress<'a> = &'a mut dyn FnMut(usize, usize) -> ControlFlow<()>;
+
+/// Meshes together with the source of each vertex, as returned by `load_from_obj_with_remap`.
+type RemappedMeshes = Vec<(ObjMesh, Vec<VTNIndex>)>;
+
+fn load_obj<F>(
+    bytes: &[u8],
+    options: &ObjLoadOptions,
+    mtl_resolver: F,
+    progress: Option<Progress<'_>>,
+) -> Result<(RemappedMeshes, usize), ObjError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let parsed = parse_obj(bytes, options.strict)?;
+    let materials = load_materials(&parsed.material_libraries, mtl_resolver)?;
+    let meshes = load_from_data(&parsed, options, &materials, progress)?;
+    Ok((meshes, parsed.skipped))
+}
+
+/// Parse the material libraries provided by `mtl_resolver` into a single table.
+///
+/// Materials of later libraries replace earlier materials with the same name.
+fn load_materials<F>(libraries: &[String], mut mtl_resolver: F) -> Result<Vec<Material>, ObjError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let mut materials: Vec<Material> = Vec::new();
+    for library in libraries {
+        let bytes = match mtl_resolver(library) {
+            Some(bytes) => bytes,
+            None => {
+                trace!("Material library '{}' is not resolved", library);
+                continue;
+            }
+        };
+        add_materials(&mut materials, library, mtl::parse(library, &bytes)?);
+    }
+    Ok(materials)
+}
+
+/// Add materials of `library` to `materials`, replacing ones with the same name.
+fn add_materials(materials: &mut Vec<Material>, library: &str, library_materials: Vec<Material>) {
+    for material in library_materials {
+        match materials
+            .iter_mut()
+            .find(|known| known.name == material.name)
+        {
+            Some(known) => {
+                trace!("Material '{}' is redefined in '{}'", material.name, library);
+                *known = material;
+            }
+            None => materials.push(material),
+        }
+    }
+}
+
+/// Obj parsed by `parse_obj`.
+struct ParsedObj {
     obj_set: obj::ObjSet,
-) -> Result<Vec<(MeshBuilder<'static>, Option<String>)>, wavefront_obj::ParseError> {
+    /// Number of corners of each face.
+    face_sizes: Vec<usize>,
+    /// Number of skipped curves and surfaces.
+    skipped: usize,
+    /// Names of the referenced material libraries, in order.
+    material_libraries: Vec<String>,
+    /// Color of each vertex of all objects, in order. Empty if no vertex has a color.
+    vertex_colors: Vec<Option<[f32; 4]>>,
+}
+
+/// Parse obj together with the information the parser doesn't keep.
+///
+/// Fails on the first curve or surface if `strict` is set.
+fn parse_obj(bytes: &[u8], strict: bool) -> Result<ParsedObj, ObjError> {
+    let string = std::str::from_utf8(bytes)?;
+    let string = preprocess::normalize_line_endings(string);
+    let string = preprocess::join_continued_lines(&string);
+    let (string, libraries) = preprocess::strip_material_libraries(&string);
+    let material_libraries = libraries.into_iter().map(str::to_owned).collect();
+    let (string, skipped) = preprocess::strip_free_form(&string);
+    if let Some(&(line, feature)) = skipped.first() {
+        if strict {
+            return Err(ObjError::UnsupportedFeature {
+                feature: feature.to_owned(),
+                line,
+            });
+        }
+        warn!(
+            "Skipping {} curve and surface statements, first at line {}",
+            skipped.len(),
+            line
+        );
+    }
+    let string = preprocess::resolve_relative_indices(&string);
+    let (string, vertex_colors) = preprocess::strip_vertex_colors(&string);
+    let string = preprocess::divide_homogeneous(&string);
+    if let Some((line, keyword, vertices)) = preprocess::find_degenerate_primitive(&string) {
+        return Err(ObjError::UnsupportedPrimitive {
+            keyword: keyword.to_owned(),
+            vertices,
+            line,
+        });
+    }
+    let face_sizes = preprocess::face_sizes(&string);
+    let obj_set = obj::parse(&*string).map_err(|e| ObjError::Parse {
+        line_number: e.line_number,
+        message: e.message,
+    })?;
+    Ok(ParsedObj {
+        obj_set,
+        face_sizes,
+        skipped: skipped.len(),
+        material_libraries,
+        vertex_colors,
+    })
+}
+
+fn load_from_data(
+    parsed: &ParsedObj,
+    options: &ObjLoadOptions,
+    materials: &[Material],
+    progress: Option<Progress<'_>>,
+) -> Result<Vec<(ObjMesh, Vec<VTNIndex>)>, ObjError> {
     // Takes a list of objects that contain geometries that contain shapes that contain
     // vertex/texture/normal indices into the main list of vertices, and converts to
-    // MeshBuilders with Position, Normal, TexCoord.
+    // MeshBuilders with Position, Normal, TexCoord, Tangent.
     trace!("Loading mesh");
-    let mut objects = vec![];
+    let jobs = mesh_jobs(parsed, options, materials);
 
-    for object in obj_set.objects {
-        for geometry in &object.geometry {
-            let mut builder = MeshBuilder::new();
+    // The callback may not be shared between threads, so progress is reported
+    // from a sequential loop.
+    if let Some(progress) = progress {
+        let total = jobs.len();
+        let mut objects = Vec::new();
+        for (done, job) in jobs.into_iter().enumerate() {
+            objects.extend(job.load(options)?);
+            if let ControlFlow::Break(()) = progress(done + 1, total) {
+                trace!("Loading cancelled");
+                return Err(ObjError::Cancelled);
+            }
+        }
+        trace!("Loaded mesh");
+        return Ok(objects);
+    }
+
+    // Meshes are independent of each other, so they can be converted in parallel.
+    // Collecting keeps them in the order of the jobs either way.
+    #[cfg(feature = "parallel")]
+    let objects = {
+        use rayon::prelude::*;
+        jobs.into_par_iter()
+            .map(|job| job.load(options))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    #[cfg(not(feature = "parallel"))]
+    let objects = jobs
+        .into_iter()
+        .map(|job| job.load(options))
+        .collect::<Result<Vec<_>, _>>()?;
+    let objects = objects.into_iter().flatten().collect();
+
+    trace!("Loaded mesh");
+    Ok(objects)
+}
 
-            let mut indices = Vec::new();
+/// Split objects into meshes according to `options`.
+fn mesh_jobs<'a>(
+    parsed: &'a ParsedObj,
+    options: &ObjLoadOptions,
+    materials: &[Material],
+) -> Vec<MeshJob<'a>> {
+    let mut jobs = vec![];
+    let mut face_sizes = &parsed.face_sizes[..];
+    let mut vertex_colors = &parsed.vertex_colors[..];
 
-            geometry.shapes.iter().for_each(|shape| {
-                if let obj::Primitive::Triangle(v1, v2, v3) = shape.primitive {
-                    indices.push(v1);
-                    indices.push(v2);
-                    indices.push(v3);
+    for object in &parsed.obj_set.objects {
+        // Vertices are numbered within each object.
+        let (object_colors, rest) =
+            vertex_colors.split_at(object.vertices.len().min(vertex_colors.len()));
+        vertex_colors = rest;
+        let object_name = Some(object.name.clone()).filter(|name| !name.is_empty());
+        let find_material = |name: &Option<String>| {
+            name.as_ref().and_then(|name| {
+                let material = materials.iter().find(|material| &material.name == name);
+                if material.is_none() && !materials.is_empty() {
+                    warn!("Material '{}' is not defined", name);
                 }
-            });
-            // We can't use the vertices directly because we have per face normals and not per vertex normals in most obj files
-            // TODO: Compress duplicates and return indices for indexbuffer.
-            let positions = indices
+                material.cloned()
+            })
+        };
+
+        if options.merge_geometries {
+            let shapes = object
+                .geometry
                 .iter()
-                .map(|index| {
-                    let vertex: obj::Normal = object.vertices[index.0];
-                    Position([vertex.x as f32, vertex.y as f32, vertex.z as f32])
-                })
+                .flat_map(|geometry| geometry.shapes.iter().cloned())
                 .collect::<Vec<_>>();
+            if shapes.is_empty() {
+                continue;
+            }
+            let groups = common(shapes.iter().map(|shape| &shape.groups));
+            let material_name = common(
+                object
+                    .geometry
+                    .iter()
+                    .filter(|geometry| !geometry.shapes.is_empty())
+                    .map(|geometry| &geometry.material_name),
+            )
+            .cloned()
+            .flatten();
+            jobs.push(MeshJob {
+                object,
+                face_sizes: take_faces(&mut face_sizes, &shapes),
+                group_name: groups
+                    .filter(|groups| !groups.is_empty())
+                    .map(|groups| groups.join(" ")),
+                shapes: Cow::Owned(shapes),
+                vertex_colors: object_colors,
+                object_name,
+                material: find_material(&material_name),
+                material_name,
+            });
+            continue;
+        }
 
-            trace!("Loading normals");
-            let normals = indices
-                .iter()
-                .map(|index| {
-                    index
-                        .2
-                        .map(|i| {
-                            let normal: obj::Normal = object.normals[i];
-                            Normal([normal.x as f32, normal.y as f32, normal.z as f32])
-                        })
-                        .unwrap_or(Normal([0.0, 0.0, 0.0]))
+        for geometry in &object.geometry {
+            let material = find_material(&geometry.material_name);
+
+            for shapes in split_groups(&geometry.shapes) {
+                let groups = &shapes[0].groups;
+                jobs.push(MeshJob {
+                    object,
+                    shapes: Cow::Borrowed(shapes),
+                    face_sizes: take_faces(&mut face_sizes, shapes),
+                    vertex_colors: object_colors,
+                    object_name: object_name.clone(),
+                    group_name: Some(groups.join(" ")).filter(|_| !groups.is_empty()),
+                    material_name: geometry.material_name.clone(),
+                    material: material.clone(),
                 })
-                .collect::<Vec<_>>();
+            }
+        }
+    }
 
-            let tex_coords = indices
+    jobs
+}
+
+/// Shapes of a single mesh to be loaded, together with its names.
+#[derive(Debug)]
+struct MeshJob<'a> {
+    object: &'a obj::Object,
+    shapes: Cow<'a, [obj::Shape]>,
+    /// Number of corners of each face in `shapes`.
+    face_sizes: &'a [usize],
+    /// Colors of the vertices of the object, empty if it has none.
+    vertex_colors: &'a [Option<[f32; 4]>],
+    object_name: Option<String>,
+    group_name: Option<String>,
+    material_name: Option<String>,
+    material: Option<Material>,
+}
+
+/// Primitive types of the meshes a geometry is split into, in the order of the meshes.
+const TOPOLOGIES: [Primitive; 3] = [
+    Primitive::TriangleList,
+    Primitive::LineList,
+    Primitive::PointList,
+];
+
+impl MeshJob<'_> {
+    /// Colors of the vertices loaded from `remap`, if any of them has one.
+    ///
+    /// Vertices without a color are white.
+    fn colors(&self, remap: &[VTNIndex], options: &ObjLoadOptions) -> Option<Vec<Color>> {
+        if !options.attributes.contains(AttributeMask::COLOR)
+            || !remap
+                .iter()
+                .any(|index| matches!(self.vertex_colors.get(index.0), Some(Some(_))))
+        {
+            return None;
+        }
+        Some(
+            remap
                 .iter()
                 .map(|index| {
-                    index
-                        .1
-                        .map(|i| {
-                            let tvertex: obj::TVertex = object.tex_vertices[i];
-                            TexCoord([tvertex.u as f32, tvertex.v as f32])
-                        })
-                        .unwrap_or(TexCoord([0.0, 0.0]))
+                    let color = self.vertex_colors.get(index.0).copied().flatten();
+                    Color(color.unwrap_or([1.0; 4]))
                 })
-                .collect::<Vec<_>>();
+                .collect(),
+        )
+    }
+
+    /// Load a mesh for each primitive type present in the shapes.
+    fn load(self, options: &ObjLoadOptions) -> Result<Vec<(ObjMesh, Vec<VTNIndex>)>, ObjError> {
+        let mut meshes = Vec::new();
+        for &topology in &TOPOLOGIES {
+            let mut data = load_geometry(
+                self.object,
+                &self.shapes,
+                topology,
+                self.face_sizes,
+                options,
+            )?;
+            if data.indices.is_empty() {
+                continue;
+            }
+            data.colors = self.colors(&data.remap, options);
+            let remap = std::mem::take(&mut data.remap);
+            let smoothing_groups = data
+                .triangle_shapes
+                .iter()
+                .map(|&shape| smoothing_group(&self.shapes[shape]))
+                .collect();
+            let mesh = ObjMesh {
+                offset: data.offset,
+                normals_missing: data.normals_missing,
+                tex_coords_missing: data.tex_coords_missing,
+                smoothing_groups,
+                primitive_topology: topology,
+                builder: data.into_builder(),
+                object_name: self.object_name.clone(),
+                group_name: self.group_name.clone(),
+                material_name: self.material_name.clone(),
+                material: self.material.clone(),
+            };
+            meshes.push((mesh, remap));
+        }
+        Ok(meshes)
+    }
+}
+
+/// Load triangles of the meshes of a single object into one mesh with a submesh for each.
+fn load_submeshes(
+    jobs: &[MeshJob<'_>],
+    options: &ObjLoadOptions,
+) -> Result<Option<ObjObject>, ObjError> {
+    let object = jobs[0].object;
+    let shapes = jobs
+        .iter()
+        .flat_map(|job| job.shapes.iter().cloned())
+        .collect::<Vec<_>>();
+    let face_sizes = jobs
+        .iter()
+        .flat_map(|job| job.face_sizes.iter().copied())
+        .collect::<Vec<_>>();
+    let mut data = load_geometry(
+        object,
+        &shapes,
+        Primitive::TriangleList,
+        &face_sizes,
+        options,
+    )?;
+    if data.indices.is_empty() {
+        return Ok(None);
+    }
+    data.colors = jobs[0].colors(&data.remap, options);
+    if shapes
+        .iter()
+        .any(|shape| !matches!(shape.primitive, obj::Primitive::Triangle(..)))
+    {
+        warn!("Skipping lines and points of object '{}'", object.name);
+    }
 
-            // builder.set_indices(indices.iter().map(|i| i.0 as u16).collect::<Vec<u16>>());
+    // Triangles stay in the order of their shapes, so each job covers a contiguous range.
+    let mut submeshes = Vec::new();
+    let mut triangle_shapes = &data.triangle_shapes[..];
+    let mut shape_end = 0;
+    let mut index_start = 0;
+    for job in jobs {
+        shape_end += job.shapes.len();
+        let triangles = triangle_shapes
+            .iter()
+            .take_while(|&&shape| shape < shape_end)
+            .count();
+        triangle_shapes = &triangle_shapes[triangles..];
+        if triangles == 0 {
+            continue;
+        }
+        let index_count = triangles as u32 * 3;
+        submeshes.push(ObjSubmesh {
+            name: job.group_name.clone(),
+            material_name: job.material_name.clone(),
+            material: job.material.clone(),
+            index_start,
+            index_count,
+        });
+        index_start += index_count;
+    }
+    debug_assert!(triangle_shapes.is_empty());
 
-            debug_assert!(&normals.len() == &positions.len());
-            debug_assert!(&tex_coords.len() == &positions.len());
+    Ok(Some(ObjObject {
+        object_name: jobs[0].object_name.clone(),
+        offset: data.offset,
+        normals_missing: data.normals_missing,
+        tex_coords_missing: data.tex_coords_missing,
+        builder: data.into_builder(),
+        submeshes,
+    }))
+}
 
-            builder.add_vertices(positions);
-            builder.add_vertices(normals);
-            builder.add_vertices(tex_coords);
+/// Vertex attributes and indices of a single geometry
+/// before they are packed into a `MeshBuilder`.
+#[derive(Debug)]
+struct GeometryData {
+    offset: [f64; 3],
+    positions: Vec<Position>,
+    normals: Vec<Normal>,
+    tex_coords: Vec<TexCoord>,
+    tex_coords_3d: Option<Vec<TexCoord3>>,
+    tangents: Vec<Tangent>,
+    bitangents: Option<Vec<Bitangent>>,
+    /// Vertex colors, if any vertex of the file used by the geometry has one.
+    colors: Option<Vec<Color>>,
+    indices: Vec<u32>,
+    topology: Primitive,
+    /// Source indices of each vertex.
+    remap: Vec<VTNIndex>,
+    /// Index of the shape each triangle was created from.
+    triangle_shapes: Vec<usize>,
+    /// Attributes added to the builder. Vectors of the others may be empty.
+    attributes: AttributeMask,
+    /// Whether normals were left out because the file has none.
+    normals_missing: bool,
+    /// Whether texture coordinates were left out because the file has none.
+    tex_coords_missing: bool,
+}
 
-            // TODO: Add Material loading
-            objects.push((builder, geometry.material_name.clone()))
+impl GeometryData {
+    fn into_builder(self) -> MeshBuilder<'static> {
+        let mut builder = MeshBuilder::new();
+        builder.set_prim_type(self.topology);
+        builder.set_indices_auto(self.indices);
+        if self.attributes.contains(AttributeMask::POSITION) {
+            builder.add_vertices(self.positions);
+        }
+        if self.attributes.contains(AttributeMask::NORMAL) {
+            builder.add_vertices(self.normals);
+        }
+        if self.attributes.contains(AttributeMask::TEX_COORD) {
+            builder.add_vertices(self.tex_coords);
         }
+        if self.attributes.contains(AttributeMask::TANGENT) {
+            builder.add_vertices(self.tangents);
+        }
+        if let Some(tex_coords_3d) = self.tex_coords_3d {
+            builder.add_vertices(tex_coords_3d);
+        }
+        if let Some(bitangents) = self.bitangents {
+            builder.add_vertices(bitangents);
+        }
+        if let Some(colors) = self.colors {
+            builder.add_vertices(colors);
+        }
+        debug_assert_eq!(builder.validate(), Ok(()));
+        builder
     }
-    trace!("Loaded mesh");
-    Ok(objects)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// Returns the item shared by all `items`, or `None` if they differ.
+fn common<T: PartialEq>(mut items: impl Iterator<Item = T>) -> Option<T> {
+    let first = items.next()?;
+    if items.all(|item| item == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
 
-    #[test]
-    fn test_load_from_obj() {
-        let quad = b"v -1.000000 -1.000000 1.000000\nv 1.000000 -1.000000 1.000000\nv -1.000000 1.000000 1.000000\nv 1.000000 1.000000 1.000000\nv -1.000000 1.000000 -1.000000\nv 1.000000 1.000000 -1.000000\nv -1.000000 -1.000000 -1.000000\nv 1.000000 -1.000000 -1.000000\n
-vt 0.000000 0.000000\nvt 1.000000 0.000000\nvt 0.000000 1.000000\nvt 1.000000 1.000000\n
-vn 0.000000 0.000000 1.000000\nvn 0.000000 1.000000 0.000000\nvn 0.000000 0.000000 -1.000000\nvn 0.000000 -1.000000 0.000000\nvn 1.000000 0.000000 0.000000\nvn -1.000000 0.000000 0.000000\n
-s 1
-f 1/1/1 2/2/1 3/3/1\nf 3/3/1 2/2/1 4/4/1
-s 2
-f 3/1/2 4/2/2 5/3/2\nf 5/3/2 4/2/2 6/4/2
-s 3
-f 5/4/3 6/3/3 7/2/3\nf 7/2/3 6/3/3 8/1/3
-s 4
-f 7/1/4 8/2/4 1/3/4\nf 1/3/4 8/2/4 2/4/4
-s 5
-f 2/1/5 8/2/5 4/3/5\nf 4/3/5 8/2/5 6/4/5
-s 6
-f 7/1/6 1/2/6 5/3/6\nf 5/3/6 1/2/6 3/4/6
-";
-        let result = load_from_obj(quad).ok().unwrap();
-        // dbg!(& result);
-        assert_eq!(result.len(), 1);
+/// Owned copies of `names` without repetitions, in the order of their first occurrence.
+fn distinct<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut distinct: Vec<String> = Vec::new();
+    for name in names {
+        if !distinct.iter().any(|known| known == name) {
+            distinct.push(name.to_owned());
+        }
+    }
+    distinct
+}
 
-        // When compressed into unique vertices there should be 4 vertices per side of the quad
-        // assert!()
+/// Take sizes of the faces triangulated into `shapes` from the front of `face_sizes`.
+fn take_faces<'a>(face_sizes: &mut &'a [usize], shapes: &[obj::Shape]) -> &'a [usize] {
+    let triangles = shapes
+        .iter()
+        .filter(|shape| matches!(shape.primitive, obj::Primitive::Triangle(..)))
+        .count();
+    let mut covered = 0;
+    let mut count = 0;
+    while covered < triangles && count < face_sizes.len() {
+        covered += face_sizes[count].saturating_sub(2);
+        count += 1;
+    }
+    let (taken, rest) = face_sizes.split_at(count);
+    *face_sizes = rest;
+    taken
+}
+
+/// Split shapes into runs of consecutive shapes belonging to the same groups.
+fn split_groups(shapes: &[obj::Shape]) -> Vec<&[obj::Shape]> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in 1..=shapes.len() {
+        if end == shapes.len() || shapes[end].groups != shapes[start].groups {
+            runs.push(&shapes[start..end]);
+            start = end;
+        }
+    }
+    runs
+}
+
+/// Load primitives of `topology` in `shapes`.
+///
+/// Normals are generated and tangents computed only for triangles.
+fn load_geometry(
+    object: &obj::Object,
+    shapes: &[obj::Shape],
+    topology: Primitive,
+    face_sizes: &[usize],
+    options: &ObjLoadOptions,
+) -> Result<GeometryData, ObjError> {
+    let triangles = topology == Primitive::TriangleList;
+    let too_large = |kind, count, limit: Option<usize>| match limit {
+        Some(limit) if count > limit => Err(ObjError::TooLarge {
+            object: object.name.clone(),
+            kind,
+            count,
+            limit,
+        }),
+        _ => Ok(()),
+    };
+    let corner_count = shapes
+        .iter()
+        .map(|shape| match (&shape.primitive, topology) {
+            (obj::Primitive::Triangle(..), Primitive::TriangleList) => 3,
+            (obj::Primitive::Line(..), Primitive::LineList) => 2,
+            (obj::Primitive::Point(..), Primitive::PointList) => 1,
+            _ => 0,
+        })
+        .sum();
+    too_large("indices", corner_count, options.max_indices)?;
+    let mut indices = primitive_corners(object, shapes, topology)?;
+    let mut triangle_shapes = if triangles {
+        triangle_shapes(shapes)
+    } else {
+        Vec::new()
+    };
+    if triangles && options.polygon_mode == PolygonMode::EarClip {
+        let (clipped, clipped_shapes) =
+            ear_clip_faces(object, indices, triangle_shapes, face_sizes);
+        indices = clipped;
+        triangle_shapes = clipped_shapes;
+    }
+    if triangles && options.drop_degenerate {
+        let len = indices.len();
+        let (kept, kept_shapes): (Vec<_>, Vec<_>) = indices
+            .chunks_exact(3)
+            .zip(triangle_shapes)
+            .filter(|(triangle, _)| !is_degenerate(object, triangle))
+            .unzip();
+        indices = kept.into_iter().flatten().copied().collect();
+        triangle_shapes = kept_shapes;
+        if indices.len() < len {
+            trace!(
+                "Dropped {} degenerate triangles in object '{}'",
+                (len - indices.len()) / 3,
+                object.name
+            );
+        }
+    }
+
+    // We can't use the vertices directly because we have per face normals and not per vertex normals in most obj files.
+    // Instead every unique combination of position, texture coordinate and normal becomes a vertex.
+    // Vertices are ordered by these indices rather than by any hash, so loading the same bytes
+    // always produces identical buffers. Without welding every corner is a vertex of its own.
+    // Corners that get generated normals are also split by smoothing group.
+    let generate_normals = triangles && options.generate_normals;
+    let corners = indices
+        .iter()
+        .enumerate()
+        .map(|(corner, index)| {
+            let group = match triangle_shapes.get(corner / 3) {
+                Some(&shape) if generate_normals && index.2.is_none() => options
+                    .force_smoothing_group
+                    .unwrap_or_else(|| smoothing_group(&shapes[shape])),
+                _ => 0,
+            };
+            (*index, group)
+        })
+        .collect::<Vec<_>>();
+    let (vertices, mut indices) = if options.weld {
+        // Files exported with equal position, texture coordinate and normal indices
+        // need no sorting to find the unique corners.
+        #[cfg(any(test, feature = "bench"))]
+        let fast = options.weld_by_index;
+        #[cfg(not(any(test, feature = "bench")))]
+        let fast = true;
+        let by_index = if fast {
+            weld_by_index(&corners, |(index, _)| index.0)
+        } else {
+            None
+        };
+        by_index.unwrap_or_else(|| weld(&corners))
+    } else {
+        (
+            corners.iter().collect(),
+            (0..corners.len() as u32).collect(),
+        )
+    };
+    too_large("vertices", vertices.len(), options.max_vertices)?;
+    let reindex = vertices.iter().map(|vertex| &vertex.0).collect::<Vec<_>>();
+
+    let matrix = match options.handedness {
+        Handedness::RightHanded => options.transform,
+        Handedness::LeftHanded => {
+            let mut matrix = options.transform.unwrap_or([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            // Negating the Z row, the third element of every column, negates Z
+            // of the transformed positions, and the mirroring determinant
+            // takes care of normals and winding.
+            for column in matrix.iter_mut() {
+                column[2] = -column[2];
+            }
+            Some(matrix)
+        }
+    };
+    let transform = matrix.map(Transform::new);
+    let mirror = matches!(transform, Some(Transform { mirror: true, .. }));
+    if triangles && mirror != options.flip_winding {
+        // Mirroring reverses the winding, so it is reversed again to keep faces facing out.
+        for triangle in indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+    }
+    let vertex_position = |index: &VTNIndex| {
+        let vertex: obj::Vertex = object.vertices[index.0];
+        let position = [vertex.x, vertex.y, vertex.z];
+        transform.map_or(position, |transform| transform.point(position))
+    };
+
+    // Recentering is done in `f64` so that precision is only lost for the offset.
+    let offset = if options.recenter {
+        bounding_box_center(reindex.iter().map(|&index| vertex_position(index)))
+    } else {
+        [0.0; 3]
+    };
+    let positions = reindex
+        .iter()
+        .map(|index| {
+            let [x, y, z] = vertex_position(index);
+            let [x, y, z] = [x - offset[0], y - offset[1], z - offset[2]];
+            Position(match options.position_map {
+                Some(map) => map(x, y, z),
+                None => [x as f32, y as f32, z as f32],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let attributes = if triangles {
+        options.attributes
+    } else {
+        options.attributes & !AttributeMask::TANGENT
+    };
+    // Normals are left out only if none would be read from the file or computed.
+    let normals_missing = options.omit_missing_normals
+        && attributes.contains(AttributeMask::NORMAL)
+        && !(triangles && (options.generate_normals || !options.weld))
+        && reindex.iter().all(|index| index.2.is_none());
+    let attributes = if normals_missing {
+        attributes & !(AttributeMask::NORMAL | AttributeMask::TANGENT)
+    } else {
+        attributes
+    };
+    let tex_coords_missing =
+        options.omit_missing_tex_coords && reindex.iter().all(|index| index.1.is_none());
+    let attributes = if tex_coords_missing {
+        attributes & !AttributeMask::TEX_COORD
+    } else {
+        attributes
+    };
+    let emit_bitangent = triangles && options.emit_bitangent && !normals_missing;
+    let need_tangents = attributes.contains(AttributeMask::TANGENT) || emit_bitangent;
+    let need_normals = attributes.contains(AttributeMask::NORMAL) || need_tangents;
+    let need_tex_coords = attributes.contains(AttributeMask::TEX_COORD) || need_tangents;
+
+    trace!("Loading normals");
+    let mut normals = if need_normals {
+        reindex
+            .iter()
+            .map(|index| {
+                index
+                    .2
+                    .map(|i| {
+                        let normal: obj::Normal = object.normals[i];
+                        let normal = [normal.x, normal.y, normal.z];
+                        let [x, y, z] =
+                            transform.map_or(normal, |transform| transform.normal(normal));
+                        Normal(match options.normal_map {
+                            Some(map) => map(x, y, z),
+                            None => [x as f32, y as f32, z as f32],
+                        })
+                    })
+                    .unwrap_or(Normal(options.default_normal))
+            })
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
+    let tex_coord = |index: &VTNIndex| {
+        let [u, v] = options.default_tex_coord;
+        index.1.map_or([u, v, 0.0], |i| {
+            let tvertex: obj::TVertex = object.tex_vertices[i];
+            let u = if options.flip_u {
+                1.0 - tvertex.u
+            } else {
+                tvertex.u
+            };
+            let v = if options.flip_v {
+                1.0 - tvertex.v
+            } else {
+                tvertex.v
+            };
+            match options.tex_coord_map {
+                Some(map) => map(u, v, tvertex.w),
+                None => [u as f32, v as f32, tvertex.w as f32],
+            }
+        })
+    };
+    let tex_coords = if need_tex_coords {
+        reindex
+            .iter()
+            .map(|index| {
+                let [u, v, _] = tex_coord(index);
+                TexCoord([u, v])
+            })
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let tex_coords_3d = if options.tex_coords_3d && !tex_coords_missing {
+        Some(
+            reindex
+                .iter()
+                .map(|index| TexCoord3(tex_coord(index)))
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        None
+    };
+
+    // Normals can't be computed for lines and points.
+    if triangles && need_normals && !options.weld {
+        trace!("Computing face normals");
+        // Each vertex belongs to a single triangle, so the smooth normal is the face normal.
+        let keys = (0..positions.len()).collect::<Vec<_>>();
+        normals = compute_normals(&positions, &indices, &keys, options.normal_weighting);
+    } else if triangles
+        && need_normals
+        && options.generate_normals
+        && reindex.iter().any(|index| index.2.is_none())
+    {
+        trace!("Generating normals");
+        let keys = vertices
+            .iter()
+            .map(|&&(index, group)| (index.0, group))
+            .collect::<Vec<_>>();
+        let generated = compute_normals(&positions, &indices, &keys, options.normal_weighting);
+        for ((normal, generated), index) in normals.iter_mut().zip(generated).zip(&reindex) {
+            if index.2.is_none() {
+                *normal = generated;
+            }
+        }
+    }
+
+    let tangents = if need_tangents {
+        trace!("Computing tangents");
+        compute_tangents(
+            &positions,
+            &normals,
+            &tex_coords,
+            &indices,
+            options.tangent_mode,
+        )
+    } else {
+        Vec::new()
+    };
+    let bitangents = if emit_bitangent {
+        Some(
+            tangents
+                .iter()
+                .zip(&normals)
+                .map(|(tangent, normal)| {
+                    let [x, y, z, w] = tangent.0;
+                    Bitangent(scale(cross(normal.0, [x, y, z]), w))
+                })
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        None
+    };
+
+    Ok(GeometryData {
+        offset,
+        positions,
+        normals,
+        tex_coords,
+        tex_coords_3d,
+        tangents,
+        bitangents,
+        colors: None,
+        indices,
+        topology,
+        remap: reindex.into_iter().copied().collect(),
+        triangle_shapes,
+        attributes,
+        normals_missing,
+        tex_coords_missing,
+    })
+}
+
+/// Collect corners of all primitives of `topology` in `shapes`,
+/// checking that they reference existing data.
+fn primitive_corners(
+    object: &obj::Object,
+    shapes: &[obj::Shape],
+    topology: Primitive,
+) -> Result<Vec<VTNIndex>, ObjError> {
+    let mut indices = Vec::new();
+
+    // Polygonal faces are already fan-triangulated by the parser.
+    for (face, shape) in shapes.iter().enumerate() {
+        let start = indices.len();
+        match (&shape.primitive, topology) {
+            (&obj::Primitive::Triangle(v1, v2, v3), Primitive::TriangleList) => {
+                indices.extend_from_slice(&[v1, v2, v3])
+            }
+            (&obj::Primitive::Line(v1, v2), Primitive::LineList) => {
+                indices.extend_from_slice(&[v1, v2])
+            }
+            (&obj::Primitive::Point(v1), Primitive::PointList) => indices.push(v1),
+            _ => continue,
+        }
+        for index in &indices[start..] {
+            check_index(object, face, index)?;
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Index of each triangle in `shapes`, in the order of `primitive_corners`.
+fn triangle_shapes(shapes: &[obj::Shape]) -> Vec<usize> {
+    shapes
+        .iter()
+        .enumerate()
+        .filter(|(_, shape)| matches!(shape.primitive, obj::Primitive::Triangle(..)))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Smoothing group of `shape`, zero if it has none.
+fn smoothing_group(shape: &obj::Shape) -> u32 {
+    shape.smoothing_groups.first().copied().unwrap_or(0)
+}
+
+/// Center of the bounding box of `vertices`, or origin if there are none.
+fn bounding_box_center(mut vertices: impl Iterator<Item = [f64; 3]>) -> [f64; 3] {
+    let first = match vertices.next() {
+        Some(vertex) => vertex,
+        None => return [0.0; 3],
+    };
+    let (min, max) = vertices.fold((first, first), |(mut min, mut max), vertex| {
+        for (i, &value) in vertex.iter().enumerate() {
+            min[i] = min[i].min(value);
+            max[i] = max[i].max(value);
+        }
+        (min, max)
+    });
+    [
+        (min[0] + max[0]) / 2.0,
+        (min[1] + max[1]) / 2.0,
+        (min[2] + max[2]) / 2.0,
+    ]
+}
+
+/// Affine transform applied to loaded positions and normals.
+#[derive(Clone, Copy, Debug)]
+struct Transform {
+    /// Columns of the matrix.
+    columns: [[f64; 4]; 4],
+    /// Columns of the inverse transpose of the upper 3x3 matrix, scaled by its determinant.
+    normal_columns: [[f64; 3]; 3],
+    /// Whether the determinant is negative.
+    mirror: bool,
+}
+
+impl Transform {
+    fn new(matrix: [[f32; 4]; 4]) -> Self {
+        let mut columns = [[0.0; 4]; 4];
+        for (column, source) in columns.iter_mut().zip(&matrix) {
+            for (value, &source) in column.iter_mut().zip(source) {
+                *value = f64::from(source);
+            }
+        }
+        let [a, b, c] = [columns[0], columns[1], columns[2]];
+        let cross = |u: [f64; 4], v: [f64; 4]| {
+            [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ]
+        };
+        let normal_columns = [cross(b, c), cross(c, a), cross(a, b)];
+        let determinant = (0..3).map(|i| a[i] * normal_columns[0][i]).sum::<f64>();
+        Transform {
+            columns,
+            normal_columns,
+            mirror: determinant < 0.0,
+        }
+    }
+
+    fn point(&self, point: [f64; 3]) -> [f64; 3] {
+        let c = &self.columns;
+        let mut result = [0.0; 3];
+        for (i, value) in result.iter_mut().enumerate() {
+            *value = c[0][i] * point[0] + c[1][i] * point[1] + c[2][i] * point[2] + c[3][i];
+        }
+        result
+    }
+
+    /// Transform normal, keeping its length if it is a unit vector.
+    fn normal(&self, normal: [f64; 3]) -> [f64; 3] {
+        let c = &self.normal_columns;
+        let mut result = [0.0; 3];
+        for (i, value) in result.iter_mut().enumerate() {
+            *value = c[0][i] * normal[0] + c[1][i] * normal[1] + c[2][i] * normal[2];
+        }
+        let length = result.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if length == 0.0 {
+            return [0.0; 3];
+        }
+        // Dividing by the length instead of the determinant keeps the length of `normal`,
+        // only the sign of the determinant has to be applied.
+        let mut scale = normal.iter().map(|v| v * v).sum::<f64>().sqrt() / length;
+        if self.mirror {
+            scale = -scale;
+        }
+        [result[0] * scale, result[1] * scale, result[2] * scale]
+    }
+}
+
+/// Triangulate again faces fan-triangulated by the parser, using ear clipping.
+///
+/// Returns the new corners and the shape of each new triangle.
+fn ear_clip_faces(
+    object: &obj::Object,
+    corners: Vec<VTNIndex>,
+    triangle_shapes: Vec<usize>,
+    face_sizes: &[usize],
+) -> (Vec<VTNIndex>, Vec<usize>) {
+    let triangles: usize = face_sizes.iter().map(|size| size.saturating_sub(2)).sum();
+    if triangles * 3 != corners.len() {
+        warn!(
+            "Faces of object '{}' don't match their triangles, keeping fan triangulation",
+            object.name
+        );
+        return (corners, triangle_shapes);
+    }
+
+    let mut result = Vec::with_capacity(corners.len());
+    let mut shapes = Vec::with_capacity(triangle_shapes.len());
+    let mut rest = &corners[..];
+    let mut rest_shapes = &triangle_shapes[..];
+    for &size in face_sizes {
+        let (fan, tail) = rest.split_at(size.saturating_sub(2) * 3);
+        rest = tail;
+        let (fan_shapes, tail) = rest_shapes.split_at(fan.len() / 3);
+        rest_shapes = tail;
+        if size <= 3 {
+            result.extend_from_slice(fan);
+            shapes.extend_from_slice(fan_shapes);
+            continue;
+        }
+
+        // The fan consists of triangles `(apex, c[i], c[i + 1])`, so the polygon is
+        // the first triangle followed by the last corner of each other triangle.
+        let polygon = fan[1..3]
+            .iter()
+            .chain(fan.chunks_exact(3).skip(1).map(|triangle| &triangle[2]))
+            .chain(std::iter::once(&fan[0]))
+            .copied()
+            .collect::<Vec<_>>();
+        let points = polygon
+            .iter()
+            .map(|index| {
+                let vertex = object.vertices[index.0];
+                [vertex.x, vertex.y, vertex.z]
+            })
+            .collect::<Vec<_>>();
+        for triangle in triangulate::ear_clip(&points) {
+            result.extend(triangle.iter().map(|&i| polygon[i]));
+            shapes.push(fan_shapes[0]);
+        }
+    }
+    (result, shapes)
+}
+
+/// Whether the triangle references the same position twice
+/// or is too thin to be represented with `f32` positions.
+fn is_degenerate(object: &obj::Object, triangle: &[VTNIndex]) -> bool {
+    let (a, b, c) = (triangle[0].0, triangle[1].0, triangle[2].0);
+    if a == b || b == c || c == a {
+        return true;
+    }
+    let position = |index: usize| {
+        let vertex = object.vertices[index];
+        [vertex.x, vertex.y, vertex.z]
+    };
+    let (a, b, c) = (position(a), position(b), position(c));
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length_squared = |w: [f64; 3]| w[0] * w[0] + w[1] * w[1] + w[2] * w[2];
+    // Twice the area compared against the squared length of the longer edge.
+    let threshold = f64::from(f32::EPSILON) * length_squared(u).max(length_squared(v));
+    length_squared(n).sqrt() <= threshold
+}
+
+fn check_index(object: &obj::Object, face: usize, index: &VTNIndex) -> Result<(), ObjError> {
+    let checks = [
+        (Some(index.0), object.vertices.len(), "vertex"),
+        (index.1, object.tex_vertices.len(), "texture vertex"),
+        (index.2, object.normals.len(), "normal"),
+    ];
+
+    for &(index, len, kind) in &checks {
+        match index {
+            Some(index) if index >= len => {
+                return Err(ObjError::IndexOutOfRange {
+                    object: object.name.clone(),
+                    face,
+                    kind,
+                    index,
+                    len,
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Compute area-weighted smooth normals for indexed triangle list.
+///
+/// Vertices with equal `keys` share the normal, so that vertices
+/// split by differing texture coordinates are still smoothed together.
+fn compute_normals<K>(
+    positions: &[Position],
+    indices: &[u32],
+    keys: &[K],
+    weighting: NormalWeighting,
+) -> Vec<Normal>
+where
+    K: Copy + Eq + std::hash::Hash,
+{
+    let mut accumulated = HashMap::<K, [f32; 3]>::new();
+
+    for triangle in indices.chunks_exact(3) {
+        let [i0, i1, i2] = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let corners = [positions[i0].0, positions[i1].0, positions[i2].0];
+        // Length of the cross product is twice the triangle area.
+        let normal = cross(sub(corners[1], corners[0]), sub(corners[2], corners[0]));
+        let unit = match weighting {
+            NormalWeighting::Area => normal,
+            _ => match normalize(normal) {
+                Some(unit) => unit,
+                None => continue,
+            },
+        };
+        for (corner, &i) in [i0, i1, i2].iter().enumerate() {
+            let weighted = match weighting {
+                NormalWeighting::Angle => scale(unit, corner_angle(corners, corner)),
+                _ => unit,
+            };
+            let sum = accumulated.entry(keys[i]).or_insert([0.0; 3]);
+            *sum = add(*sum, weighted);
+        }
+    }
+
+    keys.iter()
+        .map(|key| {
+            let normal = accumulated.get(key).and_then(|&normal| normalize(normal));
+            Normal(normal.unwrap_or([0.0, 0.0, 0.0]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tangent::dot;
+    use std::path::Path;
+
+    /// Parse `obj` and load the triangles of the first geometry of its first object.
+    fn load_test_geometry(obj: &str, options: &ObjLoadOptions) -> GeometryData {
+        let parsed = parse_obj(obj.as_bytes(), false).unwrap();
+        let object = &parsed.obj_set.objects[0];
+        let shapes = &object.geometry[0].shapes;
+        let face_sizes = take_faces(&mut &parsed.face_sizes[..], shapes);
+        load_geometry(object, shapes, Primitive::TriangleList, face_sizes, options).unwrap()
+    }
+
+    const QUAD: &[u8] = b"v -1.000000 -1.000000 1.000000\nv 1.000000 -1.000000 1.000000\nv -1.000000 1.000000 1.000000\nv 1.000000 1.000000 1.000000\nv -1.000000 1.000000 -1.000000\nv 1.000000 1.000000 -1.000000\nv -1.000000 -1.000000 -1.000000\nv 1.000000 -1.000000 -1.000000\n
+vt 0.000000 0.000000\nvt 1.000000 0.000000\nvt 0.000000 1.000000\nvt 1.000000 1.000000\n
+vn 0.000000 0.000000 1.000000\nvn 0.000000 1.000000 0.000000\nvn 0.000000 0.000000 -1.000000\nvn 0.000000 -1.000000 0.000000\nvn 1.000000 0.000000 0.000000\nvn -1.000000 0.000000 0.000000\n
+s 1
+f 1/1/1 2/2/1 3/3/1\nf 3/3/1 2/2/1 4/4/1
+s 2
+f 3/1/2 4/2/2 5/3/2\nf 5/3/2 4/2/2 6/4/2
+s 3
+f 5/4/3 6/3/3 7/2/3\nf 7/2/3 6/3/3 8/1/3
+s 4
+f 7/1/4 8/2/4 1/3/4\nf 1/3/4 8/2/4 2/4/4
+s 5
+f 2/1/5 8/2/5 4/3/5\nf 4/3/5 8/2/5 6/4/5
+s 6
+f 7/1/6 1/2/6 5/3/6\nf 5/3/6 1/2/6 3/4/6
+";
+
+    #[test]
+    fn test_load_from_obj() {
+        let result = load_from_obj(QUAD).ok().unwrap();
+        // dbg!(& result);
+        assert_eq!(result.len(), 1);
+
+        // When compressed into unique vertices there should be 4 vertices per side of the quad
+        // assert!()
+    }
+
+    #[test]
+    fn test_load_tangents() {
+        let data = load_test_geometry(
+            std::str::from_utf8(QUAD).unwrap(),
+            &ObjLoadOptions::default(),
+        );
+        assert!(!data.tangents.is_empty());
+        assert_eq!(data.tangents.len(), data.positions.len());
+        assert!(data
+            .tangents
+            .iter()
+            .all(|t| t.0.iter().all(|c| c.is_finite())));
+    }
+
+    /// `QUAD` with normals removed.
+    fn quad_without_normals() -> String {
+        std::str::from_utf8(QUAD)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("vn"))
+            .map(|line| {
+                line.split(' ')
+                    .map(|word| word.rsplitn(2, '/').last().unwrap())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_tangents_orthogonal_to_normals() {
+        // Smooth normals of the cube corners are diagonal, unlike the face tangents.
+        let cube = quad_without_normals();
+        for &tangent_mode in &[TangentMode::Simple, TangentMode::MikkTSpace] {
+            let options = ObjLoadOptions {
+                generate_normals: true,
+                tangent_mode,
+                ..ObjLoadOptions::default()
+            };
+            let (builder, _) = load_from_obj_with_options(cube.as_bytes(), options)
+                .unwrap()
+                .remove(0);
+            let normals = builder.read_attribute::<Normal>().unwrap();
+            let tangents = builder.read_attribute::<Tangent>().unwrap();
+            assert_eq!(normals.len(), tangents.len());
+            for (normal, tangent) in normals.iter().zip(&tangents) {
+                let [x, y, z, _] = tangent.0;
+                assert!(dot(normal.0, [x, y, z]).abs() < 1e-5);
+                assert!((dot([x, y, z], [x, y, z]) - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tangents_of_small_tex_coords() {
+        // Texture coordinates increase along y, unlike the fallback tangent along x.
+        let quad = |scale: f32| {
+            format!(
+                "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0 1\n\
+                 vt 0 0\nvt 0 {0}\nvt {0} {0}\nvt {0} 0\n\
+                 f 1/1/1 2/2/1 3/3/1 4/4/1\n",
+                scale
+            )
+        };
+        for &tangent_mode in &[TangentMode::Simple, TangentMode::MikkTSpace] {
+            let options = ObjLoadOptions {
+                tangent_mode,
+                ..ObjLoadOptions::default()
+            };
+            let tangents = |scale| {
+                let (builder, _) = load_from_obj_with_options(quad(scale).as_bytes(), options)
+                    .unwrap()
+                    .remove(0);
+                builder.read_attribute::<Tangent>().unwrap()
+            };
+            let (full, small) = (tangents(1.0), tangents(1e-4));
+            assert_eq!(full.len(), small.len());
+            for (full, small) in full.iter().zip(&small) {
+                assert!((full.0[1] - 1.0).abs() < 1e-5);
+                for (a, b) in full.0.iter().zip(&small.0) {
+                    assert!((a - b).abs() < 1e-5, "{:?} != {:?}", full, small);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_tangents_without_tex_coords() {
+        let triangle = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//1\n";
+        let data = load_test_geometry(triangle, &ObjLoadOptions::default());
+        assert_eq!(data.tangents.len(), 3);
+        for tangent in &data.tangents {
+            assert_eq!(tangent.0, [1.0, 0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn test_default_attributes() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvt 0.5 0.5\nvn 1 0 0\n\
+                    f 1/1/1 2/1/1 3/1/1\nf 3 2 4\n";
+        let options = ObjLoadOptions {
+            default_normal: [0.0, 0.0, 1.0],
+            default_tex_coord: [0.25, 0.75],
+            ..ObjLoadOptions::default()
+        };
+        let (mesh, remap) = load_from_obj_with_remap(obj, options).unwrap().remove(0);
+        let normals = mesh.builder.read_attribute::<Normal>().unwrap();
+        let tex_coords = mesh.builder.read_attribute::<TexCoord>().unwrap();
+        assert_eq!(remap.len(), 6);
+        for ((source, normal), tex_coord) in remap.iter().zip(&normals).zip(&tex_coords) {
+            match source.2 {
+                Some(_) => assert_eq!(normal.0, [1.0, 0.0, 0.0]),
+                None => assert_eq!(normal.0, [0.0, 0.0, 1.0]),
+            }
+            match source.1 {
+                Some(_) => assert_eq!(tex_coord.0, [0.5, 0.5]),
+                None => assert_eq!(tex_coord.0, [0.25, 0.75]),
+            }
+        }
+    }
+
+    #[test]
+    fn test_omit_missing_tex_coords() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let options = ObjLoadOptions {
+            omit_missing_tex_coords: true,
+            omit_missing_normals: true,
+            tex_coords_3d: true,
+            ..ObjLoadOptions::default()
+        };
+        let mesh = load_from_obj_grouped(obj, options).unwrap().remove(0);
+        assert!(mesh.tex_coords_missing && mesh.normals_missing);
+        assert_eq!(mesh.builder.read_attribute::<TexCoord>(), None);
+        assert_eq!(mesh.builder.read_attribute::<TexCoord3>(), None);
+        assert_eq!(mesh.builder.read_attribute::<Normal>(), None);
+        assert!(mesh.builder.read_attribute::<Position>().is_some());
+
+        let mesh = load_from_obj_grouped(obj, ObjLoadOptions::default())
+            .unwrap()
+            .remove(0);
+        assert!(!mesh.tex_coords_missing);
+        assert!(mesh.builder.read_attribute::<TexCoord>().is_some());
+    }
+
+    #[test]
+    fn test_omit_missing_normals() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nf 1/1 2/2 3/3\n";
+        let options = ObjLoadOptions {
+            omit_missing_normals: true,
+            ..ObjLoadOptions::default()
+        };
+        let mesh = load_from_obj_grouped(obj, options).unwrap().remove(0);
+        assert!(mesh.normals_missing);
+        assert_eq!(mesh.builder.read_attribute::<Normal>(), None);
+        assert_eq!(mesh.builder.read_attribute::<Tangent>(), None);
+        assert_eq!(mesh.builder.vertex_count(), 3);
+
+        let generated = ObjLoadOptions {
+            generate_normals: true,
+            ..options
+        };
+        let mesh = load_from_obj_grouped(obj, generated).unwrap().remove(0);
+        assert!(!mesh.normals_missing);
+        assert!(mesh.builder.read_attribute::<Normal>().is_some());
+
+        let mesh = load_from_obj_grouped(QUAD, options).unwrap().remove(0);
+        assert!(!mesh.normals_missing);
+        assert!(mesh.builder.read_attribute::<Normal>().is_some());
+    }
+
+    #[test]
+    fn test_mikktspace_handedness() {
+        let load = |tex_coords: &str| {
+            let triangle = format!(
+                "v 0 0 0\nv 1 0 0\nv 0 1 0\n{}vn 0 0 1\nf 1/1/1 2/2/1 3/3/1\n",
+                tex_coords
+            );
+            let options = ObjLoadOptions {
+                tangent_mode: TangentMode::MikkTSpace,
+                ..ObjLoadOptions::default()
+            };
+            load_test_geometry(&triangle, &options).tangents
+        };
+
+        for tangent in load("vt 0 0\nvt 1 0\nvt 0 1\n") {
+            assert_eq!(tangent.0, [1.0, 0.0, 0.0, 1.0]);
+        }
+        // Mirrored along u, so the tangent points along -x with the bitangent still along +y.
+        for tangent in load("vt 1 0\nvt 0 0\nvt 1 1\n") {
+            assert_eq!(tangent.0, [-1.0, 0.0, 0.0, -1.0]);
+        }
+    }
+
+    #[test]
+    fn test_emit_bitangent() {
+        let options = ObjLoadOptions {
+            emit_bitangent: true,
+            tangent_mode: TangentMode::MikkTSpace,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_with_options(QUAD, options).unwrap();
+        let builder = &result[0].0;
+        let normals = builder.read_attribute::<Normal>().unwrap();
+        let tangents = builder.read_attribute::<Tangent>().unwrap();
+        let bitangents = builder.read_attribute::<Bitangent>().unwrap();
+        assert_eq!(bitangents.len(), normals.len());
+        for ((normal, tangent), bitangent) in normals.iter().zip(&tangents).zip(&bitangents) {
+            let [x, y, z, w] = tangent.0;
+            let expected = scale(cross(normal.0, [x, y, z]), w);
+            assert!(dot(expected, bitangent.0) > 1.0 - 1e-6);
+        }
+        assert!(load_from_obj(QUAD).unwrap()[0]
+            .0
+            .read_attribute::<Bitangent>()
+            .is_none());
+    }
+
+    #[test]
+    fn test_attribute_mask() {
+        let options = ObjLoadOptions {
+            attributes: AttributeMask::POSITION,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_with_options(QUAD, options).unwrap();
+        let full = load_from_obj(QUAD).unwrap();
+        let builder = &result[0].0;
+        assert!(builder.read_attribute::<Normal>().is_none());
+        assert!(builder.read_attribute::<TexCoord>().is_none());
+        assert!(builder.read_attribute::<Tangent>().is_none());
+        assert_eq!(
+            builder.read_attribute::<Position>(),
+            full[0].0.read_attribute::<Position>()
+        );
+        assert_eq!(builder.read_indices(), full[0].0.read_indices());
+
+        let data = load_test_geometry(std::str::from_utf8(QUAD).unwrap(), &options);
+        assert!(data.normals.is_empty() && data.tex_coords.is_empty() && data.tangents.is_empty());
+    }
+
+    #[test]
+    fn test_load_lines_and_points() {
+        let obj = b"o lines\nv 0 0 0\nv 1 0 0\nv 0 1 0\nl 1 2\n";
+        let result = load_from_obj_grouped(obj, ObjLoadOptions::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].primitive_topology, Primitive::LineList);
+        assert_eq!(result[0].builder.read_indices().unwrap(), [0, 1]);
+        let mesh = &result[0].builder;
+        assert_eq!(mesh.read_attribute::<Position>().unwrap().len(), 2);
+        assert!(mesh.read_attribute::<Tangent>().is_none());
+        assert_eq!(mesh.validate(), Ok(()));
+
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\np 3\nl 1 2 3\n";
+        let result = load_from_obj_grouped(obj, ObjLoadOptions::default()).unwrap();
+        let topologies = result
+            .iter()
+            .map(|mesh| mesh.primitive_topology)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            topologies,
+            [
+                Primitive::TriangleList,
+                Primitive::LineList,
+                Primitive::PointList
+            ]
+        );
+        assert_eq!(result[1].builder.read_indices().unwrap().len(), 4);
+        assert_eq!(result[2].builder.read_indices().unwrap().len(), 1);
+
+        let stats = obj_stats(obj).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[1].primitive_topology, Primitive::LineList);
+        assert_eq!((stats[1].vertices, stats[1].triangles), (4, 0));
+    }
+
+    #[test]
+    fn test_load_quad_face() {
+        let quad = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\nf 1/1/1 2/2/1 3/3/1 4/4/1\n";
+        let data = load_test_geometry(quad, &ObjLoadOptions::default());
+        assert_eq!(data.indices.len(), 6);
+        assert_eq!(data.positions.len(), 4);
+    }
+
+    #[test]
+    fn test_load_with_materials() {
+        let obj = b"mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl red\nf 1 2 3\n";
+        let mtl = b"newmtl red\nKa 0.1 0.1 0.1\nKd 1 0 0\nKs 0.5\nmap_Kd red.png\n";
+        let mut requested = vec![];
+        let result = load_from_obj_with_materials(obj, |name| {
+            requested.push(name.to_owned());
+            Some(mtl.to_vec())
+        })
+        .unwrap();
+
+        assert_eq!(requested, ["quad.mtl"]);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].material_name.as_ref().unwrap(), "red");
+        let material = result[0].material.as_ref().unwrap();
+        assert_eq!(material.ambient, Some([0.1, 0.1, 0.1]));
+        assert_eq!(material.diffuse, Some([1.0, 0.0, 0.0]));
+        assert_eq!(material.specular, Some([0.5, 0.5, 0.5]));
+        assert_eq!(material.textures.diffuse.as_ref().unwrap(), "red.png");
+    }
+
+    #[test]
+    fn test_material_textures() {
+        let mtl = b"newmtl wood\n\
+                    map_Kd -s 2 2 2 wood.png\n\
+                    map_Ks -o 0.5 -clamp on wood spec.png\n\
+                    map_Bump -bm 0.5 wood_bump.png\n\
+                    map_d -imfchan m -unknown alpha.png\n\
+                    norm -s 2 wood_normal.png\n";
+        let materials = mtl::parse("wood.mtl", mtl).unwrap();
+        assert_eq!(
+            materials[0].textures,
+            MaterialTextures {
+                ambient: None,
+                diffuse: Some("wood.png".to_owned()),
+                specular: Some("wood spec.png".to_owned()),
+                bump: Some("wood_bump.png".to_owned()),
+                dissolve: Some("alpha.png".to_owned()),
+                normal: Some("wood_normal.png".to_owned()),
+            }
+        );
+        assert!(mtl::parse("wood.mtl", b"newmtl wood\nmap_Kd -s 2\n").is_err());
+    }
+
+    #[test]
+    fn test_material_parameters() {
+        let mtl = b"newmtl glossy\nNs 96.0\nNi 1.5\nd 0.5\nillum 2\n\
+                    newmtl glass\nTr 0.75\nd -halo 0.5\nTr 0.75\n\
+                    newmtl plain\nKd 1 1 1\n";
+        let materials = mtl::parse("materials.mtl", mtl).unwrap();
+        let glossy = &materials[0];
+        assert_eq!(glossy.specular_exponent, Some(96.0));
+        assert_eq!(glossy.optical_density, Some(1.5));
+        assert_eq!(glossy.dissolve, Some(0.5));
+        assert_eq!(glossy.illumination_model, Some(2));
+        assert_eq!(materials[1].dissolve, Some(0.25));
+        assert_eq!(materials[1].illumination_model, None);
+        assert_eq!(materials[2].dissolve, None);
+        assert_eq!(materials[2].specular_exponent, None);
+
+        assert!(mtl::parse("materials.mtl", b"newmtl bad\nNs\n").is_err());
+        assert!(mtl::parse("materials.mtl", b"newmtl bad\nillum 2.5\n").is_err());
+    }
+
+    #[test]
+    fn test_load_with_multiple_material_libraries() {
+        let obj = b"mtllib a.mtl b.mtl\nmtllib c.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                    usemtl red\nf 1 2 3\nusemtl blue\nf 1 3 4\n";
+        let mut requested = vec![];
+        let result = load_from_obj_with_materials(obj, |name| {
+            requested.push(name.to_owned());
+            match name {
+                "a.mtl" => Some(b"newmtl red\nKd 1 0 0\nnewmtl blue\nKd 0 0 0.5\n".to_vec()),
+                "b.mtl" => Some(b"newmtl blue\nKd 0 0 1\n".to_vec()),
+                _ => None,
+            }
+        })
+        .unwrap();
+
+        assert_eq!(requested, ["a.mtl", "b.mtl", "c.mtl"]);
+        let diffuse = result
+            .iter()
+            .map(|mesh| mesh.material.as_ref().unwrap().diffuse)
+            .collect::<Vec<_>>();
+        assert_eq!(diffuse, [Some([1.0, 0.0, 0.0]), Some([0.0, 0.0, 1.0])]);
+    }
+
+    #[test]
+    fn test_split_group_by_material() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                    g quad\nusemtl red\nf 1 2 3\nusemtl blue\nf 1 3 4\nf 4 3 2\n";
+        let result = load_from_obj(obj).unwrap();
+        let materials = result
+            .iter()
+            .map(|(_, material)| material.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(materials, [Some("red"), Some("blue")]);
+        assert_eq!(result[0].0.read_indices().unwrap().len(), 3);
+        assert_eq!(result[1].0.read_indices().unwrap().len(), 6);
+
+        let grouped = load_from_obj_grouped(obj, ObjLoadOptions::default()).unwrap();
+        assert!(grouped
+            .iter()
+            .all(|mesh| mesh.group_name.as_deref() == Some("quad")));
+    }
+
+    #[test]
+    fn test_load_submeshes() {
+        // Put each side of the quad into a group of its own.
+        let obj = std::str::from_utf8(QUAD)
+            .unwrap()
+            .lines()
+            .map(|line| match line.strip_prefix("s ") {
+                Some(side) => format!("g side{}\n{}\n", side, line),
+                None => format!("{}\n", line),
+            })
+            .collect::<String>()
+            .into_bytes();
+        assert_eq!(
+            load_from_obj_grouped(&obj, ObjLoadOptions::default())
+                .unwrap()
+                .len(),
+            6
+        );
+
+        let objects = load_from_obj_submeshes(&obj, ObjLoadOptions::default(), |_| None).unwrap();
+        assert_eq!(objects.len(), 1);
+        let object = &objects[0];
+        let positions = |builder: &MeshBuilder<'_>| builder.read_attribute::<Position>().unwrap();
+        assert_eq!(
+            positions(&object.builder),
+            positions(&load_from_obj(QUAD).unwrap()[0].0)
+        );
+        assert_eq!(object.submeshes.len(), 6);
+        let mut next = 0;
+        for (side, submesh) in object.submeshes.iter().enumerate() {
+            assert_eq!(submesh.name, Some(format!("side{}", side + 1)));
+            assert_eq!(submesh.index_start, next);
+            assert_eq!(submesh.index_count, 6);
+            next += submesh.index_count;
+        }
+        assert_eq!(next as usize, object.builder.read_indices().unwrap().len());
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let bad = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 99/1/1 2/1/1 3/1/1\n";
+        assert!(load_from_obj(bad).is_err());
+
+        let vertex = obj::Vertex {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let object = obj::Object {
+            name: "broken".to_owned(),
+            vertices: vec![vertex; 3],
+            tex_vertices: vec![],
+            normals: vec![vertex],
+            geometry: vec![obj::Geometry {
+                material_name: None,
+                shapes: vec![obj::Shape {
+                    primitive: obj::Primitive::Triangle(
+                        (0, None, Some(0)),
+                        (1, None, Some(0)),
+                        (99, None, Some(0)),
+                    ),
+                    groups: vec![],
+                    smoothing_groups: vec![],
+                }],
+            }],
+        };
+        assert_eq!(
+            load_geometry(
+                &object,
+                &object.geometry[0].shapes,
+                Primitive::TriangleList,
+                &[],
+                &ObjLoadOptions::default()
+            )
+            .unwrap_err(),
+            ObjError::IndexOutOfRange {
+                object: "broken".to_owned(),
+                face: 0,
+                kind: "vertex",
+                index: 99,
+                len: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            load_from_obj(b"v 0 0 0\n\xff").unwrap_err(),
+            ObjError::InvalidUtf8 { position: 8 }
+        );
+        match load_from_obj(b"v 0 0 0\nv 1 0 0\nf 1 2 x\n").unwrap_err() {
+            ObjError::Parse { line_number, .. } => assert_eq!(line_number, 3),
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let options = ObjLoadOptions {
+            generate_normals: true,
+            merge_geometries: true,
+            ..ObjLoadOptions::default()
+        };
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvt 0 0\nvt 1 1\n\
+                    g a\nf 4/2 3/1 1/1\ng b\nf 1/2 2/1 4/2\nf 3 2 1\n";
+        let first = load_from_obj_with_options(obj, options).unwrap();
+        let second = load_from_obj_with_options(obj, options).unwrap();
+        assert_eq!(first.len(), second.len());
+        for ((first, _), (second, _)) in first.iter().zip(&second) {
+            assert_eq!(first.content_hash(), second.content_hash());
+            assert_eq!(first.read_indices(), second.read_indices());
+            assert_eq!(
+                first.read_attribute::<Position>(),
+                second.read_attribute::<Position>()
+            );
+            assert_eq!(
+                first.read_attribute::<Normal>(),
+                second.read_attribute::<Normal>()
+            );
+            assert_eq!(
+                first.read_attribute::<TexCoord>(),
+                second.read_attribute::<TexCoord>()
+            );
+            assert_eq!(
+                first.read_attribute::<Tangent>(),
+                second.read_attribute::<Tangent>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_to_obj() {
+        /// Triangles rotated to start at their smallest index.
+        fn triangles(builder: &MeshBuilder<'_>) -> Vec<[u32; 3]> {
+            let mut triangles = builder
+                .read_indices()
+                .unwrap()
+                .chunks_exact(3)
+                .map(|t| {
+                    let first = (0..3).min_by_key(|&i| t[i]).unwrap();
+                    [t[first], t[(first + 1) % 3], t[(first + 2) % 3]]
+                })
+                .collect::<Vec<_>>();
+            triangles.sort();
+            triangles
+        }
+
+        let (builder, _) = load_from_obj(QUAD).unwrap().remove(0);
+        let mut obj = Vec::new();
+        write_to_obj(&builder, &mut obj).unwrap();
+        let text = std::str::from_utf8(&obj).unwrap();
+        let face = text.lines().find(|line| line.starts_with("f ")).unwrap();
+        for corner in face.split(' ').skip(1) {
+            let parts = corner.split('/').collect::<Vec<_>>();
+            assert_eq!(parts, [parts[0]; 3]);
+        }
+
+        let (reloaded, _) = load_from_obj(&obj).unwrap().remove(0);
+        assert_eq!(triangles(&reloaded), triangles(&builder));
+        assert_eq!(
+            reloaded.read_attribute::<Position>(),
+            builder.read_attribute::<Position>()
+        );
+        assert_eq!(
+            reloaded.read_attribute::<Normal>(),
+            builder.read_attribute::<Normal>()
+        );
+        assert_eq!(
+            reloaded.read_attribute::<TexCoord>(),
+            builder.read_attribute::<TexCoord>()
+        );
+
+        let lines = MeshBuilder::new()
+            .with_prim_type(Primitive::LineList)
+            .with_vertices(vec![Position([0.0, 0.0, 0.0]), Position([1.0, 0.5, 0.0])]);
+        let mut obj = Vec::new();
+        write_to_obj(&lines, &mut obj).unwrap();
+        assert_eq!(obj, b"v 0 0 0\nv 1 0.5 0\nl 1 2\n");
+
+        let empty = MeshBuilder::new();
+        assert_eq!(
+            write_to_obj(&empty, &mut Vec::new()).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+
+        let triangle = || {
+            MeshBuilder::new().with_vertices(vec![
+                Position([0.0, 0.0, 0.0]),
+                Position([1.0, 0.0, 0.0]),
+                Position([0.0, 1.0, 0.0]),
+            ])
+        };
+        let partial = triangle().with_indices(vec![0u32, 1, 2, 0]);
+        let out_of_range = triangle().with_indices(vec![0u32, 1, u32::MAX]);
+        for invalid in &[partial, out_of_range] {
+            let mut obj = Vec::new();
+            assert_eq!(
+                write_to_obj(invalid, &mut obj).unwrap_err().kind(),
+                std::io::ErrorKind::InvalidInput
+            );
+            assert!(obj.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_load_from_obj_reader() {
+        let result = load_from_obj_reader(std::io::Cursor::new(QUAD)).unwrap();
+        assert_eq!(result.len(), load_from_obj(QUAD).unwrap().len());
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_load_from_obj_path() {
+        let path = std::env::temp_dir().join("rendy_mesh_test_load_from_obj_path.obj");
+        std::fs::write(&path, QUAD).unwrap();
+        let result = load_from_obj_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            format!("{:?}", result.unwrap()),
+            format!("{:?}", load_from_obj(QUAD).unwrap())
+        );
+        assert_eq!(
+            load_from_obj_path(&path).unwrap_err(),
+            ObjError::Io(std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_from_obj_gz() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(QUAD).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(
+            format!("{:?}", load_from_obj_gz(&compressed).unwrap()),
+            format!("{:?}", load_from_obj(QUAD).unwrap())
+        );
+        assert!(matches!(
+            load_from_obj_gz(&compressed[..compressed.len() / 2]),
+            Err(ObjError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_load_with_progress() {
+        let obj = b"o a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+                    o b\nv 0 0 1\nv 1 0 1\nv 0 1 1\nf 4 5 6\n";
+        let mut calls = Vec::new();
+        let meshes = load_from_obj_with_progress(obj, |done, total| {
+            calls.push((done, total));
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(calls, [(1, 2), (2, 2)]);
+
+        let mut calls = 0;
+        let result = load_from_obj_with_progress(obj, |_, _| {
+            calls += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(result.unwrap_err(), ObjError::Cancelled);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_load_with_remap() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nvt 0 0\nvt 1 1\n\
+                    f 4/2 3/1 1/1\nf 1/1 2/1 4/2\n";
+        let result = load_from_obj_with_remap(obj, ObjLoadOptions::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        let (mesh, remap) = &result[0];
+        assert_eq!(
+            remap,
+            &[
+                (0, Some(0), None),
+                (1, Some(0), None),
+                (2, Some(0), None),
+                (3, Some(1), None)
+            ]
+        );
+        let positions = mesh.builder.read_attribute::<Position>().unwrap();
+        assert_eq!(positions.len(), remap.len());
+        for (position, index) in positions.iter().zip(remap) {
+            let vertex = [index.0 % 2, index.0 / 2];
+            assert_eq!(position.0, [vertex[0] as f32, vertex[1] as f32, 0.0]);
+        }
+
+        let options = ObjLoadOptions {
+            weld: false,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_with_remap(obj, options).unwrap();
+        assert_eq!(result[0].1.len(), 6);
+        assert_eq!(
+            result[0]
+                .1
+                .iter()
+                .filter(|&&index| index == (0, Some(0), None))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let data = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\n";
+        let relative = format!("{}f -3/-3/-1 -2/-2/-1 -1/-1/-1 # relative\n", data);
+        let absolute = format!("{}f 1/1/1 2/2/1 3/3/1 # relative\n", data);
+        assert_eq!(
+            preprocess::resolve_relative_indices(&relative),
+            absolute.as_str()
+        );
+        assert_eq!(
+            format!("{:?}", load_from_obj(relative.as_bytes()).unwrap()),
+            format!("{:?}", load_from_obj(absolute.as_bytes()).unwrap())
+        );
+        assert!(matches!(
+            preprocess::resolve_relative_indices(&absolute),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_coordinate_maps() {
+        let obj = b"v 1 2 3\nv 4 5 6\nv 7 8 10\nvt 0.25 0.5\nvn 0 1 0\nf 1/1/1 2/1/1 3/1/1\n";
+        fn swap_y_z(x: f64, y: f64, z: f64) -> [f32; 3] {
+            [x as f32, z as f32, y as f32]
+        }
+        let options = ObjLoadOptions {
+            position_map: Some(swap_y_z),
+            normal_map: Some(swap_y_z),
+            tex_coord_map: Some(|u, v, w| [v as f32, u as f32, w as f32]),
+            ..ObjLoadOptions::default()
+        };
+        let (builder, _) = load_from_obj_with_options(obj, options).unwrap().remove(0);
+        let mut positions = builder.read_attribute::<Position>().unwrap();
+        positions.sort_by(|a, b| a.0[0].partial_cmp(&b.0[0]).unwrap());
+        assert_eq!(
+            positions,
+            [
+                Position([1.0, 3.0, 2.0]),
+                Position([4.0, 6.0, 5.0]),
+                Position([7.0, 10.0, 8.0]),
+            ]
+        );
+        assert!(builder
+            .read_attribute::<Normal>()
+            .unwrap()
+            .iter()
+            .all(|normal| normal.0 == [0.0, 0.0, 1.0]));
+        assert!(builder
+            .read_attribute::<TexCoord>()
+            .unwrap()
+            .iter()
+            .all(|tex_coord| tex_coord.0 == [0.5, 0.25]));
+    }
+
+    #[test]
+    fn test_load_raw() {
+        let objects = load_from_obj_raw(QUAD).unwrap();
+        assert_eq!(objects.len(), 1);
+        let raw = &objects[0];
+        assert_eq!(raw.name, None);
+        assert_eq!(raw.positions.len(), 8);
+        assert_eq!(raw.tex_coords.len(), 4);
+        assert_eq!(raw.normals.len(), 6);
+        assert_eq!(raw.triangles.len(), 12);
+        assert!(raw.lines.is_empty() && raw.points.is_empty());
+        assert_eq!(raw.positions[0], Position([-1.0, -1.0, 1.0]));
+        assert_eq!(raw.normals[5], Normal([-1.0, 0.0, 0.0]));
+
+        // Welding creates a vertex for each distinct corner instead.
+        let (welded, _) = load_from_obj(QUAD).unwrap().remove(0);
+        assert_eq!(welded.vertex_count(), 24);
+
+        let mut corners = raw.triangles[0].to_vec();
+        corners.sort();
+        assert_eq!(
+            corners,
+            [
+                (0, Some(0), Some(0)),
+                (1, Some(1), Some(0)),
+                (2, Some(2), Some(0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let limited = |max_vertices, max_indices| {
+            let options = ObjLoadOptions {
+                max_vertices,
+                max_indices,
+                ..ObjLoadOptions::default()
+            };
+            load_from_obj_with_options(QUAD, options).map(|meshes| meshes.len())
+        };
+        assert_eq!(limited(Some(24), Some(36)), Ok(1));
+        assert_eq!(
+            limited(None, Some(4)),
+            Err(ObjError::TooLarge {
+                object: String::new(),
+                kind: "indices",
+                count: 36,
+                limit: 4,
+            })
+        );
+        assert_eq!(
+            limited(Some(4), None),
+            Err(ObjError::TooLarge {
+                object: String::new(),
+                kind: "vertices",
+                count: 24,
+                limit: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn test_homogeneous_positions() {
+        let obj = "v 2 4 6 2 # rational\nv 1 0 0 0\nv 0 1 0\nf 1 2 3\n";
+        assert_eq!(
+            preprocess::divide_homogeneous(obj),
+            "v 1 2 3 # rational\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"
+        );
+        let (builder, _) = load_from_obj(obj.as_bytes()).unwrap().remove(0);
+        let positions = builder.read_attribute::<Position>().unwrap();
+        assert!(positions.contains(&Position([1.0, 2.0, 3.0])));
+    }
+
+    #[test]
+    fn test_obj_material_names() {
+        let obj = format!(
+            "mtllib scene.mtl\nusemtl stone\n{}usemtl wood\nf 1 2 3\n\
+             mtllib extra.mtl scene.mtl\nusemtl stone # again\n",
+            std::str::from_utf8(QUAD).unwrap()
+        );
+        assert_eq!(
+            obj_material_names(obj.as_bytes()).unwrap(),
+            ObjMaterialNames {
+                libraries: vec!["scene.mtl".to_owned(), "extra.mtl".to_owned()],
+                materials: vec!["stone".to_owned(), "wood".to_owned()],
+            }
+        );
+        assert_eq!(
+            obj_material_names(QUAD).unwrap(),
+            ObjMaterialNames::default()
+        );
+    }
+
+    #[test]
+    fn test_bom_and_crlf() {
+        let clean = std::str::from_utf8(QUAD).unwrap();
+        let windows = format!("\u{feff}{}", clean.replace('\n', "\r\n"));
+        assert_eq!(preprocess::normalize_line_endings(&windows), clean);
+        assert_eq!(
+            format!("{:?}", load_from_obj(windows.as_bytes()).unwrap()),
+            format!("{:?}", load_from_obj(QUAD).unwrap())
+        );
+
+        let invalid = "\u{feff}v 0 0 0\r\nv 1 0 0\r\nv 0 1 0\r\nf 1 2 x\r\n";
+        match load_from_obj(invalid.as_bytes()) {
+            Err(ObjError::Parse { line_number, .. }) => assert_eq!(line_number, 4),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_line_continuation() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nf 1 2 \\\n4 3 # quad\n";
+        assert_eq!(
+            preprocess::join_continued_lines(obj),
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nf 1 2  4 3 # quad\n\n"
+        );
+        let single_line = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nf 1 2 4 3\n";
+        assert_eq!(
+            format!("{:?}", load_from_obj(obj.as_bytes()).unwrap()),
+            format!("{:?}", load_from_obj(single_line.as_bytes()).unwrap())
+        );
+
+        let invalid = format!("{}f 1 \\\n2 \\\n3\nf 1 2 x\n", single_line);
+        match load_from_obj(invalid.as_bytes()) {
+            Err(ObjError::Parse { line_number, .. }) => assert_eq!(line_number, 9),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n\
+                    cstype bspline\ndeg 2\ncurv 0.0 2.0 \\\n1 2 3\nparm u 0 0 0 1 1 1\nend\n";
+
+        let report = load_from_obj_with_report(obj, ObjLoadOptions::default()).unwrap();
+        assert_eq!(report.skipped_statements, 1);
+        assert_eq!(report.meshes.len(), 1);
+        assert_eq!(
+            report.meshes[0]
+                .builder
+                .read_indices()
+                .map(|indices| indices.len()),
+            Some(3)
+        );
+
+        let options = ObjLoadOptions {
+            strict: true,
+            ..ObjLoadOptions::default()
+        };
+        assert_eq!(
+            load_from_obj_with_report(obj, options).unwrap_err(),
+            ObjError::UnsupportedFeature {
+                feature: "curv".to_owned(),
+                line: 7,
+            }
+        );
+
+        let faces = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let report = load_from_obj_with_report(faces, options).unwrap();
+        assert_eq!(report.skipped_statements, 0);
+        assert_eq!(report.meshes.len(), 1);
+    }
+
+    #[test]
+    fn test_unsupported_primitive() {
+        let cases: [(&[u8], &str, usize, usize); 3] = [
+            (b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nf 1 2\n", "f", 2, 5),
+            (b"v 0 0 0\nv 1 0 0\nl 1 \\\n\n", "l", 1, 3),
+            (b"v 0 0 0\np # 1\n", "p", 0, 2),
+        ];
+        for &(obj, keyword, vertices, line) in &cases {
+            assert_eq!(
+                load_from_obj(obj).unwrap_err(),
+                ObjError::UnsupportedPrimitive {
+                    keyword: keyword.to_owned(),
+                    vertices,
+                    line,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_normals() {
+        let cube = quad_without_normals();
+
+        let options = ObjLoadOptions {
+            generate_normals: true,
+            ..ObjLoadOptions::default()
+        };
+        let data = load_test_geometry(&cube, &options);
+
+        for (position, normal) in data.positions.iter().zip(&data.normals) {
+            assert!((dot(normal.0, normal.0) - 1.0).abs() < 1e-5);
+            // Corners of the cube are smoothed to point away from the center.
+            assert!(dot(normal.0, position.0) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_normal_weighting() {
+        // A large and a small triangle with right angles at the shared first vertex.
+        let obj = b"v 0 0 0\nv 10 0 0\nv 0 10 0\nv 0 0 1\nv 1 0 0\nf 1 2 3\nf 1 4 5\n";
+        let shared_normal = |normal_weighting| {
+            let options = ObjLoadOptions {
+                generate_normals: true,
+                normal_weighting,
+                ..ObjLoadOptions::default()
+            };
+            let (builder, _) = load_from_obj_with_options(obj, options).unwrap().remove(0);
+            let positions = builder.read_attribute::<Position>().unwrap();
+            let normals = builder.read_attribute::<Normal>().unwrap();
+            let shared = positions
+                .iter()
+                .position(|position| position.0 == [0.0; 3])
+                .unwrap();
+            normals[shared].0
+        };
+
+        // Area weighting is dominated by the large triangle facing +Z.
+        let area = shared_normal(NormalWeighting::Area);
+        assert!(area[2] > 0.99 && area[1] > 0.0);
+        // Both corners have the same angle, so angle weighting averages the normals evenly.
+        let angle = shared_normal(NormalWeighting::Angle);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((angle[1] - half).abs() < 1e-5 && (angle[2] - half).abs() < 1e-5);
+        let equal = shared_normal(NormalWeighting::Equal);
+        assert!((equal[1] - half).abs() < 1e-5 && (equal[2] - half).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_smoothing_groups() {
+        // Two faces folded along the edge from vertex 1 to vertex 2.
+        let obj = |second_group: u32| {
+            format!(
+                "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\ns 1\nf 1 2 3\ns {}\nf 2 1 4\n",
+                second_group
+            )
+        };
+        let options = ObjLoadOptions {
+            generate_normals: true,
+            ..ObjLoadOptions::default()
+        };
+
+        let meshes = load_from_obj_grouped(obj(2).as_bytes(), options).unwrap();
+        assert_eq!(meshes[0].smoothing_groups, [1, 2]);
+        let builder = &meshes[0].builder;
+        let positions = builder.read_attribute::<Position>().unwrap();
+        let normals = builder.read_attribute::<Normal>().unwrap();
+        assert_eq!(positions.len(), 6);
+        let indices = builder.read_indices().unwrap();
+        for (triangle, expected) in indices
+            .chunks_exact(3)
+            .zip(&[[0.0, 0.0, 1.0], [0.0, 1.0, 0.0]])
+        {
+            for &index in triangle {
+                assert_eq!(normals[index as usize].0, *expected);
+            }
+        }
+
+        // Faces of the same group share the vertices of the edge and their normals.
+        let meshes = load_from_obj_grouped(obj(1).as_bytes(), options).unwrap();
+        assert_eq!(meshes[0].smoothing_groups, [1, 1]);
+        let builder = &meshes[0].builder;
+        assert_eq!(builder.read_attribute::<Position>().unwrap().len(), 4);
+        let positions = builder.read_attribute::<Position>().unwrap();
+        let normals = builder.read_attribute::<Normal>().unwrap();
+        let shared = positions
+            .iter()
+            .position(|position| position.0 == [1.0, 0.0, 0.0])
+            .unwrap();
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(dot(normals[shared].0, [0.0, half, half]) > 0.999);
+    }
+
+    #[test]
+    fn test_force_smoothing_group() {
+        // Two faces folded along the edge from vertex 1 to vertex 2, in different groups.
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\ns 1\nf 1 2 3\ns off\nf 2 1 4\n";
+        let options = ObjLoadOptions {
+            generate_normals: true,
+            force_smoothing_group: Some(1),
+            ..ObjLoadOptions::default()
+        };
+        let meshes = load_from_obj_grouped(obj, options).unwrap();
+        assert_eq!(meshes[0].smoothing_groups, [1, 0]);
+        let builder = &meshes[0].builder;
+        let positions = builder.read_attribute::<Position>().unwrap();
+        let normals = builder.read_attribute::<Normal>().unwrap();
+        assert_eq!(positions.len(), 4);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        for (position, normal) in positions.iter().zip(normals.iter()) {
+            let expected = match position.0 {
+                [0.0, 1.0, 0.0] => [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0] => [0.0, 1.0, 0.0],
+                _ => [0.0, half, half],
+            };
+            assert!(dot(normal.0, expected) > 0.999);
+        }
+    }
+
+    #[test]
+    fn test_load_from_obj_grouped() {
+        let obj = b"o first\nv 0 0 0\nv 1 0 0\nv 1 1 0\ng top\nf 1 2 3\ng bottom\nf 3 2 1\n\
+                    o second\nv 0 0 0\nv 1 0 0\nv 1 1 0\ng side\nf 4 5 6\n";
+        let result = load_from_obj_grouped(obj, ObjLoadOptions::default()).unwrap();
+        let names = result
+            .iter()
+            .map(|mesh| (mesh.object_name.as_deref(), mesh.group_name.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                (Some("first"), Some("top")),
+                (Some("first"), Some("bottom")),
+                (Some("second"), Some("side")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_named_object_from_obj() {
+        let obj = b"o first\nv 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 3\n\
+                    o second\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\nf 4 5 6 7\n";
+        let meshes = load_named_object_from_obj(obj, "second").unwrap();
+        assert_eq!(meshes.len(), 1);
+        let (builder, _) = &meshes[0];
+        assert_eq!(builder.triangle_count(), 2);
+        assert_eq!(
+            builder.bounding_box(),
+            Some(([0.0, 0.0, 1.0], [1.0, 1.0, 1.0]))
+        );
+        assert_eq!(
+            format!("{:?}", meshes),
+            format!("{:?}", &load_from_obj(obj).unwrap()[1..])
+        );
+
+        assert!(load_named_object_from_obj(obj, "third").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_geometries() {
+        let obj = b"o quad\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                    g a\nusemtl red\nf 1 2 3\ng b\nusemtl blue\nf 1 3 4\n";
+        let options = ObjLoadOptions {
+            merge_geometries: true,
+            ..ObjLoadOptions::default()
+        };
+        assert_eq!(
+            load_from_obj_grouped(obj, ObjLoadOptions::default())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let result = load_from_obj_grouped(obj, options).unwrap();
+        assert_eq!(result.len(), 1);
+        let positions = result[0].builder.read_attribute::<Position>().unwrap();
+        assert_eq!(positions.len(), 4);
+        assert_eq!(result[0].object_name.as_deref(), Some("quad"));
+        assert_eq!(result[0].group_name, None);
+        assert_eq!(result[0].material_name, None);
+
+        let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl red\ng a\nf 1 2 3\ng b\nf 3 2 1\n";
+        let result = load_from_obj_grouped(obj, options).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].material_name.as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn test_obj_stats() {
+        let stats = obj_stats(QUAD).unwrap();
+        let meshes = load_from_obj(QUAD).unwrap();
+        assert_eq!(stats.len(), meshes.len());
+        assert_eq!(stats[0].triangles, 12);
+        assert_eq!(stats[0].vertices, 36);
+        assert_eq!(
+            Some(stats[0].unique_vertices),
+            meshes[0]
+                .0
+                .read_attribute::<Position>()
+                .map(|positions| positions.len())
+        );
+    }
+
+    #[test]
+    fn test_recenter() {
+        let obj = b"v 1000000 1000000 1000000\nv 1000002 1000000 1000000\n\
+                    v 1000000 1000002.5 1000001\nf 1 2 3\n";
+        let options = ObjLoadOptions {
+            recenter: true,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_grouped(obj, options).unwrap();
+        assert_eq!(result[0].offset, [1000001.0, 1000001.25, 1000000.5]);
+        assert_eq!(
+            result[0].builder.bounding_box(),
+            Some(([-1.0, -1.25, -0.5], [1.0, 1.25, 0.5]))
+        );
+
+        let result = load_from_obj_grouped(obj, ObjLoadOptions::default()).unwrap();
+        assert_eq!(result[0].offset, [0.0; 3]);
+    }
+
+    #[test]
+    fn test_ear_clip_polygon_mode() {
+        let obj = b"v 0 0 0\nv 2 0 0\nv 2 1 0\nv 1 1 0\nv 1 2 0\nv 0 2 0\nf 1 2 3 4 5 6\n";
+        let options = ObjLoadOptions {
+            polygon_mode: PolygonMode::EarClip,
+            ..ObjLoadOptions::default()
+        };
+        assert_eq!(parse_obj(obj, false).unwrap().face_sizes, [6]);
+        let data = load_test_geometry(std::str::from_utf8(obj).unwrap(), &options);
+        assert_eq!(data.indices.len(), 12);
+
+        // Every triangle must be inside the L and wound like the face.
+        for triangle in data.indices.chunks_exact(3) {
+            let p = triangle
+                .iter()
+                .map(|&i| data.positions[i as usize].0)
+                .collect::<Vec<_>>();
+            let normal = cross(sub(p[1], p[0]), sub(p[2], p[0]));
+            assert!(normal[2] > 0.0);
+            let centroid = scale(add(add(p[0], p[1]), p[2]), 1.0 / 3.0);
+            assert!(centroid[0] < 1.0 || centroid[1] < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_drop_degenerate() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nv 2 0 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\n\
+                    f 1/1/1 1/1/1 2/2/1\nf 1/1/1 2/2/1 4/2/1\nf 1/1/1 2/2/1 3/3/1\n";
+        let options = ObjLoadOptions {
+            drop_degenerate: true,
+            ..ObjLoadOptions::default()
+        };
+        let obj = std::str::from_utf8(obj).unwrap();
+        let data = load_test_geometry(obj, &options);
+        assert_eq!(data.indices.len(), 3);
+        assert_eq!(data.positions.len(), 3);
+
+        let data = load_test_geometry(obj, &ObjLoadOptions::default());
+        assert_eq!(data.indices.len(), 9);
+    }
+
+    #[test]
+    fn test_unwelded() {
+        let options = ObjLoadOptions {
+            weld: false,
+            ..ObjLoadOptions::default()
+        };
+        let data = load_test_geometry(std::str::from_utf8(QUAD).unwrap(), &options);
+        assert_eq!(data.positions.len(), 36);
+        assert_eq!(data.indices, (0..36).collect::<Vec<u32>>());
+        // The first two triangles are the +z side.
+        assert!(data.normals[..6]
+            .iter()
+            .all(|normal| normal.0 == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_flip_tex_coords() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.2 0.3\nf 1/1 2/1 3/1\n";
+        let load = |flip_u, flip_v| {
+            let options = ObjLoadOptions {
+                flip_u,
+                flip_v,
+                ..ObjLoadOptions::default()
+            };
+            let data = load_test_geometry(obj, &options);
+            data.tex_coords[0].0
+        };
+        assert_eq!(load(false, false), [0.2, 0.3]);
+        assert_eq!(load(false, true), [0.2, 0.7]);
+        assert_eq!(load(true, false), [0.8, 0.3]);
+    }
+
+    #[test]
+    fn test_flip_texcoord_v() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            vt 0 0\nvt 1 0.25\nvt 1 1\nvt 0 0.75\nf 1/1 2/2 3/3 4/4\n";
+        let load = |flip_v| {
+            let options = ObjLoadOptions {
+                flip_v,
+                ..ObjLoadOptions::default()
+            };
+            let (builder, _) = load_from_obj_with_options(obj, options).unwrap().remove(0);
+            builder
+        };
+        let mut builder = load(false);
+        builder.flip_texcoord_v();
+        assert_eq!(
+            builder.read_attribute::<TexCoord>(),
+            load(true).read_attribute::<TexCoord>()
+        );
+        assert_eq!(
+            builder.read_attribute::<Position>(),
+            load(false).read_attribute::<Position>()
+        );
+        assert_eq!(builder.read_indices(), load(false).read_indices());
+
+        let mut positions_only = MeshBuilder::new().with_vertices(vec![Position([0.0; 3])]);
+        positions_only.flip_texcoord_v();
+        assert_eq!(positions_only.read_attribute::<TexCoord>(), None);
+    }
+
+    #[test]
+    fn test_flip_winding() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let load = |flip_winding| {
+            let options = ObjLoadOptions {
+                flip_winding,
+                generate_normals: true,
+                ..ObjLoadOptions::default()
+            };
+            let meshes = load_from_obj_with_options(obj, options).unwrap();
+            let builder = &meshes[0].0;
+            (
+                builder.read_indices().unwrap(),
+                builder.read_attribute::<Normal>().unwrap()[0],
+            )
+        };
+        let (indices, normal) = load(false);
+        let (flipped, flipped_normal) = load(true);
+        assert_eq!(flipped, [indices[0], indices[2], indices[1]]);
+        assert_eq!(normal, Normal([0.0, 0.0, 1.0]));
+        assert_eq!(flipped_normal, Normal([0.0, 0.0, -1.0]));
+    }
+
+    #[test]
+    fn test_tex_coords_3d() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0.5 0.5 0.25\nvt 1 1\nf 1/1 2/1 3/2\n";
+        let options = ObjLoadOptions {
+            tex_coords_3d: true,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_with_options(obj, options).unwrap();
+        let mut tex_coords = result[0].0.read_attribute::<TexCoord3>().unwrap();
+        tex_coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        tex_coords.dedup();
+        assert_eq!(
+            tex_coords,
+            [TexCoord3([0.5, 0.5, 0.25]), TexCoord3([1.0, 1.0, 0.0])]
+        );
+        assert!(result[0].0.read_attribute::<TexCoord>().is_some());
+        let layout = result[0].0.vertex_layout();
+        let names = layout
+            .attributes
+            .iter()
+            .map(|attribute| attribute.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"tex_coord") && names.contains(&"tex_coord3"));
+
+        let result = load_from_obj(obj).unwrap();
+        assert!(result[0].0.read_attribute::<TexCoord3>().is_none());
+    }
+
+    #[test]
+    fn test_transform() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 0.8 -0.6\nvn 0 0.6 0.8\nf 1//1 2//1 3//1\n";
+        let load = |transform| {
+            let options = ObjLoadOptions {
+                transform: Some(transform),
+                ..ObjLoadOptions::default()
+            };
+            load_from_obj_with_options(obj, options)
+                .unwrap()
+                .remove(0)
+                .0
+        };
+        let (original, _) = load_from_obj(obj).unwrap().remove(0);
+        let original_positions = original.read_attribute::<Position>().unwrap();
+        let original_normals = original.read_attribute::<Normal>().unwrap();
+
+        let scaled = load([
+            [2.0, 0.0, 0.0, 0.0],
+            [0.0, 2.0, 0.0, 0.0],
+            [0.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        for (position, original) in scaled
+            .read_attribute::<Position>()
+            .unwrap()
+            .iter()
+            .zip(&original_positions)
+        {
+            assert_eq!(position.0, scale(original.0, 2.0));
+        }
+        assert_eq!(scaled.read_attribute::<Normal>().unwrap(), original_normals);
+
+        let rotated = load(z_up_to_y_up());
+        let positions = rotated.read_attribute::<Position>().unwrap();
+        assert!(positions.contains(&Position([0.0, -0.6, -0.8])));
+        for normal in rotated.read_attribute::<Normal>().unwrap() {
+            let [x, y, z] = normal.0;
+            assert!(x.abs() < 1e-6 && (y - 0.8).abs() < 1e-6 && (z + 0.6).abs() < 1e-6);
+        }
+
+        // Non-uniform scaling keeps normals unit length and perpendicular to the face.
+        let stretched = load([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 3.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let positions = stretched.read_attribute::<Position>().unwrap();
+        let normal = stretched.read_attribute::<Normal>().unwrap()[0].0;
+        assert!((dot(normal, normal) - 1.0).abs() < 1e-6);
+        for p in &positions[1..] {
+            assert!(dot(normal, sub(p.0, positions[0].0)).abs() < 1e-6);
+        }
+
+        // Mirroring keeps the winding consistent with the transformed normals.
+        let mirrored = load([
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let positions = mirrored.read_attribute::<Position>().unwrap();
+        let normal = mirrored.read_attribute::<Normal>().unwrap()[0].0;
+        let indices = mirrored.read_indices().unwrap();
+        let [a, b, c] = [
+            positions[indices[0] as usize].0,
+            positions[indices[1] as usize].0,
+            positions[indices[2] as usize].0,
+        ];
+        assert!(dot(cross(sub(b, a), sub(c, a)), normal) > 0.0);
+    }
+
+    #[test]
+    fn test_handedness() {
+        let obj = b"v 0 0 1\nv 1 0 2\nv 0 1 3\nvn 0.6 0 0.8\nf 1//1 2//1 3//1\n";
+        let (right, _) = load_from_obj(obj).unwrap().remove(0);
+        let options = ObjLoadOptions {
+            handedness: Handedness::LeftHanded,
+            ..ObjLoadOptions::default()
+        };
+        let (left, _) = load_from_obj_with_options(obj, options).unwrap().remove(0);
+
+        let negate_z = |[x, y, z]: [f32; 3]| [x, y, -z];
+        let right_positions = right.read_attribute::<Position>().unwrap();
+        let left_positions = left.read_attribute::<Position>().unwrap();
+        assert_eq!(left_positions.len(), right_positions.len());
+        for (left, right) in left_positions.iter().zip(&right_positions) {
+            assert_eq!(left.0, negate_z(right.0));
+        }
+        for normal in left.read_attribute::<Normal>().unwrap() {
+            assert_eq!(normal.0, [0.6, 0.0, -0.8]);
+        }
+
+        let right_indices = right.read_indices().unwrap();
+        let left_indices = left.read_indices().unwrap();
+        assert_eq!(
+            left_indices,
+            [right_indices[0], right_indices[2], right_indices[1]]
+        );
+    }
+
+    #[test]
+    fn test_path_resolver() {
+        let permissive = PathResolver::new("assets/models");
+        let sandboxed = PathResolver::sandboxed("assets/models");
+        for resolver in &[&permissive, &sandboxed] {
+            assert_eq!(
+                resolver.resolve("./materials\\wood.mtl").unwrap(),
+                Path::new("assets/models/materials/wood.mtl")
+            );
+            assert_eq!(
+                resolver
+                    .resolve_from("materials/wood.mtl", "../textures/./wood.png")
+                    .unwrap(),
+                Path::new("assets/models/textures/wood.png")
+            );
+        }
+        assert_eq!(
+            sandboxed.resolve("/etc/passwd"),
+            Err(ObjError::PathOutsideBase {
+                path: "/etc/passwd".to_owned()
+            })
+        );
+        assert_eq!(
+            permissive.resolve("/etc/passwd").unwrap(),
+            Path::new("/etc/passwd")
+        );
+
+        let mtl = b"newmtl evil\nmap_Kd ../evil.png\nmap_Ks spec.png\n";
+        let material = mtl::parse("model.mtl", mtl).unwrap().remove(0);
+        let mut textures = material.textures.clone();
+        assert_eq!(
+            sandboxed.resolve_textures("model.mtl", &mut textures),
+            Err(ObjError::PathOutsideBase {
+                path: "../evil.png".to_owned()
+            })
+        );
+        let mut textures = material.textures;
+        permissive
+            .resolve_textures("model.mtl", &mut textures)
+            .unwrap();
+        assert_eq!(
+            textures.diffuse.as_ref().map(Path::new),
+            Some(Path::new("assets/evil.png"))
+        );
+        assert_eq!(
+            textures.specular.as_ref().map(Path::new),
+            Some(Path::new("assets/models/spec.png"))
+        );
+    }
+
+    #[test]
+    fn test_load_from_obj_streaming() {
+        use std::fmt::Write;
+
+        #[derive(Default)]
+        struct Collector {
+            meshes: Vec<(Option<String>, usize, usize)>,
+            positions: Vec<Vec<Position>>,
+            normals: Vec<Option<Vec<Normal>>>,
+            tex_coords: Vec<Option<Vec<TexCoord>>>,
+            tangents: Vec<Option<Vec<Tangent>>>,
+            indices: Vec<Vec<u32>>,
+        }
+        fn append<T: Copy>(all: &mut [Option<Vec<T>>], chunk: Option<&[T]>) {
+            if let Some(chunk) = chunk {
+                let all = all.last_mut().unwrap().get_or_insert_with(Vec::new);
+                all.extend_from_slice(chunk);
+            }
+        }
+        impl ObjVisitor for Collector {
+            fn begin_mesh(&mut self, mesh: &ObjMeshInfo<'_>) {
+                self.meshes.push((
+                    mesh.material_name.map(str::to_owned),
+                    mesh.vertex_count,
+                    mesh.index_count,
+                ));
+                self.positions.push(Vec::new());
+                self.normals.push(None);
+                self.tex_coords.push(None);
+                self.tangents.push(None);
+                self.indices.push(Vec::new());
+            }
+            fn vertices(&mut self, vertices: ObjVertices<'_>) {
+                assert!(vertices.positions.len() <= STREAM_CHUNK);
+                let positions = self.positions.last_mut().unwrap();
+                positions.extend_from_slice(vertices.positions);
+                append(&mut self.normals, vertices.normals);
+                append(&mut self.tex_coords, vertices.tex_coords);
+                append(&mut self.tangents, vertices.tangents);
+            }
+            fn indices(&mut self, indices: &[u32]) {
+                assert!(indices.len() <= STREAM_CHUNK);
+                self.indices.last_mut().unwrap().extend_from_slice(indices);
+            }
+        }
+
+        // A grid with more vertices and indices than fit in one chunk, followed by
+        // a triangle of another material and a line.
+        let mut obj = String::new();
+        let size = 70;
+        for y in 0..size {
+            for x in 0..size {
+                writeln!(obj, "v {} {} 0\nvt {} {}", x, y, x, y).unwrap();
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let a = y * size + x + 1;
+                let (b, c, d) = (a + 1, a + size, a + size + 1);
+                writeln!(obj, "f {0}/{0} {1}/{1} {3}/{3} {2}/{2}", a, b, c, d).unwrap();
+            }
+        }
+        obj.push_str("usemtl other\nf 1 2 3\nl 1 4\n");
+
+        let flipped = ObjLoadOptions {
+            flip_v: true,
+            attributes: AttributeMask::POSITION | AttributeMask::TEX_COORD,
+            ..ObjLoadOptions::default()
+        };
+        for options in &[ObjLoadOptions::default(), flipped] {
+            let mut collector = Collector::default();
+            load_from_obj_streaming(obj.as_bytes(), options, &mut collector).unwrap();
+            let batch = load_from_obj_with_options(obj.as_bytes(), *options).unwrap();
+            assert_eq!(collector.meshes.len(), batch.len());
+            assert!(collector.meshes[0].1 > STREAM_CHUNK);
+            assert_eq!(collector.tangents[2], None);
+            for (i, (builder, material_name)) in batch.iter().enumerate() {
+                let (streamed_material, vertex_count, index_count) = &collector.meshes[i];
+                assert_eq!(streamed_material, material_name);
+                assert_eq!(*vertex_count, builder.vertex_count());
+                assert_eq!(*index_count, collector.indices[i].len());
+                assert_eq!(
+                    builder.read_attribute::<Position>().as_ref(),
+                    Some(&collector.positions[i])
+                );
+                assert_eq!(builder.read_attribute::<Normal>(), collector.normals[i]);
+                assert_eq!(
+                    builder.read_attribute::<TexCoord>(),
+                    collector.tex_coords[i]
+                );
+                assert_eq!(builder.read_attribute::<Tangent>(), collector.tangents[i]);
+                assert_eq!(builder.read_indices().unwrap(), collector.indices[i]);
+            }
+        }
+        assert_eq!(
+            load_from_obj_with_options(obj.as_bytes(), flipped).unwrap()[0]
+                .0
+                .read_attribute::<Normal>(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_vertex_colors() {
+        let obj = b"o colored\nv 0 0 0 1 0 0\nv 1 0 0 # no color\nv 0 1 0 0 0.5 1\nf 1 2 3\n\
+            o plain\nv 0 0 1\nv 1 0 1\nv 0 1 1\nf 4 5 6\n";
+        let result = load_from_obj(obj).unwrap();
+        assert_eq!(result.len(), 2);
+        let (colored, _) = &result[0];
+        let positions = colored.read_attribute::<Position>().unwrap();
+        let colors = colored.read_attribute::<Color>().unwrap();
+        let color_at =
+            |position: [f32; 3]| colors[positions.iter().position(|p| p.0 == position).unwrap()].0;
+        assert_eq!(color_at([0.0, 0.0, 0.0]), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(color_at([1.0, 0.0, 0.0]), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(color_at([0.0, 1.0, 0.0]), [0.0, 0.5, 1.0, 1.0]);
+        assert_eq!(result[1].0.read_attribute::<Color>(), None);
+
+        let options = ObjLoadOptions {
+            attributes: AttributeMask::ALL & !AttributeMask::COLOR,
+            ..ObjLoadOptions::default()
+        };
+        let result = load_from_obj_with_options(obj, options).unwrap();
+        assert_eq!(result[0].0.read_attribute::<Color>(), None);
+    }
+
+    #[test]
+    fn test_load_scene_from_objs() {
+        let first = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl stone\nf 1 2 3\n";
+        let second = b"v 0 0 1\nv 1 0 1\nv 0 1 1\nusemtl wood\nf 1 2 3\n";
+        let scene = load_scene_from_objs(&[first, second]).unwrap();
+        assert_eq!(scene.len(), 2);
+        assert_eq!(scene[0].1.as_deref(), Some("stone"));
+        assert_eq!(scene[1].1.as_deref(), Some("wood"));
+
+        // Meshes of the same material in different files are merged.
+        let scene = load_scene_from_objs(&[first, second, first]).unwrap();
+        assert_eq!(scene.len(), 2);
+        assert_eq!(scene[0].0.vertex_count(), 6);
+        let indices = scene[0].0.read_indices().unwrap();
+        assert_eq!(indices.len(), 6);
+        assert!(indices[..3]
+            .iter()
+            .zip(&indices[3..])
+            .all(|(a, b)| a + 3 == *b));
+        assert_eq!(scene[1].0.vertex_count(), 3);
+    }
+
+    #[test]
+    fn test_weld_by_index() {
+        let mut obj = String::new();
+        for i in 0..4 {
+            obj.push_str(&format!("v {} {} 0\nvt {} 0\nvn 0 0 1\n", i % 2, i / 2, i));
+        }
+        obj.push_str("f 3/3/3 1/1/1 2/2/2\nf 2/2/2 4/4/4 3/3/3\n");
+        let parsed = parse_obj(obj.as_bytes(), false).unwrap();
+        let object = &parsed.obj_set.objects[0];
+        let corners =
+            primitive_corners(object, &object.geometry[0].shapes, Primitive::TriangleList)
+                .unwrap()
+                .into_iter()
+                .map(|index| (index, 0))
+                .collect::<Vec<_>>();
+        assert_eq!(
+            weld_by_index(&corners, |(index, _)| index.0),
+            Some(weld(&corners))
+        );
+
+        // A position used with two texture coordinates needs the general path.
+        let mut split = corners.clone();
+        split[0].0 .1 = Some(0);
+        assert_eq!(weld_by_index(&split, |(index, _)| index.0), None);
+        let mut grouped = corners;
+        grouped[0].1 = 1;
+        assert_eq!(weld_by_index(&grouped, |(index, _)| index.0), None);
+
+        let (builder, _) = load_from_obj(obj.as_bytes()).unwrap().remove(0);
+        assert_eq!(builder.vertex_count(), 4);
+        assert_eq!(builder.read_indices().unwrap().len(), 6);
+        let options = ObjLoadOptions {
+            weld_by_index: false,
+            ..ObjLoadOptions::default()
+        };
+        let (general, _) = load_from_obj_with_options(obj.as_bytes(), options)
+            .unwrap()
+            .remove(0);
+        assert_eq!(general.content_hash(), builder.content_hash());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let result = load_from_obj(QUAD).unwrap();
+        assert_eq!(
+            result[0].0.bounding_box(),
+            Some(([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]))
+        );
+        assert_eq!(MeshBuilder::new().bounding_box(), None);
     }
 }
@@ -0,0 +1,222 @@
+//! Loading material definitions from mtl format.
+
+use super::ObjError;
+
+/// Material defined in a material library referenced by obj file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Material {
+    /// Name of the material as used by `usemtl` statements.
+    pub name: String,
+    /// Ambient color (`Ka`).
+    pub ambient: Option<[f32; 3]>,
+    /// Diffuse color (`Kd`).
+    pub diffuse: Option<[f32; 3]>,
+    /// Specular color (`Ks`).
+    pub specular: Option<[f32; 3]>,
+    /// Specular exponent (`Ns`), usually between 0 and 1000.
+    pub specular_exponent: Option<f32>,
+    /// Index of refraction (`Ni`).
+    pub optical_density: Option<f32>,
+    /// Opacity from 0, fully transparent, to 1, fully opaque.
+    ///
+    /// Read from `d`, or from `Tr` as `1 - Tr`. The last of them wins.
+    pub dissolve: Option<f32>,
+    /// Illumination model (`illum`), such as 2 for diffuse and specular highlights.
+    pub illumination_model: Option<u32>,
+    /// Paths to the textures of the material.
+    pub textures: MaterialTextures,
+}
+
+/// Paths to the textures of a material, as written in the material library.
+///
+/// Paths are usually relative to the directory of the library.
+/// Options preceding them, such as `-s 2 2 2`, are removed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialTextures {
+    /// Ambient texture (`map_Ka`).
+    pub ambient: Option<String>,
+    /// Diffuse texture (`map_Kd`).
+    pub diffuse: Option<String>,
+    /// Specular texture (`map_Ks`).
+    pub specular: Option<String>,
+    /// Bump map (`map_Bump` or `bump`).
+    pub bump: Option<String>,
+    /// Dissolve texture (`map_d`).
+    pub dissolve: Option<String>,
+    /// Normal map (`norm`).
+    pub normal: Option<String>,
+}
+
+/// Texture options and the number of their arguments.
+/// Options of coordinate transforms take one to three numbers.
+const MAP_OPTIONS: [(&str, usize); 13] = [
+    ("-blendu", 1),
+    ("-blendv", 1),
+    ("-bm", 1),
+    ("-boost", 1),
+    ("-cc", 1),
+    ("-clamp", 1),
+    ("-imfchan", 1),
+    ("-mm", 2),
+    ("-o", 3),
+    ("-s", 3),
+    ("-t", 3),
+    ("-texres", 1),
+    ("-type", 1),
+];
+
+/// Parse all materials from mtl file contents.
+/// `library` is the name of the file used for error reporting.
+pub(super) fn parse(library: &str, bytes: &[u8]) -> Result<Vec<Material>, ObjError> {
+    let string = std::str::from_utf8(bytes)?;
+    let string = string.strip_prefix('\u{feff}').unwrap_or(string);
+    let mut materials = Vec::new();
+
+    for (line_index, line) in string.lines().enumerate() {
+        let error = |message: &str| ObjError::Mtl {
+            library: library.to_owned(),
+            line_number: line_index + 1,
+            message: message.to_owned(),
+        };
+
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = line[keyword.len()..].trim();
+            if name.is_empty() {
+                return Err(error("Expected material name"));
+            }
+            materials.push(Material {
+                name: name.to_owned(),
+                ..Material::default()
+            });
+            continue;
+        }
+
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(error("Expected `newmtl` before material statements")),
+        };
+
+        let map = match keyword {
+            "Ka" => {
+                material.ambient = parse_color(words).map_err(error)?;
+                continue;
+            }
+            "Kd" => {
+                material.diffuse = parse_color(words).map_err(error)?;
+                continue;
+            }
+            "Ks" => {
+                material.specular = parse_color(words).map_err(error)?;
+                continue;
+            }
+            "Ns" => {
+                material.specular_exponent = Some(parse_scalar(words).map_err(error)?);
+                continue;
+            }
+            "Ni" => {
+                material.optical_density = Some(parse_scalar(words).map_err(error)?);
+                continue;
+            }
+            "d" => {
+                // `-halo` makes dissolve depend on the viewing angle, which isn't supported.
+                let words = words.skip_while(|&word| word == "-halo");
+                material.dissolve = Some(parse_scalar(words).map_err(error)?);
+                continue;
+            }
+            "Tr" => {
+                material.dissolve = Some(1.0 - parse_scalar(words).map_err(error)?);
+                continue;
+            }
+            "illum" => {
+                let model = words.next().and_then(|word| word.parse().ok());
+                material.illumination_model =
+                    Some(model.ok_or_else(|| error("Expected illumination model"))?);
+                continue;
+            }
+            "map_Ka" => &mut material.textures.ambient,
+            "map_Kd" => &mut material.textures.diffuse,
+            "map_Ks" => &mut material.textures.specular,
+            "map_Bump" | "map_bump" | "bump" => &mut material.textures.bump,
+            "map_d" => &mut material.textures.dissolve,
+            "norm" => &mut material.textures.normal,
+            _ => continue,
+        };
+        *map = Some(parse_map(&line[keyword.len()..]).map_err(error)?);
+    }
+
+    Ok(materials)
+}
+
+/// Parse `r [g b]` color. Missing `g` and `b` components are equal to `r`.
+/// Colors specified with `spectral` or `xyz` are ignored.
+fn parse_color<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<Option<[f32; 3]>, &'static str> {
+    let r = match words.next() {
+        Some("spectral") | Some("xyz") => return Ok(None),
+        Some(r) => r.parse::<f32>().map_err(|_| "Expected color component")?,
+        None => return Err("Expected color component"),
+    };
+    let mut rest = words.map(|c| c.parse::<f32>().map_err(|_| "Expected color component"));
+    match (rest.next().transpose()?, rest.next().transpose()?) {
+        (Some(g), Some(b)) => Ok(Some([r, g, b])),
+        (None, None) => Ok(Some([r, r, r])),
+        _ => Err("Expected three color components"),
+    }
+}
+
+/// Parse a single number.
+fn parse_scalar<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<f32, &'static str> {
+    words
+        .next()
+        .and_then(|word| word.parse::<f32>().ok())
+        .ok_or("Expected number")
+}
+
+/// Parse arguments of texture map statement returning the path to the texture.
+///
+/// Options are skipped together with their arguments.
+/// Unknown options are skipped alone. The path is the rest of the line and may contain spaces.
+fn parse_map(mut arguments: &str) -> Result<String, &'static str> {
+    loop {
+        arguments = arguments.trim_start();
+        let mut words = arguments.split_whitespace();
+        let option = match words.next() {
+            Some(option) if option.starts_with('-') => option,
+            _ => break,
+        };
+        let mut skipped = option.len();
+        match MAP_OPTIONS.iter().find(|&&(name, _)| name == option) {
+            Some(&(name, count)) => {
+                // Coordinate transforms and `-mm` may omit trailing numbers.
+                let optional = matches!(name, "-o" | "-s" | "-t" | "-mm");
+                for _ in 0..count {
+                    let word = match words.next() {
+                        Some(word) => word,
+                        None => return Err("Expected texture option argument"),
+                    };
+                    if optional && skipped > option.len() && word.parse::<f32>().is_err() {
+                        break;
+                    }
+                    skipped = word.as_ptr() as usize - arguments.as_ptr() as usize + word.len();
+                }
+            }
+            None => log::trace!("Skipping unknown texture option '{}'", option),
+        }
+        arguments = &arguments[skipped..];
+    }
+
+    let path = arguments.trim();
+    if path.is_empty() {
+        Err("Expected texture path")
+    } else {
+        Ok(path.to_owned())
+    }
+}
@@ -0,0 +1,142 @@
+//! Resolution of paths referenced by obj and mtl files.
+
+use {
+    super::{MaterialTextures, ObjError},
+    std::path::{Component, Path, PathBuf},
+};
+
+/// Resolves paths of material libraries and textures against a base directory,
+/// usually the directory of the obj file.
+///
+/// Both `/` and `\` separate path components, and `.` and `..` components are
+/// removed without accessing the file system. A sandboxed resolver fails with
+/// `ObjError::PathOutsideBase` on absolute paths and paths leaving the base directory,
+/// so that user-uploaded assets can't reference other files.
+/// Symbolic links within the base directory are not detected.
+#[derive(Clone, Debug)]
+pub struct PathResolver {
+    base: PathBuf,
+    sandboxed: bool,
+}
+
+impl PathResolver {
+    /// Resolver accepting any path, with relative paths resolved against `base`.
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        PathResolver {
+            base: base.into(),
+            sandboxed: false,
+        }
+    }
+
+    /// Resolver rejecting paths outside of `base`.
+    pub fn sandboxed(base: impl Into<PathBuf>) -> Self {
+        PathResolver {
+            base: base.into(),
+            sandboxed: true,
+        }
+    }
+
+    /// The base directory.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Whether paths outside of the base directory are rejected.
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    /// Resolve `path`, such as the name of a material library referenced by obj.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, ObjError> {
+        if is_absolute(path) {
+            return if self.sandboxed {
+                Err(outside(path))
+            } else {
+                Ok(PathBuf::from(path))
+            };
+        }
+
+        let mut components = Vec::new();
+        let mut parents = 0;
+        for component in path
+            .split(&['/', '\\'][..])
+            .filter(|&component| !component.is_empty() && component != ".")
+        {
+            if component == ".." {
+                if components.pop().is_none() {
+                    if self.sandboxed {
+                        return Err(outside(path));
+                    }
+                    parents += 1;
+                }
+            } else {
+                components.push(component);
+            }
+        }
+
+        let mut resolved = self.base.clone();
+        for _ in 0..parents {
+            match resolved.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    resolved.pop();
+                }
+                _ => resolved.push(".."),
+            }
+        }
+        resolved.extend(components);
+        Ok(resolved)
+    }
+
+    /// Resolve `path` written in the file `file`, relative to the directory of that file.
+    ///
+    /// `file` is itself relative to the base directory, as are material libraries.
+    pub fn resolve_from(&self, file: &str, path: &str) -> Result<PathBuf, ObjError> {
+        if is_absolute(path) {
+            self.resolve(path)
+        } else {
+            // The `..` removes the name of the file.
+            self.resolve(&format!("{}/../{}", file, path))
+                .map_err(|_| outside(path))
+        }
+    }
+
+    /// Replace paths of `textures` from the material library `library`
+    /// with their resolved paths.
+    pub fn resolve_textures(
+        &self,
+        library: &str,
+        textures: &mut MaterialTextures,
+    ) -> Result<(), ObjError> {
+        let MaterialTextures {
+            ambient,
+            diffuse,
+            specular,
+            bump,
+            dissolve,
+            normal,
+        } = textures;
+        for texture in [ambient, diffuse, specular, bump, dissolve, normal].iter_mut() {
+            if let Some(path) = texture.as_mut() {
+                *path = self
+                    .resolve_from(library, path)?
+                    .to_string_lossy()
+                    .into_owned();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn outside(path: &str) -> ObjError {
+    ObjError::PathOutsideBase {
+        path: path.to_owned(),
+    }
+}
+
+/// Whether `path` starts at a root or a drive, on any platform.
+fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with('\\')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
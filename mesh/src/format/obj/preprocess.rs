@@ -0,0 +1,333 @@
+//! Rewriting of obj text into the subset understood by the parser.
+
+use std::borrow::Cow;
+
+/// Remove a leading byte order mark and replace CRLF line endings with LF.
+///
+/// Line numbers are preserved.
+pub(super) fn normalize_line_endings(text: &str) -> Cow<'_, str> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Join statements continued with a trailing `\` into a single line.
+///
+/// Continuation lines are left empty, so line numbers are preserved
+/// and errors in a continued statement are reported at its first line.
+pub(super) fn join_continued_lines(text: &str) -> Cow<'_, str> {
+    let mut lines: Vec<Cow<'_, str>> = Vec::new();
+    let mut continued = false;
+    let mut joined = 0;
+    let mut changed = false;
+
+    for line in text.split('\n') {
+        let statement = line.split('#').next().unwrap_or("").trim_end();
+        let continues = statement.ends_with('\\');
+        let part = if continues {
+            &statement[..statement.len() - 1]
+        } else {
+            line
+        };
+        match lines.last_mut() {
+            Some(last) if continued => {
+                let last = last.to_mut();
+                last.push(' ');
+                last.push_str(part);
+                joined += 1;
+            }
+            _ => lines.push(Cow::Borrowed(part)),
+        }
+        if !continues {
+            lines.extend((0..joined).map(|_| Cow::Borrowed("")));
+            joined = 0;
+        }
+        continued = continues;
+        changed |= continues;
+    }
+    lines.extend((0..joined).map(|_| Cow::Borrowed("")));
+
+    if !changed {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(lines.join("\n"))
+    }
+}
+
+/// Replace negative (relative) indices in `f`, `l` and `p` statements with absolute ones.
+///
+/// `-1` refers to the last vertex data of its kind defined before the statement.
+/// Indices reaching before the first element are left as is for the parser to report.
+/// Line numbers are preserved.
+pub(super) fn resolve_relative_indices(text: &str) -> Cow<'_, str> {
+    // Number of `v`, `vt` and `vn` statements seen so far.
+    let mut counts = [0usize; 3];
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    for line in text.split('\n') {
+        let statement = line.split('#').next().unwrap_or("");
+        let mut words = statement.split_whitespace();
+        match words.next() {
+            Some("v") => counts[0] += 1,
+            Some("vt") => counts[1] += 1,
+            Some("vn") => counts[2] += 1,
+            Some(keyword @ "f") | Some(keyword @ "l") | Some(keyword @ "p")
+                if statement.contains('-') =>
+            {
+                let mut rewritten = keyword.to_owned();
+                for word in words {
+                    rewritten.push(' ');
+                    for (kind, index) in word.split('/').enumerate() {
+                        if kind > 0 {
+                            rewritten.push('/');
+                        }
+                        match resolve(index, counts.get(kind).copied().unwrap_or(0)) {
+                            Some(absolute) => rewritten.push_str(&absolute.to_string()),
+                            None => rewritten.push_str(index),
+                        }
+                    }
+                }
+                rewritten.push_str(&line[statement.trim_end().len()..]);
+
+                output.push_str(&text[copied..offset]);
+                output.push_str(&rewritten);
+                copied = offset + line.len();
+            }
+            _ => {}
+        }
+        offset += line.len() + 1;
+    }
+
+    if copied == 0 {
+        Cow::Borrowed(text)
+    } else {
+        output.push_str(&text[copied..]);
+        Cow::Owned(output)
+    }
+}
+
+/// Divide coordinates of `v` statements with a homogeneous `w` coordinate by `w`.
+///
+/// The parser only accepts three coordinates, so `w` is removed. Vertices with a zero `w`
+/// are kept as they are, and values that aren't numbers are left for the parser to report.
+/// Line numbers are preserved.
+pub(super) fn divide_homogeneous(text: &str) -> Cow<'_, str> {
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    for line in text.split('\n') {
+        let statement = line.split('#').next().unwrap_or("");
+        let words = statement.split_whitespace().collect::<Vec<_>>();
+        if let ["v", x, y, z, w] = words[..] {
+            let coordinates = [x, y, z, w]
+                .iter()
+                .map(|word| word.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            if let Ok(coordinates) = coordinates {
+                let w = if coordinates[3] == 0.0 {
+                    1.0
+                } else {
+                    coordinates[3]
+                };
+                let rewritten = format!(
+                    "v {} {} {}{}",
+                    coordinates[0] / w,
+                    coordinates[1] / w,
+                    coordinates[2] / w,
+                    &line[statement.trim_end().len()..]
+                );
+
+                output.push_str(&text[copied..offset]);
+                output.push_str(&rewritten);
+                copied = offset + line.len();
+            }
+        }
+        offset += line.len() + 1;
+    }
+
+    if copied == 0 {
+        Cow::Borrowed(text)
+    } else {
+        output.push_str(&text[copied..]);
+        Cow::Owned(output)
+    }
+}
+
+/// Remove vertex colors appended to `v` statements as `v x y z r g b`,
+/// an extension written by several tools.
+///
+/// Returns the remaining text together with the color of each `v` statement in order,
+/// `None` for vertices without color, with alpha set to one. The colors are empty
+/// if no vertex has one. Values that aren't numbers are left for the parser to report.
+/// Line numbers are preserved.
+pub(super) fn strip_vertex_colors(text: &str) -> (Cow<'_, str>, Vec<Option<[f32; 4]>>) {
+    let mut colors = Vec::new();
+    let mut colored = false;
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    for line in text.split('\n') {
+        let statement = line.split('#').next().unwrap_or("");
+        let words = statement.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["v", x, y, z, r, g, b] => {
+                let color = [r, g, b]
+                    .iter()
+                    .map(|word| word.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>();
+                match color {
+                    Ok(color) => {
+                        colors.push(Some([color[0], color[1], color[2], 1.0]));
+                        colored = true;
+                        let rewritten =
+                            format!("v {} {} {}{}", x, y, z, &line[statement.trim_end().len()..]);
+                        output.push_str(&text[copied..offset]);
+                        output.push_str(&rewritten);
+                        copied = offset + line.len();
+                    }
+                    Err(_) => colors.push(None),
+                }
+            }
+            ["v", ..] => colors.push(None),
+            _ => {}
+        }
+        offset += line.len() + 1;
+    }
+
+    if !colored {
+        colors.clear();
+    }
+    if copied == 0 {
+        (Cow::Borrowed(text), colors)
+    } else {
+        output.push_str(&text[copied..]);
+        (Cow::Owned(output), colors)
+    }
+}
+
+/// Curve and surface statements, which describe free-form geometry.
+const FREE_FORM_PRIMITIVES: [&str; 3] = ["curv", "curv2", "surf"];
+
+/// Statements that only affect free-form geometry.
+const FREE_FORM_STATEMENTS: [&str; 12] = [
+    "vp", "cstype", "deg", "bmat", "step", "parm", "trim", "hole", "scrv", "sp", "end", "con",
+];
+
+/// Blank out free-form geometry statements, which the parser doesn't understand.
+///
+/// Continued statements must already be joined by `join_continued_lines`.
+/// Returns the remaining text together with the line number and keyword
+/// of each removed curve or surface. Line numbers are preserved.
+pub(super) fn strip_free_form(text: &str) -> (Cow<'_, str>, Vec<(usize, &str)>) {
+    let mut skipped = Vec::new();
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    for (line_index, line) in text.split('\n').enumerate() {
+        let statement = line.split('#').next().unwrap_or("");
+        let strip = match statement.split_whitespace().next() {
+            Some(keyword) if FREE_FORM_PRIMITIVES.contains(&keyword) => {
+                skipped.push((line_index + 1, keyword));
+                true
+            }
+            Some(keyword) => FREE_FORM_STATEMENTS.contains(&keyword),
+            None => false,
+        };
+        if strip {
+            output.push_str(&text[copied..offset]);
+            copied = offset + line.trim_end_matches('\r').len();
+        }
+        offset += line.len() + 1;
+    }
+
+    if copied == 0 {
+        (Cow::Borrowed(text), skipped)
+    } else {
+        output.push_str(&text[copied..]);
+        (Cow::Owned(output), skipped)
+    }
+}
+
+/// Blank out `mtllib` statements, so that the parser accepts any number of them.
+///
+/// Returns the remaining text together with the names of all referenced material libraries,
+/// in the order they are referenced. Line numbers are preserved.
+pub(super) fn strip_material_libraries(text: &str) -> (Cow<'_, str>, Vec<&str>) {
+    let mut libraries = Vec::new();
+    let mut output = String::new();
+    let mut copied = 0;
+    let mut offset = 0;
+
+    for line in text.split('\n') {
+        let statement = line.split('#').next().unwrap_or("");
+        let mut words = statement.split_whitespace();
+        if words.next() == Some("mtllib") {
+            libraries.extend(words);
+            output.push_str(&text[copied..offset]);
+            copied = offset + line.trim_end_matches('\r').len();
+        }
+        offset += line.len() + 1;
+    }
+
+    if copied == 0 {
+        (Cow::Borrowed(text), libraries)
+    } else {
+        output.push_str(&text[copied..]);
+        (Cow::Owned(output), libraries)
+    }
+}
+
+/// Line number, keyword and number of corners of the first `f`, `l` or `p` statement
+/// with too few corners to form its primitive.
+pub(super) fn find_degenerate_primitive(text: &str) -> Option<(usize, &str, usize)> {
+    text.split('\n').enumerate().find_map(|(line_index, line)| {
+        let mut words = line.split('#').next().unwrap_or("").split_whitespace();
+        let keyword = words.next()?;
+        let min = match keyword {
+            "f" => 3,
+            "l" => 2,
+            "p" => 1,
+            _ => return None,
+        };
+        let corners = words.count();
+        if corners < min {
+            Some((line_index + 1, keyword, corners))
+        } else {
+            None
+        }
+    })
+}
+
+/// Number of corners of each `f` statement, in order.
+pub(super) fn face_sizes(text: &str) -> Vec<usize> {
+    text.lines()
+        .filter_map(|line| {
+            let mut words = line.split('#').next().unwrap_or("").split_whitespace();
+            if words.next() == Some("f") {
+                Some(words.count())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn resolve(index: &str, count: usize) -> Option<usize> {
+    if !index.starts_with('-') {
+        return None;
+    }
+    let relative: usize = index[1..].parse().ok()?;
+    if relative == 0 || relative > count {
+        None
+    } else {
+        Some(count + 1 - relative)
+    }
+}
@@ -0,0 +1,173 @@
+//! Triangulation of polygonal faces.
+
+/// Triangulate a simple polygon, which may be concave, by ear clipping.
+///
+/// Returns triangles as indices into `points` with the winding of the polygon.
+/// The polygon is projected onto the plane most perpendicular to its normal.
+/// Zero-area ears, produced by collinear or repeated vertices, are skipped.
+/// Self-intersecting polygons are still triangulated, although not meaningfully.
+pub(super) fn ear_clip(points: &[[f64; 3]]) -> Vec<[usize; 3]> {
+    let normal = newell_normal(points);
+    // Drop the axis along which the normal is largest.
+    let axis = (0..3)
+        .max_by(|&a, &b| {
+            normal[a]
+                .abs()
+                .partial_cmp(&normal[b].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap();
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    // Projection flips the orientation if the normal points along the negative axis.
+    let sign = if normal[axis] < 0.0 { -1.0 } else { 1.0 };
+    let projected = points
+        .iter()
+        .map(|p| [p[u], p[v] * sign])
+        .collect::<Vec<_>>();
+
+    let scale = projected
+        .iter()
+        .flat_map(|p| p.iter())
+        .fold(0.0f64, |max, c| max.max(c.abs()));
+    let epsilon = scale * scale * 1e-12;
+
+    let mut remaining = (0..points.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corner = |i: usize| {
+            (
+                remaining[(i + len - 1) % len],
+                remaining[i],
+                remaining[(i + 1) % len],
+            )
+        };
+
+        let ear = (0..len).find(|&i| {
+            let (a, b, c) = corner(i);
+            area(projected[a], projected[b], projected[c]) > epsilon
+                && !remaining.iter().any(|&p| {
+                    p != a
+                        && p != b
+                        && p != c
+                        && projected[p] != projected[a]
+                        && projected[p] != projected[b]
+                        && projected[p] != projected[c]
+                        && inside(projected[p], projected[a], projected[b], projected[c])
+                })
+        });
+
+        match ear {
+            Some(i) => {
+                let (a, b, c) = corner(i);
+                triangles.push([a, b, c]);
+                remaining.remove(i);
+            }
+            None => {
+                // Drop a degenerate vertex if there is one, otherwise the polygon
+                // is self-intersecting and any ear is clipped to make progress.
+                let i = (0..len)
+                    .find(|&i| {
+                        let (a, b, c) = corner(i);
+                        area(projected[a], projected[b], projected[c]).abs() <= epsilon
+                    })
+                    .unwrap_or_else(|| {
+                        let (a, b, c) = corner(0);
+                        triangles.push([a, b, c]);
+                        0
+                    });
+                remaining.remove(i);
+            }
+        }
+    }
+
+    if let [a, b, c] = remaining[..] {
+        if area(projected[a], projected[b], projected[c]).abs() > epsilon {
+            triangles.push([a, b, c]);
+        }
+    }
+    triangles
+}
+
+/// Polygon normal by Newell's method, which is robust for concave polygons.
+fn newell_normal(points: &[[f64; 3]]) -> [f64; 3] {
+    let mut normal = [0.0; 3];
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        normal[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        normal[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        normal[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    normal
+}
+
+/// Twice the signed area of a triangle, positive for counter-clockwise winding.
+fn area(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Whether `p` is inside or on the boundary of counter-clockwise triangle `abc`.
+fn inside(p: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    area(a, b, p) >= 0.0 && area(b, c, p) >= 0.0 && area(c, a, p) >= 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle_area(points: &[[f64; 3]], [a, b, c]: [usize; 3]) -> f64 {
+        let p = |i: usize| [points[i][0], points[i][1]];
+        area(p(a), p(b), p(c)) / 2.0
+    }
+
+    #[test]
+    fn test_ear_clip_l_shape() {
+        let points = [
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 2.0, 0.0],
+            [0.0, 2.0, 0.0],
+        ];
+        let triangles = ear_clip(&points);
+        assert_eq!(triangles.len(), 4);
+
+        // All triangles keep the winding and are inside the L, so with the total area
+        // matching the polygon they can't overlap.
+        let mut total = 0.0;
+        for &triangle in &triangles {
+            let area = triangle_area(&points, triangle);
+            assert!(area > 0.0);
+            total += area;
+            let centroid = (0..2)
+                .map(|axis| triangle.iter().map(|&i| points[i][axis]).sum::<f64>() / 3.0)
+                .collect::<Vec<_>>();
+            assert!(centroid[0] < 1.0 || centroid[1] < 1.0);
+        }
+        assert!((total - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ear_clip_degenerate() {
+        // Square in the xz plane with a collinear vertex in the middle of an edge.
+        let points = [
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+        ];
+        let triangles = ear_clip(&points);
+        let areas = triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let p = |i: usize| [points[i][0], points[i][2]];
+                area(p(a), p(b), p(c)).abs() / 2.0
+            })
+            .collect::<Vec<_>>();
+        assert!(areas.iter().all(|&area| area > 0.0));
+        assert!((areas.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,877 @@
+//! Loading and writing mesh data in ply format.
+
+use {
+    crate::{mesh::MeshBuilder, Color, Normal, Position, TexCoord},
+    rendy_core::hal::pso::Primitive,
+};
+
+/// Ply loading error.
+#[derive(Debug, PartialEq)]
+pub enum PlyError {
+    /// The header is malformed or describes unsupported data.
+    Header {
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+    /// Reading the ply data failed.
+    Io(std::io::ErrorKind),
+    /// The ascii body was improper UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// The body ended before all declared elements were read.
+    UnexpectedEof,
+    /// An ascii value couldn't be parsed as its declared type.
+    InvalidValue {
+        /// Name of the element being read.
+        element: String,
+        /// Index of the element being read.
+        index: usize,
+    },
+    /// A face references vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Index of the face.
+        face: usize,
+        /// The invalid index.
+        index: usize,
+        /// Number of vertices defined in the file.
+        len: usize,
+    },
+}
+
+impl std::error::Error for PlyError {}
+impl std::fmt::Display for PlyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlyError::Header {
+                line_number,
+                message,
+            } => write!(
+                f,
+                "Error parsing ply header at line {}: {}",
+                line_number, message
+            ),
+            PlyError::Io(kind) => write!(f, "Error reading ply file: {:?}", kind),
+            PlyError::Utf8(e) => write!(f, "{}", e),
+            PlyError::UnexpectedEof => write!(f, "Unexpected end of ply data"),
+            PlyError::InvalidValue { element, index } => {
+                write!(f, "Invalid value in {} {}", element, index)
+            }
+            PlyError::IndexOutOfRange { face, index, len } => write!(
+                f,
+                "Face {} references vertex {} but only {} are defined",
+                face, index, len
+            ),
+        }
+    }
+}
+
+/// Load mesh data from ply.
+///
+/// Ascii, binary little endian and binary big endian encodings are supported.
+/// Vertices must have `x`, `y` and `z` properties. `nx ny nz`, `s t` (or `u v`)
+/// and `red green blue` with optional `alpha` are loaded when present.
+/// Integer colors are normalized to `0.0..=1.0`.
+///
+/// Polygonal faces are triangulated as a fan. Without a `face` element
+/// the mesh is loaded as a point list.
+pub fn load_from_ply(bytes: &[u8]) -> Result<MeshBuilder<'static>, PlyError> {
+    let (header, body) = parse_header(bytes)?;
+    let vertex = header.elements.iter().position(|e| e.name == "vertex");
+    let face = header.elements.iter().position(|e| e.name == "face");
+    let vertex_layout = match vertex {
+        Some(vertex) => VertexLayout::new(&header.elements[vertex])
+            .ok_or_else(|| header.error("Vertex element lacks x, y or z property"))?,
+        None => return Err(header.error("No vertex element")),
+    };
+    let face_list = match face {
+        Some(face) => Some(
+            header.elements[face]
+                .properties
+                .iter()
+                .position(|p| {
+                    (p.name == "vertex_indices" || p.name == "vertex_index") && p.count.is_some()
+                })
+                .ok_or_else(|| header.error("Face element lacks vertex_indices list"))?,
+        ),
+        None => None,
+    };
+
+    let mut reader = match header.encoding {
+        Encoding::Ascii => Reader::Ascii(
+            std::str::from_utf8(body)
+                .map_err(PlyError::Utf8)?
+                .split_ascii_whitespace(),
+        ),
+        Encoding::BinaryLittleEndian => Reader::Binary {
+            bytes: body,
+            big_endian: false,
+        },
+        Encoding::BinaryBigEndian => Reader::Binary {
+            bytes: body,
+            big_endian: true,
+        },
+    };
+
+    // Faces may precede vertices, so indices are checked against the declared count.
+    let len = vertex.map_or(0, |vertex| header.elements[vertex].count);
+    let mut data = PlyData::default();
+    let mut row = Row::default();
+    for (element_index, element) in header.elements.iter().enumerate() {
+        for index in 0..element.count {
+            reader
+                .read_row(element, &mut row)
+                .map_err(|error| match error {
+                    ReadError::Eof => PlyError::UnexpectedEof,
+                    ReadError::Invalid => PlyError::InvalidValue {
+                        element: element.name.clone(),
+                        index,
+                    },
+                })?;
+            if Some(element_index) == vertex {
+                vertex_layout.push(&row, &mut data);
+            } else if Some(element_index) == face {
+                let corners = row.list(face_list.unwrap());
+                for i in 2..corners.len().max(2) {
+                    for &corner in &[corners[0], corners[i - 1], corners[i]] {
+                        // Negative indices saturate so they are reported as out of range.
+                        let corner = if corner < 0.0 {
+                            u32::MAX
+                        } else {
+                            corner as u32
+                        };
+                        if corner as usize >= len {
+                            return Err(PlyError::IndexOutOfRange {
+                                face: index,
+                                index: corner as usize,
+                                len,
+                            });
+                        }
+                        data.indices.push(corner);
+                    }
+                }
+            }
+        }
+    }
+
+    log::trace!(
+        "Loaded {} ply vertices and {} triangles",
+        len,
+        data.indices.len() / 3
+    );
+
+    let mut builder = MeshBuilder::new();
+    if face.is_some() {
+        builder.set_indices(data.indices);
+    } else {
+        builder.set_prim_type(Primitive::PointList);
+    }
+    builder.add_vertices(data.positions);
+    if vertex_layout.normal.is_some() {
+        builder.add_vertices(data.normals);
+    }
+    if vertex_layout.tex_coord.is_some() {
+        builder.add_vertices(data.tex_coords);
+    }
+    if vertex_layout.color.is_some() {
+        builder.add_vertices(data.colors);
+    }
+    Ok(builder)
+}
+
+/// Load mesh data from the ply file at `path`.
+///
+/// Same as `load_from_ply`, but parses the memory-mapped file instead of reading it first.
+/// The file must not be modified or truncated by other processes while it is loaded.
+/// This can't be enforced, and truncation may crash the process.
+/// The loaded mesh owns its data and doesn't borrow the mapping.
+#[cfg(feature = "mmap")]
+pub fn load_from_ply_path(path: &std::path::Path) -> Result<MeshBuilder<'static>, PlyError> {
+    let map = super::mmap::map(path).map_err(|e| PlyError::Io(e.kind()))?;
+    load_from_ply(&map)
+}
+
+/// Write mesh data as ply.
+///
+/// Writes `x y z` vertex properties and, when the mesh has them, `nx ny nz` normals,
+/// `s t` texture coordinates and `red green blue alpha` colors.
+/// Colors are clamped to `0.0..=1.0` and stored as `uchar`, as most tools expect.
+/// Triangle lists are written as faces, point lists as vertices only.
+///
+/// With `binary` the body is encoded as binary little endian, otherwise as ascii.
+/// Fails with `InvalidInput` if the mesh has no positions or other primitive type.
+pub fn write_to_ply<W>(builder: &MeshBuilder<'_>, out: &mut W, binary: bool) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+    let faces = match builder.prim_type() {
+        Primitive::TriangleList => true,
+        Primitive::PointList => false,
+        _ => return Err(invalid("Only triangle and point lists can be written")),
+    };
+    let positions = builder
+        .read_attribute::<Position>()
+        .ok_or_else(|| invalid("Mesh has no positions"))?;
+    let normals = builder.read_attribute::<Normal>();
+    let tex_coords = builder.read_attribute::<TexCoord>();
+    let colors = builder.read_attribute::<Color>();
+    let indices = match (faces, builder.read_indices()) {
+        (false, _) => Vec::new(),
+        (true, Some(indices)) => indices,
+        (true, None) => (0..positions.len() as u32).collect(),
+    };
+
+    let encoding = if binary {
+        "binary_little_endian"
+    } else {
+        "ascii"
+    };
+    writeln!(out, "ply\nformat {} 1.0", encoding)?;
+    writeln!(out, "element vertex {}", positions.len())?;
+    let mut properties = vec!["float x", "float y", "float z"];
+    if normals.is_some() {
+        properties.extend_from_slice(&["float nx", "float ny", "float nz"]);
+    }
+    if tex_coords.is_some() {
+        properties.extend_from_slice(&["float s", "float t"]);
+    }
+    if colors.is_some() {
+        properties.extend_from_slice(&["uchar red", "uchar green", "uchar blue", "uchar alpha"]);
+    }
+    for property in properties {
+        writeln!(out, "property {}", property)?;
+    }
+    if faces {
+        writeln!(out, "element face {}", indices.len() / 3)?;
+        writeln!(out, "property list uchar uint vertex_indices")?;
+    }
+    writeln!(out, "end_header")?;
+
+    let mut row = RowWriter {
+        binary,
+        bytes: Vec::new(),
+    };
+    for (index, position) in positions.iter().enumerate() {
+        let mut values = position.0.to_vec();
+        if let Some(normals) = &normals {
+            values.extend_from_slice(&normals[index].0);
+        }
+        if let Some(tex_coords) = &tex_coords {
+            values.extend_from_slice(&tex_coords[index].0);
+        }
+        for &value in &values {
+            row.float(value);
+        }
+        if let Some(colors) = &colors {
+            for &channel in &colors[index].0 {
+                row.uchar((channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        }
+        row.finish(out)?;
+    }
+    for triangle in indices.chunks_exact(3) {
+        row.uchar(3);
+        for &index in triangle {
+            row.uint(index);
+        }
+        row.finish(out)?;
+    }
+    Ok(())
+}
+
+/// Encoder of the values of a single element.
+struct RowWriter {
+    binary: bool,
+    bytes: Vec<u8>,
+}
+
+impl RowWriter {
+    fn value(&mut self, value: impl ToString, le_bytes: &[u8]) {
+        if self.binary {
+            self.bytes.extend_from_slice(le_bytes);
+        } else {
+            if !self.bytes.is_empty() {
+                self.bytes.push(b' ');
+            }
+            self.bytes.extend_from_slice(value.to_string().as_bytes());
+        }
+    }
+
+    fn float(&mut self, value: f32) {
+        self.value(value, &value.to_le_bytes());
+    }
+
+    fn uchar(&mut self, value: u8) {
+        self.value(value, &[value]);
+    }
+
+    fn uint(&mut self, value: u32) {
+        self.value(value, &value.to_le_bytes());
+    }
+
+    /// Write the row to `out` and start a new one.
+    fn finish<W: std::io::Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        if !self.binary {
+            self.bytes.push(b'\n');
+        }
+        out.write_all(&self.bytes)?;
+        self.bytes.clear();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// Factor mapping the full range of an unsigned color channel to `0.0..=1.0`.
+    fn color_scale(self) -> f64 {
+        match self {
+            Scalar::U8 => 1.0 / 255.0,
+            Scalar::U16 => 1.0 / 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    /// Type of the list length for list properties.
+    count: Option<Scalar>,
+    /// Type of the value, or of the list items for list properties.
+    value: Scalar,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+#[derive(Debug)]
+struct Header {
+    encoding: Encoding,
+    elements: Vec<Element>,
+    lines: usize,
+}
+
+impl Header {
+    fn error(&self, message: &str) -> PlyError {
+        PlyError::Header {
+            line_number: self.lines,
+            message: message.to_owned(),
+        }
+    }
+}
+
+/// Parse the header, returning it along with the remaining body bytes.
+fn parse_header(bytes: &[u8]) -> Result<(Header, &[u8]), PlyError> {
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut rest = bytes;
+    let mut line_number = 0;
+
+    loop {
+        line_number += 1;
+        let error = |message: &str| PlyError::Header {
+            line_number,
+            message: message.to_owned(),
+        };
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| error("Expected end_header"))?;
+        let line = std::str::from_utf8(&rest[..end]).map_err(|_| error("Invalid UTF-8"))?;
+        rest = &rest[end + 1..];
+
+        let mut words = line.split_whitespace();
+        let keyword = words.next();
+        if line_number == 1 {
+            if keyword != Some("ply") {
+                return Err(error("Expected ply"));
+            }
+            continue;
+        }
+        match keyword {
+            Some("format") => {
+                encoding = Some(match words.next() {
+                    Some("ascii") => Encoding::Ascii,
+                    Some("binary_little_endian") => Encoding::BinaryLittleEndian,
+                    Some("binary_big_endian") => Encoding::BinaryBigEndian,
+                    _ => return Err(error("Unknown format")),
+                });
+            }
+            Some("element") => {
+                let name = words.next().ok_or_else(|| error("Expected element name"))?;
+                let count = words
+                    .next()
+                    .and_then(|count| count.parse().ok())
+                    .ok_or_else(|| error("Expected element count"))?;
+                elements.push(Element {
+                    name: name.to_owned(),
+                    count,
+                    properties: Vec::new(),
+                });
+            }
+            Some("property") => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("Unexpected property before element"))?;
+                let ty = words
+                    .next()
+                    .ok_or_else(|| error("Expected property type"))?;
+                let (count, ty) = if ty == "list" {
+                    let count = words
+                        .next()
+                        .and_then(Scalar::from_name)
+                        .ok_or_else(|| error("Unknown list count type"))?;
+                    (Some(count), words.next())
+                } else {
+                    (None, Some(ty))
+                };
+                let value = ty
+                    .and_then(Scalar::from_name)
+                    .ok_or_else(|| error("Unknown property type"))?;
+                let name = words
+                    .next()
+                    .ok_or_else(|| error("Expected property name"))?;
+                element.properties.push(Property {
+                    name: name.to_owned(),
+                    count,
+                    value,
+                });
+            }
+            Some("comment") | Some("obj_info") | None => {}
+            Some("end_header") => break,
+            Some(_) => return Err(error("Unexpected keyword")),
+        }
+    }
+
+    let encoding = encoding.ok_or(PlyError::Header {
+        line_number,
+        message: "Expected format".to_owned(),
+    })?;
+    Ok((
+        Header {
+            encoding,
+            elements,
+            lines: line_number,
+        },
+        rest,
+    ))
+}
+
+/// Values of the properties of a single element.
+#[derive(Debug, Default)]
+struct Row {
+    values: Vec<f64>,
+    /// Range of values belonging to each property.
+    ranges: Vec<std::ops::Range<usize>>,
+}
+
+impl Row {
+    fn scalar(&self, property: usize) -> f64 {
+        self.values[self.ranges[property].start]
+    }
+
+    fn list(&self, property: usize) -> &[f64] {
+        &self.values[self.ranges[property].clone()]
+    }
+}
+
+enum ReadError {
+    Eof,
+    Invalid,
+}
+
+enum Reader<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { bytes: &'a [u8], big_endian: bool },
+}
+
+impl Reader<'_> {
+    fn read_row(&mut self, element: &Element, row: &mut Row) -> Result<(), ReadError> {
+        row.values.clear();
+        row.ranges.clear();
+        for property in &element.properties {
+            let len = match property.count {
+                Some(count) => {
+                    let len = self.read(count)?;
+                    if len < 0.0 || len.fract() != 0.0 {
+                        return Err(ReadError::Invalid);
+                    }
+                    len as usize
+                }
+                None => 1,
+            };
+            let start = row.values.len();
+            for _ in 0..len {
+                let value = self.read(property.value)?;
+                row.values.push(value);
+            }
+            row.ranges.push(start..row.values.len());
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, scalar: Scalar) -> Result<f64, ReadError> {
+        match self {
+            Reader::Ascii(words) => words
+                .next()
+                .ok_or(ReadError::Eof)?
+                .parse()
+                .map_err(|_| ReadError::Invalid),
+            Reader::Binary { bytes, big_endian } => {
+                let size = scalar.size();
+                if bytes.len() < size {
+                    return Err(ReadError::Eof);
+                }
+                let (value, rest) = bytes.split_at(size);
+                *bytes = rest;
+                let mut raw = [0; 8];
+                raw[..size].copy_from_slice(value);
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+                Ok(match scalar {
+                    Scalar::I8 => f64::from(raw[0] as i8),
+                    Scalar::U8 => f64::from(raw[0]),
+                    Scalar::I16 => f64::from(i16::from_le_bytes([raw[0], raw[1]])),
+                    Scalar::U16 => f64::from(u16::from_le_bytes([raw[0], raw[1]])),
+                    Scalar::I32 => f64::from(i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+                    Scalar::U32 => f64::from(u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+                    Scalar::F32 => f64::from(f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+                    Scalar::F64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PlyData {
+    positions: Vec<Position>,
+    normals: Vec<Normal>,
+    tex_coords: Vec<TexCoord>,
+    colors: Vec<Color>,
+    indices: Vec<u32>,
+}
+
+/// Indices of the vertex properties making up each attribute.
+#[derive(Debug)]
+struct VertexLayout {
+    position: [usize; 3],
+    normal: Option<[usize; 3]>,
+    tex_coord: Option<[usize; 2]>,
+    /// Color channels with the scale normalizing them.
+    color: Option<[(usize, f64); 3]>,
+    alpha: Option<(usize, f64)>,
+}
+
+impl VertexLayout {
+    fn new(element: &Element) -> Option<Self> {
+        let find = |name: &str| {
+            element
+                .properties
+                .iter()
+                .position(|p| p.name == name && p.count.is_none())
+        };
+        let channel = |name: &str| {
+            find(name).map(|index| (index, element.properties[index].value.color_scale()))
+        };
+        Some(VertexLayout {
+            position: [find("x")?, find("y")?, find("z")?],
+            normal: (|| Some([find("nx")?, find("ny")?, find("nz")?]))(),
+            tex_coord: (|| Some([find("s")?, find("t")?]))()
+                .or_else(|| Some([find("u")?, find("v")?])),
+            color: (|| Some([channel("red")?, channel("green")?, channel("blue")?]))(),
+            alpha: channel("alpha"),
+        })
+    }
+
+    fn push(&self, row: &Row, data: &mut PlyData) {
+        let [x, y, z] = self.position;
+        data.positions.push(Position([
+            row.scalar(x) as f32,
+            row.scalar(y) as f32,
+            row.scalar(z) as f32,
+        ]));
+        if let Some([x, y, z]) = self.normal {
+            data.normals.push(Normal([
+                row.scalar(x) as f32,
+                row.scalar(y) as f32,
+                row.scalar(z) as f32,
+            ]));
+        }
+        if let Some([u, v]) = self.tex_coord {
+            data.tex_coords
+                .push(TexCoord([row.scalar(u) as f32, row.scalar(v) as f32]));
+        }
+        if let Some(channels) = self.color {
+            let channel = |(index, scale): (usize, f64)| (row.scalar(index) * scale) as f32;
+            data.colors.push(Color([
+                channel(channels[0]),
+                channel(channels[1]),
+                channel(channels[2]),
+                self.alpha.map_or(1.0, channel),
+            ]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TRIANGLE: &str = "ply
+format ascii 1.0
+comment colored triangle
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+0 1 0 0 0 255
+3 0 1 2
+";
+
+    const QUAD: &str = "ply
+format ascii 1.0
+element vertex 4
+property double x
+property double y
+property double z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+element face 1
+property list uchar uint vertex_index
+end_header
+0 0 0 0 0 1 0 0
+1 0 0 0 0 1 1 0
+1 1 0 0 0 1 1 1
+0 1 0 0 0 1 0 1
+4 0 1 2 3
+";
+
+    /// Binary little endian encoding of `TRIANGLE`.
+    fn binary_triangle() -> Vec<u8> {
+        let mut bytes = TRIANGLE
+            .replace("ascii", "binary_little_endian")
+            .into_bytes();
+        bytes
+            .truncate(bytes.len() - "0 0 0 255 0 0\n1 0 0 0 255 0\n0 1 0 0 0 255\n3 0 1 2\n".len());
+        let vertices = [
+            ([0.0f32, 0.0, 0.0], [255u8, 0, 0]),
+            ([1.0, 0.0, 0.0], [0, 255, 0]),
+            ([0.0, 1.0, 0.0], [0, 0, 255]),
+        ];
+        for (position, color) in vertices.iter() {
+            for c in position.iter() {
+                bytes.extend_from_slice(&c.to_le_bytes());
+            }
+            bytes.extend_from_slice(color);
+        }
+        bytes.push(3);
+        for i in 0..3i32 {
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_load_colors() {
+        let colors = vec![
+            Color([1.0, 0.0, 0.0, 1.0]),
+            Color([0.0, 1.0, 0.0, 1.0]),
+            Color([0.0, 0.0, 1.0, 1.0]),
+        ];
+        for bytes in &[TRIANGLE.as_bytes().to_vec(), binary_triangle()] {
+            let mesh = load_from_ply(bytes).unwrap();
+            assert_eq!(mesh.read_attribute::<Color>(), Some(colors.clone()));
+            assert_eq!(
+                mesh.read_attribute::<Position>(),
+                Some(vec![
+                    Position([0.0, 0.0, 0.0]),
+                    Position([1.0, 0.0, 0.0]),
+                    Position([0.0, 1.0, 0.0]),
+                ])
+            );
+            assert_eq!(mesh.read_attribute::<Normal>(), None);
+        }
+    }
+
+    #[test]
+    fn test_load_quad() {
+        let mesh = load_from_ply(QUAD.as_bytes()).unwrap();
+        assert_eq!(mesh.read_attribute::<Position>().unwrap().len(), 4);
+        assert_eq!(
+            mesh.read_attribute::<Normal>().unwrap()[0],
+            Normal([0.0, 0.0, 1.0])
+        );
+        assert_eq!(
+            mesh.read_attribute::<TexCoord>().unwrap()[2],
+            TexCoord([1.0, 1.0])
+        );
+        assert_eq!(mesh.read_attribute::<Color>(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_load_from_ply_path() {
+        let path = std::env::temp_dir().join("rendy_mesh_test_load_from_ply_path.ply");
+        std::fs::write(&path, QUAD).unwrap();
+        let mesh = load_from_ply_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            format!("{:?}", mesh.unwrap()),
+            format!("{:?}", load_from_ply(QUAD.as_bytes()).unwrap())
+        );
+        assert_eq!(
+            load_from_ply_path(&path).unwrap_err(),
+            PlyError::Io(std::io::ErrorKind::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_write_to_ply() {
+        let positions = vec![
+            Position([0.0, 0.0, 0.0]),
+            Position([1.5, 0.0, 0.0]),
+            Position([0.0, -2.0, 0.25]),
+            Position([1.0, 1.0, 0.0]),
+        ];
+        let colors = vec![
+            Color([1.0, 0.0, 0.0, 1.0]),
+            Color([0.0, 1.0, 0.0, 1.0]),
+            Color([0.0, 0.0, 1.0, 0.0]),
+            Color([1.0, 1.0, 1.0, 1.0]),
+        ];
+        let indices = vec![0u32, 1, 2, 2, 1, 3];
+        let mesh = MeshBuilder::new()
+            .with_indices(indices.clone())
+            .with_vertices(positions.clone())
+            .with_vertices(colors.clone());
+
+        for &binary in &[false, true] {
+            let mut bytes = Vec::new();
+            write_to_ply(&mesh, &mut bytes, binary).unwrap();
+            let loaded = load_from_ply(&bytes).unwrap();
+            assert_eq!(loaded.read_attribute::<Position>(), Some(positions.clone()));
+            assert_eq!(loaded.read_attribute::<Color>(), Some(colors.clone()));
+            assert_eq!(loaded.read_attribute::<Normal>(), None);
+            assert_eq!(loaded.read_indices(), Some(indices.clone()));
+        }
+
+        let loaded = load_from_ply(QUAD.as_bytes()).unwrap();
+        let mut bytes = Vec::new();
+        write_to_ply(&loaded, &mut bytes, false).unwrap();
+        let reloaded = load_from_ply(&bytes).unwrap();
+        assert_eq!(
+            reloaded.read_attribute::<Normal>(),
+            loaded.read_attribute::<Normal>()
+        );
+        assert_eq!(
+            reloaded.read_attribute::<TexCoord>(),
+            loaded.read_attribute::<TexCoord>()
+        );
+
+        let points = MeshBuilder::new()
+            .with_prim_type(Primitive::PointList)
+            .with_vertices(positions.clone());
+        let mut bytes = Vec::new();
+        write_to_ply(&points, &mut bytes, true).unwrap();
+        let loaded = load_from_ply(&bytes).unwrap();
+        assert_eq!(loaded.prim_type(), Primitive::PointList);
+        assert_eq!(loaded.read_attribute::<Position>(), Some(positions));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let out_of_range = TRIANGLE.replace("3 0 1 2", "3 0 1 3");
+        assert_eq!(
+            load_from_ply(out_of_range.as_bytes()).err(),
+            Some(PlyError::IndexOutOfRange {
+                face: 0,
+                index: 3,
+                len: 3,
+            })
+        );
+        // The face index counts polygons, not the triangles they are split into.
+        let polygons = TRIANGLE
+            .replace("element face 1", "element face 2")
+            .replace("3 0 1 2", "4 0 1 2 1\n4 0 1 2 3");
+        assert_eq!(
+            load_from_ply(polygons.as_bytes()).err(),
+            Some(PlyError::IndexOutOfRange {
+                face: 1,
+                index: 3,
+                len: 3,
+            })
+        );
+        let truncated = TRIANGLE.replace("3 0 1 2\n", "");
+        assert_eq!(
+            load_from_ply(truncated.as_bytes()).err(),
+            Some(PlyError::UnexpectedEof)
+        );
+        let no_position = TRIANGLE.replace("property float z\n", "");
+        assert_eq!(
+            load_from_ply(no_position.as_bytes()).err(),
+            Some(PlyError::Header {
+                line_number: 12,
+                message: "Vertex element lacks x, y or z property".to_owned(),
+            })
+        );
+    }
+}
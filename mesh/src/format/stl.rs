@@ -0,0 +1,338 @@
+//! Loading mesh data from stl format.
+
+use {
+    crate::{mesh::MeshBuilder, Normal, Position},
+    std::collections::HashMap,
+};
+
+/// Size of the binary stl header preceding the triangle count.
+const HEADER_SIZE: usize = 80;
+
+/// Size of a single triangle record in binary stl.
+const TRIANGLE_SIZE: usize = 50;
+
+/// Stl loading error.
+#[derive(Debug, PartialEq)]
+pub enum StlError {
+    /// Binary data ended before all declared triangles were read.
+    UnexpectedEof {
+        /// Number of triangles declared in the header.
+        triangles: usize,
+        /// Number of bytes actually passed.
+        len: usize,
+    },
+    /// The ascii data was improper UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// Parsing of the ascii stl failed.
+    Parse {
+        /// Line at which parsing failed.
+        line_number: usize,
+        /// Description of the error.
+        message: String,
+    },
+}
+
+impl std::error::Error for StlError {}
+impl std::fmt::Display for StlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StlError::UnexpectedEof { triangles, len } => write!(
+                f,
+                "Binary stl declares {} triangles but only has {} bytes",
+                triangles, len
+            ),
+            StlError::Utf8(e) => write!(f, "{}", e),
+            StlError::Parse {
+                line_number,
+                message,
+            } => write!(
+                f,
+                "Error parsing stl file at line {}: {}",
+                line_number, message
+            ),
+        }
+    }
+}
+
+/// Load mesh data from stl.
+///
+/// Both binary and ascii stl are supported. Binary data may start with `solid` too,
+/// so the format is decided by whether the length matches the declared triangle count.
+///
+/// Vertices shared by several facets with the same normal are deduplicated.
+/// Facets with a zero normal get one computed from their winding.
+pub fn load_from_stl(bytes: &[u8]) -> Result<MeshBuilder<'static>, StlError> {
+    let triangles = if is_binary(bytes) {
+        parse_binary(bytes)?
+    } else {
+        parse_ascii(std::str::from_utf8(bytes).map_err(StlError::Utf8)?)?
+    };
+    log::trace!("Loaded {} stl triangles", triangles.len());
+
+    let mut positions: Vec<Position> = Vec::new();
+    let mut normals: Vec<Normal> = Vec::new();
+    let mut indices: Vec<u32> = Vec::with_capacity(triangles.len() * 3);
+    let mut index_map: HashMap<([u32; 3], [u32; 3]), u32> = HashMap::new();
+
+    for (normal, corners) in triangles {
+        let normal = facet_normal(normal, corners);
+        for position in corners.iter() {
+            let key = (bits(*position), bits(normal));
+            let index = *index_map.entry(key).or_insert_with(|| {
+                positions.push(Position(*position));
+                normals.push(Normal(normal));
+                positions.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    Ok(MeshBuilder::new()
+        .with_indices(indices)
+        .with_vertices(positions)
+        .with_vertices(normals))
+}
+
+type Triangle = ([f32; 3], [[f32; 3]; 3]);
+
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < HEADER_SIZE + 4 {
+        return false;
+    }
+    let starts_with_solid = bytes
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace())
+        .take(5)
+        .eq(b"solid".iter());
+    !starts_with_solid
+        || binary_count(bytes).checked_mul(TRIANGLE_SIZE) == Some(bytes.len() - HEADER_SIZE - 4)
+}
+
+fn binary_count(bytes: &[u8]) -> usize {
+    let mut count = [0; 4];
+    count.copy_from_slice(&bytes[HEADER_SIZE..HEADER_SIZE + 4]);
+    u32::from_le_bytes(count) as usize
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<Triangle>, StlError> {
+    let count = binary_count(bytes);
+    let data = &bytes[HEADER_SIZE + 4..];
+    if data.len() / TRIANGLE_SIZE < count {
+        return Err(StlError::UnexpectedEof {
+            triangles: count,
+            len: bytes.len(),
+        });
+    }
+
+    Ok(data
+        .chunks_exact(TRIANGLE_SIZE)
+        .take(count)
+        .map(|record| {
+            let vector = |offset: usize| {
+                let mut vector = [0.0; 3];
+                for (i, component) in vector.iter_mut().enumerate() {
+                    let start = offset + i * 4;
+                    let mut raw = [0; 4];
+                    raw.copy_from_slice(&record[start..start + 4]);
+                    *component = f32::from_le_bytes(raw);
+                }
+                vector
+            };
+            (vector(0), [vector(12), vector(24), vector(36)])
+        })
+        .collect())
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<Triangle>, StlError> {
+    let mut triangles = Vec::new();
+    let mut normal = None;
+    let mut corners = Vec::with_capacity(3);
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: &str| StlError::Parse {
+            line_number,
+            message: message.to_owned(),
+        };
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("facet") => {
+                if normal.is_some() {
+                    return Err(error("Unexpected facet inside another facet"));
+                }
+                if words.next() != Some("normal") {
+                    return Err(error("Expected facet normal"));
+                }
+                normal = Some(parse_vector(&mut words).ok_or_else(|| error("Expected 3 floats"))?);
+            }
+            Some("vertex") => {
+                if normal.is_none() {
+                    return Err(error("Unexpected vertex outside of facet"));
+                }
+                if corners.len() == 3 {
+                    return Err(error("Expected exactly 3 vertices per facet"));
+                }
+                corners.push(parse_vector(&mut words).ok_or_else(|| error("Expected 3 floats"))?);
+            }
+            Some("endfacet") => {
+                let normal = normal
+                    .take()
+                    .ok_or_else(|| error("Unexpected endfacet outside of facet"))?;
+                if corners.len() != 3 {
+                    return Err(error("Expected exactly 3 vertices per facet"));
+                }
+                triangles.push((normal, [corners[0], corners[1], corners[2]]));
+                corners.clear();
+            }
+            Some("solid") | Some("endsolid") | Some("outer") | Some("endloop") | None => {}
+            Some(_) => return Err(error("Unexpected token")),
+        }
+    }
+
+    if normal.is_some() {
+        return Err(StlError::Parse {
+            line_number: text.lines().count(),
+            message: "Unterminated facet".to_owned(),
+        });
+    }
+    Ok(triangles)
+}
+
+fn parse_vector<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<[f32; 3]> {
+    let mut vector = [0.0; 3];
+    for component in vector.iter_mut() {
+        *component = words.next()?.parse().ok()?;
+    }
+    Some(vector)
+}
+
+fn facet_normal(normal: [f32; 3], [a, b, c]: [[f32; 3]; 3]) -> [f32; 3] {
+    if normal.iter().any(|n| *n != 0.0) {
+        return normal;
+    }
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len > f32::EPSILON && len.is_finite() {
+        [n[0] / len, n[1] / len, n[2] / len]
+    } else {
+        normal
+    }
+}
+
+/// Hash key of a vector. Adding zero folds `-0.0` into `0.0` so they stay equal.
+fn bits(v: [f32; 3]) -> [u32; 3] {
+    [
+        (v[0] + 0.0).to_bits(),
+        (v[1] + 0.0).to_bits(),
+        (v[2] + 0.0).to_bits(),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TRIANGLE: &str = "solid triangle
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 0
+    outer loop
+      vertex 1 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid triangle
+";
+
+    fn binary(triangles: &[Triangle]) -> Vec<u8> {
+        // Header deliberately starts with `solid` like many exporters write it.
+        let mut bytes = b"solid binary".to_vec();
+        bytes.resize(HEADER_SIZE, 0);
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for (normal, corners) in triangles {
+            for v in std::iter::once(normal).chain(corners.iter()) {
+                for c in v.iter() {
+                    bytes.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&[0, 0]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_ascii() {
+        let triangles = parse_ascii(TRIANGLE).unwrap();
+        assert_eq!(
+            triangles,
+            vec![
+                (
+                    [0.0, 0.0, 1.0],
+                    [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+                ),
+                (
+                    [0.0, 0.0, 0.0],
+                    [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ascii_error() {
+        let text = "solid broken\nfacet normal 0 0 1\nvertex 0 0\n";
+        assert_eq!(
+            parse_ascii(text),
+            Err(StlError::Parse {
+                line_number: 3,
+                message: "Expected 3 floats".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect_binary() {
+        let triangles = parse_ascii(TRIANGLE).unwrap();
+        let bytes = binary(&triangles);
+        assert!(is_binary(&bytes));
+        assert!(!is_binary(TRIANGLE.as_bytes()));
+        assert_eq!(parse_binary(&bytes), Ok(triangles));
+    }
+
+    #[test]
+    fn test_binary_truncated() {
+        let triangles = parse_ascii(TRIANGLE).unwrap();
+        let mut bytes = binary(&triangles);
+        bytes[..5].copy_from_slice(b"model");
+        bytes.truncate(bytes.len() - 1);
+        assert_eq!(
+            load_from_stl(&bytes).err(),
+            Some(StlError::UnexpectedEof {
+                triangles: 2,
+                len: bytes.len(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_from_stl() {
+        let mesh = load_from_stl(TRIANGLE.as_bytes()).unwrap();
+        let positions = mesh.read_attribute::<Position>().unwrap();
+        let normals = mesh.read_attribute::<Normal>().unwrap();
+        // The zero facet normal is computed, so both facets share their vertices.
+        assert_eq!(positions.len(), 4);
+        assert!(normals.iter().all(|n| *n == Normal([0.0, 0.0, 1.0])));
+    }
+}
@@ -0,0 +1,56 @@
+//! Deduplication of vertices referenced by face corners.
+
+/// Collapse equal corners into shared vertices.
+///
+/// Corners are typically tuples of position, texture coordinate and normal indices.
+/// Returns the unique corners, which become the vertices, and the vertex index of each corner.
+/// Unique corners are sorted, so the vertex order depends only on the set of corners.
+pub(super) fn weld<K: Ord>(corners: &[K]) -> (Vec<&K>, Vec<u32>) {
+    let mut unique = corners.iter().collect::<Vec<_>>();
+    unique.sort();
+    unique.dedup();
+    let indices = corners
+        .iter()
+        .map(|corner| unique.binary_search(&corner).unwrap() as u32)
+        .collect();
+    (unique, indices)
+}
+
+/// Collapse equal corners into shared vertices in linear time, if every index returned
+/// by `index` belongs to a single kind of corner, as when the position, texture coordinate
+/// and normal indices of every corner are equal.
+///
+/// Corners must be ordered by their index first, so that the result is the same as that
+/// of `weld`. Returns `None` if two different corners have the same index.
+#[cfg(feature = "obj")]
+pub(super) fn weld_by_index<K: Eq>(
+    corners: &[K],
+    index: impl Fn(&K) -> usize,
+) -> Option<(Vec<&K>, Vec<u32>)> {
+    // Only the range of indices in use is tracked, so that meshes using a small part
+    // of a large vertex list stay cheap.
+    let first = corners.iter().map(&index).min()?;
+    let last = corners.iter().map(&index).max()?;
+    let mut slots: Vec<Option<&K>> = vec![None; last - first + 1];
+    for corner in corners {
+        match &mut slots[index(corner) - first] {
+            Some(known) if *known != corner => return None,
+            Some(_) => {}
+            slot => *slot = Some(corner),
+        }
+    }
+
+    let mut unique = Vec::new();
+    let mut vertex_of = vec![0; slots.len()];
+    for (slot, vertex) in slots.iter().zip(&mut vertex_of) {
+        if let Some(corner) = *slot {
+            *vertex = unique.len() as u32;
+            unique.push(corner);
+        }
+    }
+    let indices = corners
+        .iter()
+        .map(|corner| vertex_of[index(corner) - first])
+        .collect();
+    Some((unique, indices))
+}
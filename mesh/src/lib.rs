@@ -24,6 +24,11 @@ use rendy_resource as resource;
 
 mod format;
 mod mesh;
+mod optimize;
+mod simplify;
+mod subdivide;
+mod tangent;
+mod winding;
 
 pub use crate::{format::*, mesh::*};
 pub use rendy_core::types::vertex::*;
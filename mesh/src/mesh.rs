@@ -3,15 +3,19 @@
 //!
 
 use crate::{
+    attribute_uuid,
     command::{EncoderCommon, Graphics, QueueId, RenderPassEncoder, Supports},
-    core::cast_cow,
+    core::{cast_cow, cast_vec},
     factory::{BufferState, Factory, UploadError},
     memory::{Data, Upload, Write},
     resource::{Buffer, BufferInfo, Escape},
-    AsVertex, VertexFormat,
+    AsAttribute, AsVertex, Attribute, Color, Normal, Position, Tangent, TexCoord, VertexFormat,
 };
 use rendy_core::hal::adapter::PhysicalDevice;
-use std::{borrow::Cow, mem::size_of};
+use std::{
+    borrow::Cow,
+    mem::{align_of, size_of},
+};
 
 /// Vertex buffer with it's format
 #[derive(Debug)]
@@ -76,6 +80,522 @@ impl<'a> From<Cow<'a, [u32]>> for Indices<'a> {
     }
 }
 
+/// Index doesn't fit into the requested index type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexOverflow {
+    /// Position of the index in the index buffer.
+    pub position: usize,
+    /// Value of the index.
+    pub index: u32,
+}
+
+impl std::error::Error for IndexOverflow {}
+impl std::fmt::Display for IndexOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Index {} at position {} doesn't fit into u16",
+            self.index, self.position
+        )
+    }
+}
+
+/// Builders can't be merged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// Builders have different primitive types.
+    PrimitiveMismatch {
+        /// Primitive type of the builder being merged into.
+        this: rendy_core::hal::pso::Primitive,
+        /// Primitive type of the merged builder.
+        other: rendy_core::hal::pso::Primitive,
+    },
+    /// Strips and patches can't be concatenated without changing the rendered primitives.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+    /// Builders have different sets of vertex buffers.
+    VertexFormatMismatch,
+}
+
+impl std::error::Error for MergeError {}
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::PrimitiveMismatch { this, other } => write!(
+                f,
+                "Can't merge mesh of {:?} primitives into mesh of {:?} primitives",
+                other, this
+            ),
+            MergeError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't merge meshes of {:?} primitives", prim)
+            }
+            MergeError::VertexFormatMismatch => {
+                write!(f, "Can't merge meshes with different vertex formats")
+            }
+        }
+    }
+}
+
+/// Builder contents are inconsistent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Size of a vertex buffer is not a multiple of its vertex stride.
+    PartialVertex {
+        /// Index of the vertex buffer.
+        buffer: usize,
+    },
+    /// Vertex buffers have different numbers of vertices.
+    VertexCountMismatch {
+        /// Index of the vertex buffer.
+        buffer: usize,
+        /// Number of vertices in the buffer.
+        len: usize,
+        /// Number of vertices in the first buffer.
+        expected: usize,
+    },
+    /// An index references vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Position of the index in the index buffer.
+        position: usize,
+        /// Value of the index.
+        index: u32,
+        /// Number of vertices.
+        len: usize,
+    },
+    /// Number of indices, or of vertices without an index buffer,
+    /// doesn't make whole primitives of a list topology.
+    PartialPrimitive {
+        /// Number of indices.
+        len: usize,
+        /// Number of vertices of each primitive, three for triangle lists.
+        vertices_per_primitive: usize,
+    },
+}
+
+impl std::error::Error for ValidationError {}
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::PartialVertex { buffer } => write!(
+                f,
+                "Size of vertex buffer {} is not a multiple of its stride",
+                buffer
+            ),
+            ValidationError::VertexCountMismatch {
+                buffer,
+                len,
+                expected,
+            } => write!(
+                f,
+                "Vertex buffer {} has {} vertices but the first one has {}",
+                buffer, len, expected
+            ),
+            ValidationError::IndexOutOfRange {
+                position,
+                index,
+                len,
+            } => write!(
+                f,
+                "Index {} at position {} is out of range for {} vertices",
+                index, position, len
+            ),
+            ValidationError::PartialPrimitive {
+                len,
+                vertices_per_primitive,
+            } => write!(
+                f,
+                "{} indices don't form whole primitives of {} vertices",
+                len, vertices_per_primitive
+            ),
+        }
+    }
+}
+
+/// Set of standard vertex attributes.
+///
+/// Sets are combined with `|`, e.g. `AttributeMask::POSITION | AttributeMask::NORMAL`,
+/// intersected with `&` and complemented with `!`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AttributeMask(u8);
+
+impl AttributeMask {
+    /// No attributes.
+    pub const NONE: Self = AttributeMask(0);
+    /// `Position` attribute.
+    pub const POSITION: Self = AttributeMask(1);
+    /// `Normal` attribute.
+    pub const NORMAL: Self = AttributeMask(1 << 1);
+    /// `TexCoord` attribute.
+    pub const TEX_COORD: Self = AttributeMask(1 << 2);
+    /// `Tangent` attribute.
+    pub const TANGENT: Self = AttributeMask(1 << 3);
+    /// `Color` attribute.
+    pub const COLOR: Self = AttributeMask(1 << 4);
+    /// All standard attributes.
+    pub const ALL: Self = AttributeMask((1 << 5) - 1);
+
+    /// Check if all attributes of `other` are in the set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for AttributeMask {
+    fn default() -> Self {
+        AttributeMask::ALL
+    }
+}
+
+impl std::ops::BitOr for AttributeMask {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        AttributeMask(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitAnd for AttributeMask {
+    type Output = Self;
+    fn bitand(self, other: Self) -> Self {
+        AttributeMask(self.0 & other.0)
+    }
+}
+
+impl std::ops::Not for AttributeMask {
+    type Output = Self;
+    fn not(self) -> Self {
+        AttributeMask(!self.0 & AttributeMask::ALL.0)
+    }
+}
+
+/// Standard vertex attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeKind {
+    /// `Position` attribute.
+    Position,
+    /// `Normal` attribute.
+    Normal,
+    /// `TexCoord` attribute.
+    TexCoord,
+    /// `Tangent` attribute.
+    Tangent,
+    /// `Color` attribute.
+    Color,
+}
+
+impl AttributeKind {
+    /// Name and format of the attribute.
+    fn attribute(self) -> (&'static str, rendy_core::hal::format::Format) {
+        match self {
+            AttributeKind::Position => (Position::NAME, Position::FORMAT),
+            AttributeKind::Normal => (Normal::NAME, Normal::FORMAT),
+            AttributeKind::TexCoord => (TexCoord::NAME, TexCoord::FORMAT),
+            AttributeKind::Tangent => (Tangent::NAME, Tangent::FORMAT),
+            AttributeKind::Color => (Color::NAME, Color::FORMAT),
+        }
+    }
+}
+
+/// An attribute can't be converted to half floats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HalfFloatError {
+    /// The builder lacks the attribute with 32-bit float components.
+    MissingAttribute {
+        /// Name of the attribute.
+        name: &'static str,
+    },
+    /// A component is too large to be represented by a half float.
+    OutOfRange {
+        /// Name of the attribute.
+        name: &'static str,
+        /// The component value.
+        value: f32,
+    },
+}
+
+impl std::error::Error for HalfFloatError {}
+impl std::fmt::Display for HalfFloatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HalfFloatError::MissingAttribute { name } => {
+                write!(f, "No 32-bit float '{}' attribute to convert", name)
+            }
+            HalfFloatError::OutOfRange { name, value } => write!(
+                f,
+                "Value {} of '{}' attribute is out of half float range",
+                value, name
+            ),
+        }
+    }
+}
+
+/// Positions can't be quantized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuantizationError {
+    /// The builder lacks 32-bit float positions.
+    MissingAttribute {
+        /// Name of the attribute.
+        name: &'static str,
+    },
+    /// Only 1 to 16 bits per component are supported.
+    UnsupportedBits(u8),
+}
+
+impl std::error::Error for QuantizationError {}
+impl std::fmt::Display for QuantizationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuantizationError::MissingAttribute { name } => {
+                write!(f, "No 32-bit float '{}' attribute to quantize", name)
+            }
+            QuantizationError::UnsupportedBits(bits) => write!(
+                f,
+                "Can't quantize to {} bits, only 1 to 16 bits are supported",
+                bits
+            ),
+        }
+    }
+}
+
+/// Parameters decoding quantized positions, `position = offset + scale * normalized`,
+/// where `normalized` is the attribute value in `0.0..=1.0` as read by the shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantizationParams {
+    /// Minimum corner of the bounding box of the positions.
+    pub offset: [f32; 3],
+    /// Factor of the normalized value along each axis. This is the size of the bounding box
+    /// unless fewer bits than the format has are used.
+    pub scale: [f32; 3],
+    /// Unsigned normalized format the positions are stored in.
+    pub format: rendy_core::hal::format::Format,
+}
+
+/// Tangents can't be computed for the builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TangentError {
+    /// Tangents are only computed for triangle lists.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+    /// The builder lacks an attribute tangents are computed from.
+    MissingAttribute {
+        /// Name of the attribute.
+        name: &'static str,
+    },
+    /// An index references vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Position of the index in the index buffer.
+        position: usize,
+        /// Value of the index.
+        index: u32,
+        /// Number of vertices.
+        len: usize,
+    },
+}
+
+impl std::error::Error for TangentError {}
+impl std::fmt::Display for TangentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TangentError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't compute tangents of {:?} primitives", prim)
+            }
+            TangentError::MissingAttribute { name } => {
+                write!(f, "Can't compute tangents without '{}' attribute", name)
+            }
+            TangentError::IndexOutOfRange {
+                position,
+                index,
+                len,
+            } => write!(
+                f,
+                "Index {} at position {} is out of range for {} vertices",
+                index, position, len
+            ),
+        }
+    }
+}
+
+/// The builder can't be simplified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimplifyError {
+    /// Only triangle lists are simplified.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+    /// The builder lacks the `Position` attribute.
+    MissingAttribute {
+        /// Name of the attribute.
+        name: &'static str,
+    },
+    /// An index references vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Position of the index in the index buffer.
+        position: usize,
+        /// Value of the index.
+        index: u32,
+        /// Number of vertices.
+        len: usize,
+    },
+    /// An edge is shared by more than two triangles, or by two triangles
+    /// with the same orientation.
+    NonManifold {
+        /// Indices of the vertices of the edge.
+        edge: [u32; 2],
+    },
+}
+
+impl std::error::Error for SimplifyError {}
+impl std::fmt::Display for SimplifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimplifyError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't simplify {:?} primitives", prim)
+            }
+            SimplifyError::MissingAttribute { name } => {
+                write!(f, "Can't simplify mesh without '{}' attribute", name)
+            }
+            SimplifyError::IndexOutOfRange {
+                position,
+                index,
+                len,
+            } => write!(
+                f,
+                "Index {} at position {} is out of range for {} vertices",
+                index, position, len
+            ),
+            SimplifyError::NonManifold { edge } => write!(
+                f,
+                "Edge between vertices {} and {} is not manifold",
+                edge[0], edge[1]
+            ),
+        }
+    }
+}
+
+/// Winding of the builder's triangles can't be made consistent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindingError {
+    /// Only triangle lists are reoriented.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+    /// The surface is not orientable, like a Möbius strip.
+    NonOrientable {
+        /// Index of the triangle that would need both windings.
+        triangle: usize,
+    },
+}
+
+impl std::error::Error for WindingError {}
+impl std::fmt::Display for WindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindingError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't unify winding of {:?} primitives", prim)
+            }
+            WindingError::NonOrientable { triangle } => write!(
+                f,
+                "Mesh is not orientable, triangle {} would need both windings",
+                triangle
+            ),
+        }
+    }
+}
+
+/// Adjacency indices can't be built for the builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdjacencyError {
+    /// Adjacency is only built for triangle lists.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+}
+
+impl std::error::Error for AdjacencyError {}
+impl std::fmt::Display for AdjacencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdjacencyError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't build adjacency of {:?} primitives", prim)
+            }
+        }
+    }
+}
+
+/// Scheme used by `MeshBuilder::subdivide`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubdivisionScheme {
+    /// Loop subdivision of triangle meshes, which splits each triangle into four
+    /// and approximates a smooth surface.
+    Loop,
+}
+
+/// The builder can't be subdivided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubdivisionError {
+    /// The scheme doesn't support the primitive type.
+    UnsupportedPrimitive(rendy_core::hal::pso::Primitive),
+    /// The builder lacks the `Position` attribute.
+    MissingAttribute {
+        /// Name of the attribute.
+        name: &'static str,
+    },
+    /// An index references vertex that doesn't exist.
+    IndexOutOfRange {
+        /// Position of the index in the index buffer.
+        position: usize,
+        /// Value of the index.
+        index: u32,
+        /// Number of vertices.
+        len: usize,
+    },
+}
+
+impl std::error::Error for SubdivisionError {}
+impl std::fmt::Display for SubdivisionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubdivisionError::UnsupportedPrimitive(prim) => {
+                write!(f, "Can't subdivide {:?} primitives", prim)
+            }
+            SubdivisionError::MissingAttribute { name } => {
+                write!(f, "Can't subdivide mesh without '{}' attribute", name)
+            }
+            SubdivisionError::IndexOutOfRange {
+                position,
+                index,
+                len,
+            } => write!(
+                f,
+                "Index {} at position {} is out of range for {} vertices",
+                index, position, len
+            ),
+        }
+    }
+}
+
+/// Way `MeshBuilder::recompute_normals` assigns normals to vertices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NormalSmoothing {
+    /// Average area-weighted normals of all triangles sharing a vertex.
+    Smooth,
+    /// Use the normal of the triangle each vertex belongs to.
+    Flat,
+}
+
+/// Attribute of a vertex buffer of a `MeshBuilder`, see `VertexLayout`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexLayoutAttribute {
+    /// Name of the attribute as used in the shader.
+    pub name: String,
+    /// Format of the attribute.
+    pub format: rendy_core::hal::format::Format,
+    /// Index of the vertex buffer holding the attribute, in the order buffers were added.
+    pub buffer: usize,
+    /// Offset of the attribute within a vertex of the buffer.
+    pub offset: u32,
+    /// Size of a vertex of the buffer.
+    pub stride: u32,
+}
+
+/// Vertex attributes of a `MeshBuilder`, as returned by `MeshBuilder::vertex_layout`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    /// Attributes ordered by vertex buffer and by their offset within the buffer.
+    pub attributes: Vec<VertexLayoutAttribute>,
+}
+
 /// Generics-free mesh builder.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -140,6 +660,84 @@ impl<'a> MeshBuilder<'a> {
         }
     }
 
+    /// Copy of the builder with only the standard attributes in `mask`,
+    /// together with the indices and primitive type.
+    ///
+    /// Other attributes, including custom ones and attributes converted to other formats,
+    /// are left out. Vertex buffers keeping all of their attributes share borrowed data
+    /// with this builder, others are packed anew. Buffers without selected attributes
+    /// are dropped.
+    pub fn clone_with_attributes(&self, mask: AttributeMask) -> MeshBuilder<'a> {
+        let kinds = [
+            (AttributeMask::POSITION, AttributeKind::Position),
+            (AttributeMask::NORMAL, AttributeKind::Normal),
+            (AttributeMask::TEX_COORD, AttributeKind::TexCoord),
+            (AttributeMask::TANGENT, AttributeKind::Tangent),
+            (AttributeMask::COLOR, AttributeKind::Color),
+        ];
+        let selected = kinds
+            .iter()
+            .filter(|&&(bit, _)| mask.contains(bit))
+            .map(|&(_, kind)| {
+                let (name, format) = kind.attribute();
+                attribute_uuid(name, 0, format)
+            })
+            .collect::<Vec<_>>();
+
+        let mut vertices = smallvec::SmallVec::new();
+        for raw in &self.vertices {
+            let kept = raw
+                .format
+                .attributes
+                .iter()
+                .filter(|attribute| selected.contains(&attribute.uuid()))
+                .collect::<Vec<_>>();
+            if kept.len() == raw.format.attributes.len() {
+                vertices.push(raw.clone());
+                continue;
+            }
+            if kept.is_empty() {
+                continue;
+            }
+
+            // Selected attributes are packed in the order they had in the buffer.
+            let mut sources = Vec::new();
+            let mut attributes = Vec::new();
+            let mut stride = 0;
+            for attribute in kept {
+                let element = attribute.element();
+                let size = element.format.surface_desc().bits as u32 / 8;
+                sources.push((element.offset as usize, size as usize));
+                attributes.push(Attribute::new(
+                    attribute.name().to_owned(),
+                    attribute.index(),
+                    rendy_core::hal::pso::Element {
+                        format: element.format,
+                        offset: stride,
+                    },
+                ));
+                stride += size;
+            }
+            let old_stride = raw.format.stride as usize;
+            let mut packed = Vec::with_capacity(raw.vertices.len() / old_stride * stride as usize);
+            for vertex in raw.vertices.chunks_exact(old_stride) {
+                for &(offset, size) in &sources {
+                    packed.extend_from_slice(&vertex[offset..offset + size]);
+                }
+            }
+            vertices.push(RawVertices {
+                vertices: Cow::Owned(packed),
+                format: VertexFormat::with_stride(attributes, stride),
+            });
+        }
+
+        MeshBuilder {
+            vertices,
+            indices: self.indices.clone(),
+            prim: self.prim,
+        }
+    }
+
     /// Set indices buffer to the `MeshBuilder`
     pub fn with_indices<I>(mut self, indices: I) -> Self
     where
@@ -168,6 +766,119 @@ impl<'a> MeshBuilder<'a> {
         self
     }
 
+    /// Set indices buffer to the `MeshBuilder`, using the smallest index type
+    /// that can hold all indices.
+    pub fn with_indices_auto<I>(mut self, indices: I) -> Self
+    where
+        I: Into<Indices<'a>>,
+    {
+        self.set_indices_auto(indices);
+        self
+    }
+
+    /// Set indices buffer to the `MeshBuilder`, using the smallest index type
+    /// that can hold all indices.
+    ///
+    /// `u32` indices are converted to `u16` if none of them exceeds `u16::MAX`.
+    pub fn set_indices_auto<I>(&mut self, indices: I) -> &mut Self
+    where
+        I: Into<Indices<'a>>,
+    {
+        match indices.into() {
+            Indices::U32(indices) if indices.iter().all(|&i| i <= u32::from(u16::MAX)) => {
+                let indices = indices.iter().map(|&i| i as u16).collect::<Vec<_>>();
+                self.set_indices(Indices::U16(Cow::Owned(indices)))
+            }
+            indices => self.set_indices(indices),
+        }
+    }
+
+    /// Set `u16` indices buffer to the `MeshBuilder`, converting from `u32`.
+    ///
+    /// Fails without changing the builder if any index exceeds `u16::MAX`.
+    pub fn set_indices_u16(&mut self, indices: &[u32]) -> Result<&mut Self, IndexOverflow> {
+        let indices = indices
+            .iter()
+            .enumerate()
+            .map(|(position, &index)| {
+                if index > u32::from(u16::MAX) {
+                    Err(IndexOverflow { position, index })
+                } else {
+                    Ok(index as u16)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.set_indices(Indices::U16(Cow::Owned(indices))))
+    }
+
+    /// Append indices to the index buffer of the `MeshBuilder`.
+    ///
+    /// The index type is kept if all appended indices fit into it,
+    /// otherwise the buffer is widened to `u32`. A builder without indices gets a `u32` buffer.
+    pub fn extend_indices<I>(&mut self, indices: I) -> &mut Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        use rendy_core::hal::IndexType;
+
+        let indices = indices.into_iter().collect::<Vec<_>>();
+        match &mut self.indices {
+            Some(raw)
+                if raw.index_type == IndexType::U16
+                    && indices.iter().all(|&i| i <= u32::from(u16::MAX)) =>
+            {
+                let bytes = raw.indices.to_mut();
+                for index in indices {
+                    bytes.extend_from_slice(&(index as u16).to_ne_bytes());
+                }
+            }
+            Some(raw) if raw.index_type == IndexType::U32 => {
+                let bytes = raw.indices.to_mut();
+                for index in indices {
+                    bytes.extend_from_slice(&index.to_ne_bytes());
+                }
+            }
+            _ => {
+                let mut all = self.read_indices().unwrap_or_default();
+                all.extend(indices);
+                self.set_indices(Indices::U32(Cow::Owned(all)));
+            }
+        }
+        self
+    }
+
+    /// Layout of the vertex buffers, listing every attribute with its format,
+    /// buffer, offset and stride.
+    ///
+    /// Buffers are listed in the order they were added, which is also the order
+    /// `build` uploads them in.
+    pub fn vertex_layout(&self) -> VertexLayout {
+        VertexLayout {
+            attributes: self
+                .vertices
+                .iter()
+                .enumerate()
+                .flat_map(|(buffer, raw)| {
+                    raw.format
+                        .attributes
+                        .iter()
+                        .map(move |attribute| VertexLayoutAttribute {
+                            name: attribute.name().to_owned(),
+                            format: attribute.element().format,
+                            buffer,
+                            offset: attribute.element().offset,
+                            stride: raw.format.stride,
+                        })
+                })
+                .collect(),
+        }
+    }
+
+    /// Type of indices in the index buffer, or `None` if the builder has no indices.
+    pub fn index_type(&self) -> Option<rendy_core::hal::IndexType> {
+        self.indices.as_ref().map(|indices| indices.index_type)
+    }
+
     /// Add another vertices to the `MeshBuilder`
     pub fn with_vertices<V, D>(mut self, vertices: D) -> Self
     where
@@ -207,74 +918,1649 @@ impl<'a> MeshBuilder<'a> {
         self
     }
 
-    /// Builds and returns the new mesh.
-    ///
-    /// A mesh expects all vertex buffers to have the same number of elements.
-    /// If those are not equal, the length of smallest vertex buffer is selected,
-    /// effectively discaring extra data from larger buffers.
+    /// Primitive type of the mesh.
+    pub fn prim_type(&self) -> rendy_core::hal::pso::Primitive {
+        self.prim
+    }
+
+    /// Number of vertices, zero if the mesh has no vertex buffers.
     ///
-    /// Note that contents of index buffer is not validated.
-    pub fn build<B>(&self, queue: QueueId, factory: &Factory<B>) -> Result<Mesh<B>, UploadError>
-    where
-        B: rendy_core::hal::Backend,
-    {
-        let align = factory.physical().limits().non_coherent_atom_size;
-        let mut len = self
+    /// All vertex buffers should have the same number of vertices, see `validate`.
+    /// This is checked in debug builds; otherwise the smallest count is returned.
+    pub fn vertex_count(&self) -> usize {
+        let mut counts = self
             .vertices
             .iter()
-            .map(|v| v.vertices.len() as u32 / v.format.stride)
-            .min()
-            .unwrap_or(0);
+            .map(|v| v.vertices.len() / v.format.stride as usize);
+        let first = counts.next().unwrap_or(0);
+        counts.fold(first, |min, count| {
+            debug_assert_eq!(min, count, "Vertex buffers have different lengths");
+            min.min(count)
+        })
+    }
 
-        let buffer_size = self
-            .vertices
-            .iter()
-            .map(|v| (v.format.stride * len) as usize)
-            .sum();
+    /// Number of indices, or the number of vertices if the mesh has no index buffer.
+    ///
+    /// This is the number of vertices drawn, the same as `Mesh::len`.
+    pub fn index_count(&self) -> usize {
+        match &self.indices {
+            Some(RawIndices {
+                indices,
+                index_type,
+            }) => indices.len() / index_stride(*index_type),
+            None => self.vertex_count(),
+        }
+    }
 
-        let aligned_size = align_by(align, buffer_size) as u64;
+    /// Number of triangles drawn, zero unless the mesh is a triangle list or strip.
+    pub fn triangle_count(&self) -> usize {
+        match self.prim {
+            rendy_core::hal::pso::Primitive::TriangleList => self.index_count() / 3,
+            rendy_core::hal::pso::Primitive::TriangleStrip => self.index_count().saturating_sub(2),
+            _ => 0,
+        }
+    }
 
-        let mut staging = factory
-            .create_buffer(
-                BufferInfo {
-                    size: aligned_size,
-                    usage: rendy_core::hal::buffer::Usage::TRANSFER_SRC,
-                },
-                Upload,
-            )
-            .map_err(UploadError::Create)?;
+    /// Values of attribute `A` of every vertex.
+    ///
+    /// Borrows the vertex data if the attribute has a vertex buffer of its own,
+    /// otherwise the values are copied out of the interleaved buffer.
+    /// Returns `None` if no vertex buffer contains the attribute.
+    pub fn attribute<A: AsAttribute>(&self) -> Option<Cow<'_, [A]>> {
+        let (index, offset) = self.find_attribute::<A>()?;
+        let RawVertices { vertices, format } = &self.vertices[index];
+        let ptr = vertices.as_ptr();
+        if offset == 0
+            && format.stride as usize == size_of::<A>()
+            && ptr.align_offset(align_of::<A>()) == 0
+        {
+            let len = vertices.len() / size_of::<A>();
+            // Attributes are plain data, and the bytes are aligned and hold `len` values.
+            let values = unsafe { std::slice::from_raw_parts(ptr.cast::<A>(), len) };
+            return Some(Cow::Borrowed(values));
+        }
+        self.read_attribute().map(Cow::Owned)
+    }
 
-        let mut buffer = factory
-            .create_buffer(
-                BufferInfo {
-                    size: buffer_size as _,
-                    usage: rendy_core::hal::buffer::Usage::VERTEX
-                        | rendy_core::hal::buffer::Usage::TRANSFER_DST,
-                },
-                Data,
-            )
-            .map_err(UploadError::Create)?;
+    /// `Position` of every vertex, see `attribute`.
+    pub fn positions(&self) -> Option<Cow<'_, [Position]>> {
+        self.attribute()
+    }
 
-        let mut mapped = staging
-            .map(factory, 0..aligned_size)
-            .map_err(UploadError::Map)?;
-        let mut writer =
-            unsafe { mapped.write(factory, 0..aligned_size) }.map_err(UploadError::Map)?;
-        let staging_slice = unsafe { writer.slice() };
+    /// `Normal` of every vertex, see `attribute`.
+    pub fn normals(&self) -> Option<Cow<'_, [Normal]>> {
+        self.attribute()
+    }
 
-        let mut offset = 0usize;
-        let mut vertex_layouts: Vec<_> = self
-            .vertices
-            .iter()
-            .map(|RawVertices { vertices, format }| {
-                let size = (format.stride * len) as usize;
-                staging_slice[offset..offset + size].copy_from_slice(&vertices[0..size]);
-                let this_offset = offset as u64;
-                offset += size;
-                VertexBufferLayout {
-                    offset: this_offset,
-                    format: format.clone(),
-                }
+    /// `TexCoord` of every vertex, see `attribute`.
+    pub fn tex_coords(&self) -> Option<Cow<'_, [TexCoord]>> {
+        self.attribute()
+    }
+
+    /// `Tangent` of every vertex, see `attribute`.
+    pub fn tangents(&self) -> Option<Cow<'_, [Tangent]>> {
+        self.attribute()
+    }
+
+    /// `Color` of every vertex, see `attribute`.
+    pub fn colors(&self) -> Option<Cow<'_, [Color]>> {
+        self.attribute()
+    }
+
+    /// Compute axis-aligned bounding box of the mesh
+    /// as a pair of minimum and maximum corners.
+    ///
+    /// Returns `None` if the mesh has no `Position` attribute or no vertices.
+    pub fn bounding_box(&self) -> Option<([f32; 3], [f32; 3])> {
+        let positions = self.read_attribute::<Position>()?;
+        let (first, rest) = positions.split_first()?;
+        Some(
+            rest.iter()
+                .fold((first.0, first.0), |(mut min, mut max), p| {
+                    for i in 0..3 {
+                        min[i] = min[i].min(p.0[i]);
+                        max[i] = max[i].max(p.0[i]);
+                    }
+                    (min, max)
+                }),
+        )
+    }
+
+    /// Compute bounding sphere of the mesh as a pair of center and radius.
+    ///
+    /// The sphere is centered at the center of the bounding box and reaches the farthest vertex.
+    /// It encloses all vertices, but is generally not the minimal enclosing sphere.
+    ///
+    /// Returns `None` if the mesh has no `Position` attribute or no vertices.
+    pub fn bounding_sphere(&self) -> Option<([f32; 3], f32)> {
+        let positions = self.read_attribute::<Position>()?;
+        let (min, max) = self.bounding_box()?;
+        let center = [
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ];
+        let radius_squared = positions
+            .iter()
+            .map(|p| {
+                (0..3)
+                    .map(|i| (p.0[i] - center[i]) * (p.0[i] - center[i]))
+                    .sum::<f32>()
+            })
+            .fold(0.0f32, f32::max);
+        Some((center, radius_squared.sqrt()))
+    }
+
+    /// Compute average position of the vertices, including vertices not used by any index.
+    ///
+    /// Returns `None` if the mesh has no `Position` attribute or no vertices.
+    pub fn centroid_vertices(&self) -> Option<[f32; 3]> {
+        use crate::tangent::{add, scale};
+
+        let positions = self.read_attribute::<Position>()?;
+        if positions.is_empty() {
+            return None;
+        }
+        let sum = positions.iter().fold([0.0; 3], |sum, p| add(sum, p.0));
+        Some(scale(sum, 1.0 / positions.len() as f32))
+    }
+
+    /// Compute centroid of the surface, the average of triangle centroids weighted by area,
+    /// which doesn't depend on how densely parts of the mesh are tessellated.
+    ///
+    /// Returns `None` unless the mesh is a triangle list with `Position` attribute,
+    /// all indices reference existing vertices and the surface area isn't zero.
+    pub fn centroid_surface(&self) -> Option<[f32; 3]> {
+        use crate::tangent::{add, cross, scale, sub};
+
+        let (sum, area) =
+            self.triangle_positions()?
+                .iter()
+                .fold(([0.0; 3], 0.0), |(sum, area), &[a, b, c]| {
+                    let [x, y, z] = cross(sub(b, a), sub(c, a));
+                    let triangle_area = (x * x + y * y + z * z).sqrt() / 2.0;
+                    let centroid = scale(add(add(a, b), c), 1.0 / 3.0);
+                    (
+                        add(sum, scale(centroid, triangle_area)),
+                        area + triangle_area,
+                    )
+                });
+        if area > 0.0 {
+            Some(scale(sum, 1.0 / area))
+        } else {
+            None
+        }
+    }
+
+    /// Compute unit normal of every triangle from positions, in the order of the triangles.
+    ///
+    /// Stored `Normal` values are not used. Degenerate triangles get zero normals.
+    /// Returns `None` unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    pub fn face_normals(&self) -> Option<Vec<[f32; 3]>> {
+        use crate::tangent::{cross, normalize, sub};
+
+        Some(
+            self.triangle_positions()?
+                .iter()
+                .map(|&[a, b, c]| normalize(cross(sub(b, a), sub(c, a))).unwrap_or([0.0; 3]))
+                .collect(),
+        )
+    }
+
+    /// Compute total area of the triangles from positions.
+    ///
+    /// Returns zero unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    pub fn surface_area(&self) -> f32 {
+        use crate::tangent::{cross, sub};
+
+        self.triangle_positions()
+            .unwrap_or_default()
+            .iter()
+            .map(|&[a, b, c]| {
+                let [x, y, z] = cross(sub(b, a), sub(c, a));
+                (x * x + y * y + z * z).sqrt() / 2.0
+            })
+            .sum()
+    }
+
+    /// Compute volume enclosed by the triangles from positions, using the divergence theorem.
+    ///
+    /// Only meaningful for closed meshes. The volume is positive if triangles wind
+    /// counter-clockwise seen from outside and negative if the winding is inverted.
+    /// Returns zero unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    pub fn signed_volume(&self) -> f32 {
+        use crate::tangent::{cross, dot, sub};
+
+        let triangles = self.triangle_positions().unwrap_or_default();
+        // Volumes of the tetrahedra are taken relative to a vertex of the mesh
+        // instead of the origin, to keep precision for meshes far from it.
+        let origin = match triangles.first() {
+            Some(triangle) => triangle[0],
+            None => return 0.0,
+        };
+        triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let (a, b, c) = (sub(a, origin), sub(b, origin), sub(c, origin));
+                dot(a, cross(b, c)) / 6.0
+            })
+            .sum()
+    }
+
+    /// Positions of the corners of every triangle, in the order of the triangles.
+    ///
+    /// Returns `None` unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    fn triangle_positions(&self) -> Option<Vec<[[f32; 3]; 3]>> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return None;
+        }
+        let positions = self.read_attribute::<Position>()?;
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+        if indices
+            .iter()
+            .any(|&index| index as usize >= positions.len())
+        {
+            return None;
+        }
+        Some(
+            indices
+                .chunks_exact(3)
+                .map(|triangle| {
+                    [
+                        positions[triangle[0] as usize].0,
+                        positions[triangle[1] as usize].0,
+                        positions[triangle[2] as usize].0,
+                    ]
+                })
+                .collect(),
+        )
+    }
+
+    /// Reorder triangles to improve the hit rate of the post-transform vertex cache.
+    ///
+    /// Uses Tom Forsyth's linear-speed vertex cache optimization.
+    /// Rendered triangles are preserved exactly, only their order changes.
+    /// Does nothing unless the mesh is an indexed triangle list.
+    pub fn optimize_vertex_cache(&mut self) {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return;
+        }
+        if let Some(indices) = self.read_indices() {
+            let indices = crate::optimize::optimize_vertex_cache(&indices);
+            self.write_indices(indices);
+        }
+    }
+
+    /// Reorder vertices in order of their first use by the index buffer,
+    /// improving locality of vertex fetches.
+    ///
+    /// All vertex buffers are reordered together. Call it after `optimize_vertex_cache`,
+    /// since it relies on the order of indices. Does nothing if the mesh has no indices
+    /// or indices reference vertices missing from some vertex buffer.
+    pub fn optimize_vertex_fetch(&mut self) {
+        let mut indices = match self.read_indices() {
+            Some(indices) => indices,
+            None => return,
+        };
+        let len = self.vertex_count();
+        if indices.iter().any(|&index| index as usize >= len) {
+            log::warn!("Skipping vertex fetch optimization: indices are out of range");
+            return;
+        }
+
+        let order = crate::optimize::optimize_vertex_fetch(&mut indices, len);
+        self.gather_vertices(&order);
+        self.write_indices(indices);
+    }
+
+    /// Remove vertices not referenced by the index buffer, remapping the indices.
+    ///
+    /// All vertex buffers are pruned together and remaining vertices keep their order.
+    /// Does nothing if the mesh has no indices, since then every vertex is drawn,
+    /// or if indices reference vertices missing from some vertex buffer.
+    pub fn prune_unused_vertices(&mut self) {
+        let mut indices = match self.read_indices() {
+            Some(indices) => indices,
+            None => return,
+        };
+        let len = self.vertex_count();
+        if indices.iter().any(|&index| index as usize >= len) {
+            log::warn!("Skipping vertex pruning: indices are out of range");
+            return;
+        }
+
+        let mut used = vec![false; len];
+        for &index in &indices {
+            used[index as usize] = true;
+        }
+        if used.iter().all(|&used| used) {
+            return;
+        }
+        let mut remap = vec![0; len];
+        let mut kept = Vec::new();
+        for (vertex, _) in used.iter().enumerate().filter(|(_, &used)| used) {
+            remap[vertex] = kept.len() as u32;
+            kept.push(vertex as u32);
+        }
+        for index in &mut indices {
+            *index = remap[*index as usize];
+        }
+        self.gather_vertices(&kept);
+        self.write_indices(indices);
+    }
+
+    /// Split the mesh into its connected components, one builder for each.
+    ///
+    /// Primitives are connected if they share a vertex index, so vertices with equal
+    /// positions but different indices don't connect them. Weld the mesh first,
+    /// see `weld_positions`, to connect primitives by position.
+    /// Components are ordered by their first primitive. Each keeps only the vertices
+    /// it uses, in their original order, and the index type of the mesh.
+    /// Meshes without indices stay without them.
+    ///
+    /// Returns a single copy of the mesh unless it is a point, line or triangle list
+    /// with all indices referencing existing vertices.
+    pub fn split_connected_components(&self) -> Vec<MeshBuilder<'static>> {
+        use rendy_core::hal::pso::Primitive;
+
+        let corners = match self.prim {
+            Primitive::PointList => 1,
+            Primitive::LineList => 2,
+            Primitive::TriangleList => 3,
+            _ => return vec![self.clone().into_owned()],
+        };
+        let len = self.vertex_count();
+        let mut indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..len as u32).collect());
+        if indices.iter().any(|&index| index as usize >= len) {
+            log::warn!("Skipping component splitting: indices are out of range");
+            return vec![self.clone().into_owned()];
+        }
+        indices.truncate(indices.len() / corners * corners);
+
+        // Union-find over vertices, joining the vertices of each primitive.
+        fn find(parents: &mut [u32], mut vertex: u32) -> u32 {
+            while parents[vertex as usize] != vertex {
+                let parent = parents[vertex as usize];
+                parents[vertex as usize] = parents[parent as usize];
+                vertex = parent;
+            }
+            vertex
+        }
+        let mut parents = (0..len as u32).collect::<Vec<_>>();
+        for primitive in indices.chunks_exact(corners) {
+            let first = find(&mut parents, primitive[0]);
+            for &vertex in &primitive[1..] {
+                let root = find(&mut parents, vertex);
+                let (low, high) = (first.min(root), first.max(root));
+                parents[high as usize] = low;
+            }
+        }
+
+        const UNUSED: u32 = u32::MAX;
+        let mut root_components = vec![UNUSED; len];
+        let mut components = Vec::<Vec<u32>>::new();
+        for primitive in indices.chunks_exact(corners) {
+            let root = find(&mut parents, primitive[0]) as usize;
+            if root_components[root] == UNUSED {
+                root_components[root] = components.len() as u32;
+                components.push(Vec::new());
+            }
+            components[root_components[root] as usize].extend_from_slice(primitive);
+        }
+
+        let mut remap = vec![UNUSED; len];
+        components
+            .into_iter()
+            .map(|mut indices| {
+                let mut kept = indices.clone();
+                kept.sort_unstable();
+                kept.dedup();
+                for (new, &vertex) in kept.iter().enumerate() {
+                    remap[vertex as usize] = new as u32;
+                }
+                for index in &mut indices {
+                    *index = remap[*index as usize];
+                }
+
+                let mut builder = MeshBuilder {
+                    vertices: self
+                        .vertices
+                        .iter()
+                        .map(|raw| RawVertices {
+                            vertices: Cow::Borrowed(&raw.vertices[..]),
+                            format: raw.format.clone(),
+                        })
+                        .collect(),
+                    indices: None,
+                    prim: self.prim,
+                };
+                builder.gather_vertices(&kept);
+                if let Some(raw) = &self.indices {
+                    builder.indices = Some(RawIndices {
+                        indices: Cow::Borrowed(&[]),
+                        index_type: raw.index_type,
+                    });
+                    builder.write_indices(indices);
+                }
+                builder.into_owned()
+            })
+            .collect()
+    }
+
+    /// Find edges along which the texture coordinates are split, such as the seams
+    /// of an unwrapped mesh.
+    ///
+    /// Edges of triangles are matched by the positions of their ends, so a seam
+    /// is an edge whose triangles use vertices at the same positions but with
+    /// different `TexCoord` values. Each edge along a seam is returned as a pair of vertex
+    /// indices, smaller first, so a seam between two triangles yields the edges of both sides.
+    /// Pairs are sorted.
+    ///
+    /// Returns no edges unless the mesh is a triangle list with `Position` and `TexCoord`
+    /// attributes and all indices reference existing vertices.
+    pub fn uv_seam_edges(&self) -> Vec<(u32, u32)> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Vec::new();
+        }
+        let (positions, tex_coords) = match (
+            self.read_attribute::<Position>(),
+            self.read_attribute::<TexCoord>(),
+        ) {
+            (Some(positions), Some(tex_coords)) => (positions, tex_coords),
+            _ => return Vec::new(),
+        };
+        let len = self.vertex_count();
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..len as u32).collect());
+        if indices.iter().any(|&index| index as usize >= len) {
+            return Vec::new();
+        }
+        let key = |index: u32| {
+            // Adding zero turns negative zero into positive zero.
+            let [x, y, z] = positions[index as usize].0;
+            [
+                (x + 0.0).to_bits(),
+                (y + 0.0).to_bits(),
+                (z + 0.0).to_bits(),
+            ]
+        };
+
+        // Vertex pairs of the triangle edges between each pair of positions,
+        // ordered by position.
+        let mut edges = std::collections::HashMap::<[[u32; 3]; 2], Vec<[u32; 2]>>::new();
+        for triangle in indices.chunks_exact(3) {
+            for corner in 0..3 {
+                let (mut a, mut b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                if key(a) > key(b) {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                if key(a) != key(b) {
+                    edges.entry([key(a), key(b)]).or_default().push([a, b]);
+                }
+            }
+        }
+
+        let mut seams = Vec::new();
+        for sides in edges.values() {
+            for &[a, b] in sides {
+                let split = sides.iter().any(|&[c, d]| {
+                    tex_coords[a as usize] != tex_coords[c as usize]
+                        || tex_coords[b as usize] != tex_coords[d as usize]
+                });
+                if split {
+                    seams.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+        seams.sort_unstable();
+        seams.dedup();
+        seams
+    }
+
+    /// Find edges shared by more than two triangles, which make the surface non-manifold.
+    ///
+    /// Edges are counted from the indices, so vertices split along attribute seams
+    /// should be merged with `weld_positions` first. Each edge is returned as a pair
+    /// of vertex indices, smaller first. Pairs are sorted.
+    ///
+    /// Returns no edges unless the mesh is a triangle list.
+    pub fn non_manifold_edges(&self) -> Vec<(u32, u32)> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Vec::new();
+        }
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..self.vertex_count() as u32).collect());
+
+        let mut uses = std::collections::HashMap::<(u32, u32), usize>::new();
+        for triangle in indices.chunks_exact(3) {
+            for corner in 0..3 {
+                let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                if a != b {
+                    *uses.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        let mut edges = uses
+            .into_iter()
+            .filter(|&(_, count)| count > 2)
+            .map(|(edge, _)| edge)
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+    }
+
+    /// Expand the triangle list indices into indices of a triangle list with adjacency,
+    /// for geometry shaders of pipelines with `InputAssemblerDesc::with_adjacency`.
+    ///
+    /// Each triangle `(a, b, c)` becomes six indices `(a, ab, b, bc, c, ca)`, where `ab`
+    /// is the vertex opposite to the edge from `a` to `b` in the neighbouring triangle.
+    /// Edges without a neighbour reference the opposite vertex of the triangle itself.
+    /// Neighbours share the vertex indices of the edge, see `weld_positions`.
+    /// Meshes without indices are expanded as if indices were sequential.
+    pub fn build_adjacency_indices(&self) -> Result<Vec<u32>, AdjacencyError> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Err(AdjacencyError::UnsupportedPrimitive(self.prim));
+        }
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..self.vertex_count() as u32).collect());
+        let edge = |triangle: &[u32], corner: usize| {
+            let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+            ([a.min(b), a.max(b)], triangle[(corner + 2) % 3])
+        };
+
+        // Triangles using each edge, with their vertex opposite to it.
+        let mut opposites = std::collections::HashMap::<[u32; 2], Vec<(usize, u32)>>::new();
+        for (index, triangle) in indices.chunks_exact(3).enumerate() {
+            for corner in 0..3 {
+                let (key, opposite) = edge(triangle, corner);
+                opposites.entry(key).or_default().push((index, opposite));
+            }
+        }
+
+        let mut adjacency = Vec::with_capacity(indices.len() * 2);
+        for (index, triangle) in indices.chunks_exact(3).enumerate() {
+            for corner in 0..3 {
+                let (key, opposite) = edge(triangle, corner);
+                let neighbour = opposites[&key]
+                    .iter()
+                    .find(|&&(other, _)| other != index)
+                    .map_or(opposite, |&(_, vertex)| vertex);
+                adjacency.push(triangle[corner]);
+                adjacency.push(neighbour);
+            }
+        }
+        Ok(adjacency)
+    }
+
+    /// Append vertices and indices of another builder to this one.
+    ///
+    /// Both builders must have the same primitive type, which must be a list,
+    /// and vertex buffers with the same formats in the same order.
+    /// Indices of `other` are offset by the vertex count of this builder.
+    /// If only one of the builders is indexed, sequential indices are generated for the other.
+    /// `u16` indices are widened to `u32` if the merged indices don't fit.
+    pub fn merge(&mut self, other: &MeshBuilder<'_>) -> Result<&mut Self, MergeError> {
+        use rendy_core::hal::{pso::Primitive, IndexType};
+
+        if self.prim != other.prim {
+            return Err(MergeError::PrimitiveMismatch {
+                this: self.prim,
+                other: other.prim,
+            });
+        }
+        match self.prim {
+            Primitive::PointList | Primitive::LineList | Primitive::TriangleList => {}
+            prim => return Err(MergeError::UnsupportedPrimitive(prim)),
+        }
+        if self.vertices.len() != other.vertices.len()
+            || self
+                .vertices
+                .iter()
+                .zip(other.vertices.iter())
+                .any(|(a, b)| a.format != b.format)
+        {
+            return Err(MergeError::VertexFormatMismatch);
+        }
+
+        let len = self.vertex_count();
+        let other_len = other.vertex_count();
+        let indices = match (self.read_indices(), other.read_indices()) {
+            (None, None) => None,
+            (indices, other_indices) => {
+                let mut indices = indices.unwrap_or_else(|| (0..len as u32).collect());
+                let other_indices =
+                    other_indices.unwrap_or_else(|| (0..other_len as u32).collect());
+                indices.extend(other_indices.into_iter().map(|i| i + len as u32));
+                Some(indices)
+            }
+        };
+
+        // Extra vertices past the shortest buffer would misalign the appended ones.
+        for (raw, other_raw) in self.vertices.iter_mut().zip(other.vertices.iter()) {
+            let stride = raw.format.stride as usize;
+            let mut vertices = raw.vertices.to_vec();
+            vertices.truncate(len * stride);
+            vertices.extend_from_slice(&other_raw.vertices[..other_len * stride]);
+            raw.vertices = Cow::Owned(vertices);
+        }
+
+        if let Some(indices) = indices {
+            let wide = [self.index_type(), other.index_type()].contains(&Some(IndexType::U32))
+                || indices.iter().any(|&i| i > u32::from(u16::MAX));
+            if wide {
+                self.set_indices(Indices::U32(Cow::Owned(indices)));
+            } else {
+                let indices = indices.into_iter().map(|i| i as u16).collect();
+                self.set_indices(Indices::U16(Cow::Owned(indices)));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Expand indexed mesh into a mesh without indices.
+    ///
+    /// Vertices of every vertex buffer are duplicated in the order they are referenced
+    /// by the index buffer, which is then removed. Builders without indices are returned as is.
+    /// Indices must reference existing vertices, see `validate`.
+    pub fn into_unindexed(mut self) -> Self {
+        let indices = match self.read_indices() {
+            Some(indices) => indices,
+            None => return self,
+        };
+        self.gather_vertices(&indices);
+        self.indices = None;
+        self
+    }
+
+    /// Replace every `Position` with the result of `f` applied to it.
+    ///
+    /// Normals and tangents are left as is, so transforms other than translation
+    /// may require `recompute_normals` or `recompute_tangents` afterwards.
+    /// Does nothing if the mesh has no `Position` attribute.
+    pub fn map_positions<F>(&mut self, mut f: F)
+    where
+        F: FnMut([f32; 3]) -> [f32; 3],
+    {
+        if let Some(positions) = self.read_attribute::<Position>() {
+            let positions = positions.into_iter().map(|p| Position(f(p.0))).collect();
+            self.write_attribute(positions);
+        }
+    }
+
+    /// Replace every `TexCoord` with the result of `f` applied to it.
+    ///
+    /// Does nothing if the mesh has no `TexCoord` attribute.
+    pub fn map_tex_coords<F>(&mut self, mut f: F)
+    where
+        F: FnMut([f32; 2]) -> [f32; 2],
+    {
+        if let Some(tex_coords) = self.read_attribute::<TexCoord>() {
+            let tex_coords = tex_coords.into_iter().map(|t| TexCoord(f(t.0))).collect();
+            self.write_attribute(tex_coords);
+        }
+    }
+
+    /// Replace the v coordinate of every `TexCoord` with `1.0 - v`,
+    /// like `ObjLoadOptions::flip_v` does when loading.
+    ///
+    /// Does nothing if the mesh has no `TexCoord` attribute.
+    pub fn flip_texcoord_v(&mut self) {
+        self.map_tex_coords(|[u, v]| [u, 1.0 - v]);
+    }
+
+    /// Move each vertex `lambda` of the way towards the average position of its neighbours,
+    /// repeated `iterations` times, to reduce noise such as that of scanned meshes.
+    ///
+    /// Neighbours are the vertices sharing a triangle edge. Vertices with equal positions
+    /// move together, so seams between other attributes don't open. Vertices on edges
+    /// used by a single triangle, or by more than two, stay in place, keeping open meshes
+    /// from shrinking at their borders. Normals and tangents are left as is,
+    /// so smoothing is usually followed by `recompute_normals`.
+    ///
+    /// Does nothing unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    pub fn laplacian_smooth(&mut self, iterations: u32, lambda: f32) {
+        use crate::tangent::{add, scale, sub};
+
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return;
+        }
+        let positions = match self.read_attribute::<Position>() {
+            Some(positions) => positions,
+            None => return,
+        };
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+        if indices
+            .iter()
+            .any(|&index| index as usize >= positions.len())
+        {
+            return;
+        }
+
+        // Vertices sharing a position form a node, ordered by first use.
+        let mut nodes = std::collections::HashMap::new();
+        let mut node_positions = Vec::new();
+        let node_of = positions
+            .iter()
+            .map(|&Position([x, y, z])| {
+                // Adding zero turns negative zero into positive zero.
+                let key = [
+                    (x + 0.0).to_bits(),
+                    (y + 0.0).to_bits(),
+                    (z + 0.0).to_bits(),
+                ];
+                *nodes.entry(key).or_insert_with(|| {
+                    node_positions.push([x, y, z]);
+                    node_positions.len() - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut uses = std::collections::HashMap::<(usize, usize), usize>::new();
+        for triangle in indices.chunks_exact(3) {
+            for corner in 0..3 {
+                let a = node_of[triangle[corner] as usize];
+                let b = node_of[triangle[(corner + 1) % 3] as usize];
+                if a != b {
+                    *uses.entry((a.min(b), a.max(b))).or_default() += 1;
+                }
+            }
+        }
+        // Sorting makes the sums independent of the order of the hash map.
+        let mut uses = uses.into_iter().collect::<Vec<_>>();
+        uses.sort_unstable();
+        let mut neighbours = vec![Vec::new(); node_positions.len()];
+        let mut fixed = vec![false; node_positions.len()];
+        for ((a, b), count) in uses {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+            if count != 2 {
+                fixed[a] = true;
+                fixed[b] = true;
+            }
+        }
+
+        for _ in 0..iterations {
+            let previous = node_positions.clone();
+            for (node, position) in node_positions.iter_mut().enumerate() {
+                if fixed[node] || neighbours[node].is_empty() {
+                    continue;
+                }
+                let sum = neighbours[node]
+                    .iter()
+                    .fold([0.0; 3], |sum, &other| add(sum, previous[other]));
+                let average = scale(sum, 1.0 / neighbours[node].len() as f32);
+                *position = add(previous[node], scale(sub(average, previous[node]), lambda));
+            }
+        }
+        let positions = node_of
+            .iter()
+            .map(|&node| Position(node_positions[node]))
+            .collect();
+        self.write_attribute(positions);
+    }
+
+    /// Reverse the winding order of every triangle, turning front faces into back faces.
+    ///
+    /// The second and third vertex of each triangle are swapped, in the index buffer
+    /// or in the vertex buffers of meshes without indices. With `negate_normals`
+    /// normals are negated as well and so is the handedness of tangents, keeping them
+    /// consistent with the new front faces. Does nothing unless the mesh is a triangle list.
+    pub fn flip_winding(&mut self, negate_normals: bool) {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return;
+        }
+        match self.read_indices() {
+            Some(mut indices) => {
+                for triangle in indices.chunks_exact_mut(3) {
+                    triangle.swap(1, 2);
+                }
+                self.write_indices(indices);
+            }
+            None => {
+                let mut order = (0..self.vertex_count() as u32).collect::<Vec<_>>();
+                for triangle in order.chunks_exact_mut(3) {
+                    triangle.swap(1, 2);
+                }
+                self.gather_vertices(&order);
+            }
+        }
+
+        if negate_normals {
+            if let Some(normals) = self.read_attribute::<Normal>() {
+                let normals = normals
+                    .into_iter()
+                    .map(|Normal([x, y, z])| Normal([-x, -y, -z]))
+                    .collect();
+                self.write_attribute(normals);
+            }
+            if let Some(tangents) = self.read_attribute::<Tangent>() {
+                let tangents = tangents
+                    .into_iter()
+                    .map(|Tangent([x, y, z, w])| Tangent([x, y, z, -w]))
+                    .collect();
+                self.write_attribute(tangents);
+            }
+        }
+    }
+
+    /// Flip triangles wound inconsistently with their neighbours, so that back-face
+    /// culling removes the same side of the whole surface.
+    ///
+    /// Adjacency comes from the index buffer: triangles are neighbours when they share
+    /// two vertex indices, so separate vertices at equal positions should be merged
+    /// with `weld_positions` first. Each connected part keeps the winding of most
+    /// of its triangles. Meshes without indices are left as is.
+    ///
+    /// Fails without changes if the surface is not orientable.
+    pub fn unify_winding(&mut self) -> Result<&mut Self, WindingError> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Err(WindingError::UnsupportedPrimitive(self.prim));
+        }
+        if let Some(mut indices) = self.read_indices() {
+            crate::winding::unify_winding(&mut indices)
+                .map_err(|triangle| WindingError::NonOrientable { triangle })?;
+            self.write_indices(indices);
+        }
+        Ok(self)
+    }
+
+    /// Compute normals from positions and indices of a triangle list.
+    ///
+    /// Replaces values of the `Normal` attribute, or adds a vertex buffer with them
+    /// if the mesh has none. With `NormalSmoothing::Flat` vertices shared by multiple
+    /// triangles are duplicated first as by `into_unindexed`, which removes the index buffer.
+    /// Vertices not used by any triangle get zero normals.
+    ///
+    /// Does nothing unless the mesh is a triangle list with `Position` attribute
+    /// and all indices reference existing vertices.
+    pub fn recompute_normals(&mut self, smoothing: NormalSmoothing) {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return;
+        }
+        let mut positions = match self.read_attribute::<Position>() {
+            Some(positions) => positions,
+            None => return,
+        };
+        let len = self.vertex_count();
+        let mut indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..len as u32).collect());
+        if indices.iter().any(|&index| index as usize >= len) {
+            log::warn!("Skipping normal computation: indices are out of range");
+            return;
+        }
+        positions.truncate(len);
+
+        if smoothing == NormalSmoothing::Flat && self.indices.is_some() {
+            let mut used = vec![false; len];
+            let shared = indices
+                .iter()
+                .any(|&index| std::mem::replace(&mut used[index as usize], true));
+            if shared {
+                self.gather_vertices(&indices);
+                self.indices = None;
+                positions = indices
+                    .iter()
+                    .map(|&index| positions[index as usize])
+                    .collect();
+                indices = (0..positions.len() as u32).collect();
+            }
+        }
+
+        let mut sums = vec![[0.0f32; 3]; positions.len()];
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [
+                positions[triangle[0] as usize].0,
+                positions[triangle[1] as usize].0,
+                positions[triangle[2] as usize].0,
+            ];
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            // Length of the cross product is twice the triangle area which weights the sum.
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            for &index in triangle {
+                let sum = &mut sums[index as usize];
+                for i in 0..3 {
+                    sum[i] += normal[i];
+                }
+            }
+        }
+        let normals = sums
+            .into_iter()
+            .map(|[x, y, z]| {
+                let length = (x * x + y * y + z * z).sqrt();
+                if length > 0.0 {
+                    Normal([x / length, y / length, z / length])
+                } else {
+                    Normal([0.0; 3])
+                }
+            })
+            .collect();
+        self.write_attribute(normals);
+    }
+
+    /// Compute tangents from positions, normals, texture coordinates and indices
+    /// of a triangle list, following the MikkTSpace conventions.
+    ///
+    /// Replaces values of the `Tangent` attribute, or adds a vertex buffer with them
+    /// if the mesh has none. The `w` component holds the handedness of the texture space.
+    /// Tangents of all triangles sharing a vertex are accumulated before normalization,
+    /// and vertices without usable texture coordinates get a tangent perpendicular to the normal.
+    pub fn recompute_tangents(&mut self) -> Result<&mut Self, TangentError> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Err(TangentError::UnsupportedPrimitive(self.prim));
+        }
+        fn require<A: AsAttribute>(builder: &MeshBuilder<'_>) -> Result<Vec<A>, TangentError> {
+            builder
+                .read_attribute::<A>()
+                .ok_or(TangentError::MissingAttribute { name: A::NAME })
+        }
+        let len = self.vertex_count();
+        let mut positions = require::<Position>(self)?;
+        let mut normals = require::<Normal>(self)?;
+        let mut tex_coords = require::<TexCoord>(self)?;
+        positions.truncate(len);
+        normals.truncate(len);
+        tex_coords.truncate(len);
+
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..len as u32).collect());
+        if let Some(position) = indices.iter().position(|&index| index as usize >= len) {
+            return Err(TangentError::IndexOutOfRange {
+                position,
+                index: indices[position],
+                len,
+            });
+        }
+
+        let tangents = crate::tangent::compute_tangents(
+            &positions,
+            &normals,
+            &tex_coords,
+            &indices,
+            crate::tangent::TangentMode::MikkTSpace,
+        );
+        self.write_attribute(tangents);
+        Ok(self)
+    }
+
+    /// Merge vertices whose positions are within `epsilon` of each other.
+    ///
+    /// Vertices are only merged if all their other attributes match as well,
+    /// so seams of texture coordinates or normals stay intact.
+    /// Components of 32-bit float attributes may differ by up to `epsilon`,
+    /// other attributes must be bitwise equal. Each vertex is merged into the first
+    /// matching vertex before it, so merged vertices keep their first occurrence order.
+    /// Indices are remapped, and generated for builders that had none.
+    ///
+    /// Does nothing if the mesh has no `Position` attribute
+    /// or indices reference vertices missing from some vertex buffer.
+    pub fn weld_positions(&mut self, epsilon: f32) {
+        let positions = match self.read_attribute::<Position>() {
+            Some(positions) => positions,
+            None => return,
+        };
+        let len = self.vertex_count();
+        let indices = self.read_indices();
+        if let Some(indices) = &indices {
+            if indices.iter().any(|&index| index as usize >= len) {
+                log::warn!("Skipping position welding: indices are out of range");
+                return;
+            }
+        }
+
+        // Vertices within `epsilon` fall into the same or neighbouring grid cells.
+        let cell = |position: Position| {
+            let mut cell = [0i64; 3];
+            for (cell, &value) in cell.iter_mut().zip(&position.0) {
+                *cell = if epsilon > 0.0 {
+                    (value / epsilon).floor() as i64
+                } else {
+                    i64::from(value.to_bits())
+                };
+            }
+            cell
+        };
+        let mut grid = std::collections::HashMap::<[i64; 3], Vec<u32>>::new();
+        let mut kept = Vec::new();
+        let mut remap = Vec::with_capacity(len);
+        for (vertex, &position) in positions.iter().enumerate().take(len) {
+            let [x, y, z] = cell(position);
+            let mut found = None;
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let candidates = match grid.get(&[x + dx, y + dy, z + dz]) {
+                            Some(candidates) => candidates,
+                            None => continue,
+                        };
+                        for &candidate in candidates {
+                            let other = kept[candidate as usize];
+                            if self.vertices_match(vertex, other, epsilon) {
+                                found = Some(candidate);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+            let index = found.unwrap_or_else(|| {
+                let index = kept.len() as u32;
+                kept.push(vertex);
+                grid.entry([x, y, z]).or_default().push(index);
+                index
+            });
+            remap.push(index);
+        }
+
+        if kept.len() < len {
+            log::trace!("Welded {} vertices into {}", len, kept.len());
+        }
+        let order = kept.iter().map(|&vertex| vertex as u32).collect::<Vec<_>>();
+        self.gather_vertices(&order);
+        match indices {
+            Some(indices) => {
+                let indices = indices.iter().map(|&index| remap[index as usize]).collect();
+                self.write_indices(indices);
+            }
+            None => {
+                self.set_indices_auto(Indices::U32(Cow::Owned(remap)));
+            }
+        }
+    }
+
+    /// Hash of the primitive type, vertex buffers and indices, for detecting changed meshes.
+    ///
+    /// Vertex buffers are hashed in order of their formats, so the hash doesn't depend on
+    /// the order they were added in, and indices are hashed as `u32` regardless of their type.
+    /// Attributes are identified by name, array index, format and offset, and all lengths
+    /// and indices are hashed as little-endian integers of fixed size, so unlike the randomly
+    /// seeded default hasher the hash is the same across runs and builds and can be stored
+    /// in asset caches. Vertex data is hashed as stored, in native byte order.
+    pub fn content_hash(&self) -> u64 {
+        use {rendy_core::hal::pso::Primitive, std::hash::Hasher};
+
+        let mut hasher = Fnv1a::default();
+        hasher.write(&match self.prim {
+            Primitive::PointList => [0, 0],
+            Primitive::LineList => [1, 0],
+            Primitive::LineStrip => [2, 0],
+            Primitive::TriangleList => [3, 0],
+            Primitive::TriangleStrip => [4, 0],
+            Primitive::PatchList(size) => [5, size],
+        });
+
+        let mut buffers = self
+            .vertices
+            .iter()
+            .map(|RawVertices { vertices, format }| {
+                let mut attributes = format
+                    .attributes
+                    .iter()
+                    .map(|attribute| {
+                        let mut bytes = (attribute.name().len() as u64).to_le_bytes().to_vec();
+                        bytes.extend_from_slice(attribute.name().as_bytes());
+                        bytes.push(attribute.index());
+                        bytes.extend_from_slice(&(attribute.element().format as u32).to_le_bytes());
+                        bytes.extend_from_slice(&attribute.element().offset.to_le_bytes());
+                        bytes
+                    })
+                    .collect::<Vec<_>>();
+                attributes.sort();
+                let mut descriptor = format.stride.to_le_bytes().to_vec();
+                descriptor.extend_from_slice(&(attributes.len() as u64).to_le_bytes());
+                descriptor.extend(attributes.concat());
+                (descriptor, vertices)
+            })
+            .collect::<Vec<_>>();
+        buffers.sort_by(|a, b| a.0.cmp(&b.0));
+        hasher.write(&(buffers.len() as u64).to_le_bytes());
+        for (descriptor, vertices) in buffers {
+            hasher.write(&descriptor);
+            hasher.write(&(vertices.len() as u64).to_le_bytes());
+            hasher.write(vertices);
+        }
+
+        match self.read_indices() {
+            Some(indices) => {
+                hasher.write(&[1]);
+                hasher.write(&(indices.len() as u64).to_le_bytes());
+                for index in indices {
+                    hasher.write(&index.to_le_bytes());
+                }
+            }
+            None => hasher.write(&[0]),
+        }
+        hasher.finish()
+    }
+
+    /// Check that all vertex buffers have the same number of vertices,
+    /// all indices reference existing vertices and list topologies
+    /// have whole primitives, such as a multiple of three indices for triangles.
+    ///
+    /// `build` doesn't validate the builder, since the checks are linear in its size.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut expected = None;
+        for (buffer, raw) in self.vertices.iter().enumerate() {
+            let stride = raw.format.stride as usize;
+            if stride == 0 || raw.vertices.len() % stride != 0 {
+                return Err(ValidationError::PartialVertex { buffer });
+            }
+            let len = raw.vertices.len() / stride;
+            match expected {
+                None => expected = Some(len),
+                Some(expected) if expected != len => {
+                    return Err(ValidationError::VertexCountMismatch {
+                        buffer,
+                        len,
+                        expected,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        let len = expected.unwrap_or(0);
+        let indices = self.read_indices().unwrap_or_default();
+        if let Some(position) = indices.iter().position(|&index| index as usize >= len) {
+            return Err(ValidationError::IndexOutOfRange {
+                position,
+                index: indices[position],
+                len,
+            });
+        }
+
+        // Points and strips use any number of vertices.
+        let vertices_per_primitive = match self.prim {
+            rendy_core::hal::pso::Primitive::LineList => 2,
+            rendy_core::hal::pso::Primitive::TriangleList => 3,
+            rendy_core::hal::pso::Primitive::PatchList(size) => size.max(1) as usize,
+            _ => 1,
+        };
+        let len = self.index_count();
+        match len % vertices_per_primitive {
+            0 => Ok(()),
+            _ => Err(ValidationError::PartialPrimitive {
+                len,
+                vertices_per_primitive,
+            }),
+        }
+    }
+
+    /// Create a simplified copy of the mesh with about `target_ratio` of its triangles.
+    ///
+    /// Edges are collapsed in order of the quadric error metric until the target
+    /// is reached or no edge can be collapsed without damaging the surface.
+    /// Each collapse moves a vertex onto a neighbouring one, so remaining vertices keep
+    /// their positions, normals, texture coordinates and all other attributes.
+    /// Vertices with equal positions are treated as one, and seams between their
+    /// other attributes are only collapsed along themselves. Boundary vertices
+    /// only move along the boundary, so holes don't grow.
+    ///
+    /// Fails unless the mesh is a triangle list with positions where every edge
+    /// is shared by at most two triangles with opposite orientations.
+    /// Triangles referencing the same position twice are dropped.
+    pub fn simplify(&self, target_ratio: f32) -> Result<MeshBuilder<'static>, SimplifyError> {
+        if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+            return Err(SimplifyError::UnsupportedPrimitive(self.prim));
+        }
+        let positions =
+            self.read_attribute::<Position>()
+                .ok_or(SimplifyError::MissingAttribute {
+                    name: Position::NAME,
+                })?;
+        let len = self.vertex_count();
+        let indices = self
+            .read_indices()
+            .unwrap_or_else(|| (0..len as u32).collect());
+        if let Some(position) = indices.iter().position(|&index| index as usize >= len) {
+            return Err(SimplifyError::IndexOutOfRange {
+                position,
+                index: indices[position],
+                len,
+            });
+        }
+
+        let positions = positions[..len]
+            .iter()
+            .map(|position| position.0)
+            .collect::<Vec<_>>();
+        let ratio = target_ratio.clamp(0.0, 1.0);
+        let target = ((indices.len() / 3) as f32 * ratio).ceil() as usize;
+        let mut indices = crate::simplify::simplify(&positions, &indices, target)
+            .map_err(|edge| SimplifyError::NonManifold { edge })?;
+
+        let mut order = crate::optimize::optimize_vertex_fetch(&mut indices, len);
+        order.truncate(
+            indices
+                .iter()
+                .map(|&index| index as usize + 1)
+                .max()
+                .unwrap_or(0),
+        );
+        let mut builder = self.clone().into_owned();
+        builder.gather_vertices(&order);
+        if builder.indices.is_some() {
+            builder.write_indices(indices);
+        } else {
+            builder.set_indices_auto(Indices::U32(Cow::Owned(indices)));
+        }
+        Ok(builder)
+    }
+
+    /// Apply `levels` levels of subdivision to the mesh.
+    ///
+    /// Each level of `SubdivisionScheme::Loop` adds a vertex at the middle of every edge,
+    /// splits each triangle into four and moves positions by the Loop masks.
+    /// Vertices with equal positions are treated as one, so seams don't open.
+    /// Edges used by a single triangle follow the boundary rules, keeping holes in place.
+    /// Other attributes of new vertices are interpolated linearly between the ends
+    /// of their edge, so normals and tangents should be recomputed afterwards.
+    /// Attributes without 32-bit float components are copied from one end.
+    /// Meshes without indices get an index buffer, `u16` indices are widened to `u32`
+    /// when they no longer fit.
+    pub fn subdivide(
+        &mut self,
+        scheme: SubdivisionScheme,
+        levels: u32,
+    ) -> Result<&mut Self, SubdivisionError> {
+        match scheme {
+            SubdivisionScheme::Loop => {
+                if self.prim != rendy_core::hal::pso::Primitive::TriangleList {
+                    return Err(SubdivisionError::UnsupportedPrimitive(self.prim));
+                }
+            }
+        }
+        if self.find_attribute::<Position>().is_none() {
+            return Err(SubdivisionError::MissingAttribute {
+                name: Position::NAME,
+            });
+        }
+        let len = self.vertex_count();
+        if let Some(indices) = self.read_indices() {
+            if let Some(position) = indices.iter().position(|&index| index as usize >= len) {
+                return Err(SubdivisionError::IndexOutOfRange {
+                    position,
+                    index: indices[position],
+                    len,
+                });
+            }
+        }
+
+        for _ in 0..levels {
+            let len = self.vertex_count();
+            let positions = self.read_attribute::<Position>().unwrap()[..len]
+                .iter()
+                .map(|position| position.0)
+                .collect::<Vec<_>>();
+            let indices = self
+                .read_indices()
+                .unwrap_or_else(|| (0..len as u32).collect());
+            let subdivision = crate::subdivide::loop_subdivide(&positions, &indices);
+
+            self.append_midpoints(&subdivision.midpoints);
+            self.write_attribute(
+                subdivision
+                    .positions
+                    .into_iter()
+                    .map(Position)
+                    .collect::<Vec<_>>(),
+            );
+            if self.vertex_count() > u16::MAX as usize + 1 {
+                if let Some(rendy_core::hal::IndexType::U16) = self.index_type() {
+                    self.indices = None;
+                }
+            }
+            self.write_indices(subdivision.indices);
+        }
+        Ok(self)
+    }
+
+    /// Pack `Position`, `Normal`, `TexCoord` and `Tangent` attributes into a single buffer.
+    ///
+    /// Each vertex holds the attributes present in the mesh in that order, tightly packed.
+    /// Other attributes are not included. Returns the buffer and its vertex format.
+    pub fn interleaved(&self) -> (Vec<u8>, VertexFormat) {
+        type Source = (
+            usize,
+            usize,
+            usize,
+            (rendy_core::hal::format::Format, &'static str),
+        );
+
+        fn source<A: AsAttribute>(builder: &MeshBuilder<'_>) -> Option<Source> {
+            let (index, offset) = builder.find_attribute::<A>()?;
+            Some((index, offset, size_of::<A>(), (A::FORMAT, A::NAME)))
+        }
+
+        let sources = vec![
+            source::<Position>(self),
+            source::<Normal>(self),
+            source::<TexCoord>(self),
+            source::<Tangent>(self),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return (
+                Vec::new(),
+                VertexFormat::with_stride(Vec::<(_, &str)>::new(), 0),
+            );
+        }
+
+        let format = VertexFormat::new(
+            sources
+                .iter()
+                .map(|&(_, _, _, attribute)| attribute)
+                .collect::<Vec<_>>(),
+        );
+        let len = self.vertex_count();
+        let mut bytes = Vec::with_capacity(len * format.stride as usize);
+        for vertex in 0..len {
+            for &(index, offset, size, _) in &sources {
+                let RawVertices { vertices, format } = &self.vertices[index];
+                let start = vertex * format.stride as usize + offset;
+                bytes.extend_from_slice(&vertices[start..start + size]);
+            }
+        }
+        (bytes, format)
+    }
+
+    /// Re-encode a 32-bit float attribute as packed half floats.
+    ///
+    /// The attribute keeps its name and gets the 16-bit float format
+    /// with the same number of components, shrinking its vertex buffer.
+    /// Half floats keep about three decimal digits, which suits normals and
+    /// texture coordinates. Converted attributes are no longer recognized
+    /// by methods reading the standard attributes, such as `recompute_normals`.
+    ///
+    /// Fails if the attribute isn't present as 32-bit floats or a component
+    /// exceeds the half float range. Warns about positions too large
+    /// to be stored with integer precision.
+    pub fn convert_attribute_to_f16(
+        &mut self,
+        which: AttributeKind,
+    ) -> Result<&mut Self, HalfFloatError> {
+        use rendy_core::hal::format::Format;
+
+        let (name, format) = which.attribute();
+        let uuid = attribute_uuid(name, 0, format);
+        let (buffer, attribute) = self
+            .vertices
+            .iter()
+            .enumerate()
+            .find_map(|(buffer, raw)| {
+                raw.format
+                    .attributes
+                    .iter()
+                    .find(|attribute| attribute.uuid() == uuid)
+                    .map(|attribute| (buffer, attribute.clone()))
+            })
+            .ok_or(HalfFloatError::MissingAttribute { name })?;
+        let (components, half_format) = match format {
+            Format::Rg32Sfloat => (2, Format::Rg16Sfloat),
+            Format::Rgb32Sfloat => (3, Format::Rgb16Sfloat),
+            _ => (4, Format::Rgba16Sfloat),
+        };
+
+        let RawVertices { vertices, format } = &self.vertices[buffer];
+        let offset = attribute.element().offset as usize;
+        let size = components * size_of::<f32>();
+        let stride = format.stride as usize;
+        let mut largest = 0.0f32;
+        let mut encoded =
+            Vec::with_capacity(vertices.len() / stride * components * size_of::<half::f16>());
+        for vertex in vertices.chunks_exact(stride) {
+            for value in vertex[offset..offset + size].chunks_exact(size_of::<f32>()) {
+                let value = f32::from_ne_bytes([value[0], value[1], value[2], value[3]]);
+                if value.abs() > half::f16::MAX.to_f32() {
+                    return Err(HalfFloatError::OutOfRange { name, value });
+                }
+                largest = largest.max(value.abs());
+                encoded.extend_from_slice(&half::f16::from_f32(value).to_bits().to_ne_bytes());
+            }
+        }
+        if which == AttributeKind::Position && largest > 2048.0 {
+            log::warn!(
+                "Positions up to {} lose integer precision as half floats",
+                largest
+            );
+        }
+
+        self.replace_attribute(buffer, &attribute, half_format, &encoded);
+        Ok(self)
+    }
+
+    /// Quantize positions to normalized integers with `bits` bits per component.
+    ///
+    /// Each coordinate is mapped linearly from the bounding box of the positions
+    /// onto `0..=2^bits - 1` and rounded to the nearest step. Components are stored
+    /// as `Rgb8Unorm` for up to 8 bits and as `Rgb16Unorm` otherwise, so that the shader
+    /// reads them as floats in `0.0..=1.0`, and the returned parameters decode them.
+    /// Decoded positions are within half a step of the original ones.
+    ///
+    /// Quantized positions are no longer recognized by methods reading
+    /// the standard attributes, such as `bounding_box` or `recompute_normals`.
+    pub fn quantize_positions(
+        &mut self,
+        bits: u8,
+    ) -> Result<QuantizationParams, QuantizationError> {
+        use rendy_core::hal::format::Format;
+
+        if bits == 0 || bits > 16 {
+            return Err(QuantizationError::UnsupportedBits(bits));
+        }
+        let (name, format) = AttributeKind::Position.attribute();
+        let uuid = attribute_uuid(name, 0, format);
+        let (buffer, attribute) = self
+            .vertices
+            .iter()
+            .enumerate()
+            .find_map(|(buffer, raw)| {
+                raw.format
+                    .attributes
+                    .iter()
+                    .find(|attribute| attribute.uuid() == uuid)
+                    .map(|attribute| (buffer, attribute.clone()))
+            })
+            .ok_or(QuantizationError::MissingAttribute { name })?;
+
+        let positions = self.read_attribute::<Position>().unwrap_or_default();
+        let (min, max) = self.bounding_box().unwrap_or(([0.0; 3], [0.0; 3]));
+        let steps = ((1u32 << bits) - 1) as f32;
+        let mut step_size = [0.0; 3];
+        for axis in 0..3 {
+            step_size[axis] = (max[axis] - min[axis]) / steps;
+        }
+        let (quantized_format, format_steps) = if bits <= 8 {
+            (Format::Rgb8Unorm, u8::MAX as f32)
+        } else {
+            (Format::Rgb16Unorm, u16::MAX as f32)
+        };
+
+        let mut encoded =
+            Vec::with_capacity(positions.len() * quantized_format.surface_desc().bits as usize / 8);
+        for Position(position) in positions {
+            for axis in 0..3 {
+                let step = if step_size[axis] > 0.0 {
+                    ((position[axis] - min[axis]) / step_size[axis])
+                        .round()
+                        .max(0.0)
+                        .min(steps) as u16
+                } else {
+                    0
+                };
+                if bits <= 8 {
+                    encoded.push(step as u8);
+                } else {
+                    encoded.extend_from_slice(&step.to_ne_bytes());
+                }
+            }
+        }
+
+        self.replace_attribute(buffer, &attribute, quantized_format, &encoded);
+        Ok(QuantizationParams {
+            offset: min,
+            scale: [
+                step_size[0] * format_steps,
+                step_size[1] * format_steps,
+                step_size[2] * format_steps,
+            ],
+            format: quantized_format,
+        })
+    }
+
+    /// Replace `attribute` of the vertex buffer `buffer` with `encoded`,
+    /// which holds its value for every vertex in `format`.
+    /// The attribute keeps its name, and later attributes are moved to fit.
+    fn replace_attribute(
+        &mut self,
+        buffer: usize,
+        attribute: &Attribute,
+        format: rendy_core::hal::format::Format,
+        encoded: &[u8],
+    ) {
+        let RawVertices {
+            vertices,
+            format: vertex_format,
+        } = &self.vertices[buffer];
+        let offset = attribute.element().offset as usize;
+        let size = attribute.element().format.surface_desc().bits as usize / 8;
+        let encoded_size = format.surface_desc().bits as usize / 8;
+        let stride = vertex_format.stride as usize;
+        let new_stride = stride - size + encoded_size;
+        let mut converted = Vec::with_capacity(vertices.len() / stride * new_stride);
+        for (vertex, value) in vertices
+            .chunks_exact(stride)
+            .zip(encoded.chunks_exact(encoded_size))
+        {
+            converted.extend_from_slice(&vertex[..offset]);
+            converted.extend_from_slice(value);
+            converted.extend_from_slice(&vertex[offset + size..]);
+        }
+
+        let attributes = vertex_format
+            .attributes
+            .iter()
+            .map(|other| {
+                let element = other.element();
+                if other.uuid() == attribute.uuid() {
+                    Attribute::new(
+                        attribute.name().to_owned(),
+                        attribute.index(),
+                        rendy_core::hal::pso::Element {
+                            format,
+                            offset: element.offset,
+                        },
+                    )
+                } else if element.offset as usize > offset {
+                    Attribute::new(
+                        other.name().to_owned(),
+                        other.index(),
+                        rendy_core::hal::pso::Element {
+                            format: element.format,
+                            offset: (element.offset as usize + encoded_size - size) as u32,
+                        },
+                    )
+                } else {
+                    other.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        self.vertices[buffer] = RawVertices {
+            vertices: Cow::Owned(converted),
+            format: VertexFormat::with_stride(attributes, new_stride as u32),
+        };
+    }
+
+    /// Builds and returns the new mesh.
+    ///
+    /// A mesh expects all vertex buffers to have the same number of elements.
+    /// If those are not equal, the length of smallest vertex buffer is selected,
+    /// effectively discaring extra data from larger buffers.
+    ///
+    /// Note that contents of index buffer is not validated.
+    pub fn build<B>(&self, queue: QueueId, factory: &Factory<B>) -> Result<Mesh<B>, UploadError>
+    where
+        B: rendy_core::hal::Backend,
+    {
+        let align = factory.physical().limits().non_coherent_atom_size;
+        let mut len = self
+            .vertices
+            .iter()
+            .map(|v| v.vertices.len() as u32 / v.format.stride)
+            .min()
+            .unwrap_or(0);
+
+        let buffer_size = self
+            .vertices
+            .iter()
+            .map(|v| (v.format.stride * len) as usize)
+            .sum();
+
+        let aligned_size = align_by(align, buffer_size) as u64;
+
+        let mut staging = factory
+            .create_buffer(
+                BufferInfo {
+                    size: aligned_size,
+                    usage: rendy_core::hal::buffer::Usage::TRANSFER_SRC,
+                },
+                Upload,
+            )
+            .map_err(UploadError::Create)?;
+
+        let mut buffer = factory
+            .create_buffer(
+                BufferInfo {
+                    size: buffer_size as _,
+                    usage: rendy_core::hal::buffer::Usage::VERTEX
+                        | rendy_core::hal::buffer::Usage::TRANSFER_DST,
+                },
+                Data,
+            )
+            .map_err(UploadError::Create)?;
+
+        let mut mapped = staging
+            .map(factory, 0..aligned_size)
+            .map_err(UploadError::Map)?;
+        let mut writer =
+            unsafe { mapped.write(factory, 0..aligned_size) }.map_err(UploadError::Map)?;
+        let staging_slice = unsafe { writer.slice() };
+
+        let mut offset = 0usize;
+        let mut vertex_layouts: Vec<_> = self
+            .vertices
+            .iter()
+            .map(|RawVertices { vertices, format }| {
+                let size = (format.stride * len) as usize;
+                staging_slice[offset..offset + size].copy_from_slice(&vertices[0..size]);
+                let this_offset = offset as u64;
+                offset += size;
+                VertexBufferLayout {
+                    offset: this_offset,
+                    format: format.clone(),
+                }
             })
             .collect();
 
@@ -314,31 +2600,216 @@ impl<'a> MeshBuilder<'a> {
                     )?;
                 }
 
-                Some(IndexBuffer { buffer, index_type })
+                Some(IndexBuffer { buffer, index_type })
+            }
+        };
+
+        unsafe {
+            factory
+                .upload_from_staging_buffer(
+                    &mut buffer,
+                    0,
+                    staging,
+                    None,
+                    BufferState::new(queue)
+                        .with_access(rendy_core::hal::buffer::Access::VERTEX_BUFFER_READ)
+                        .with_stage(rendy_core::hal::pso::PipelineStage::VERTEX_INPUT),
+                )
+                .map_err(UploadError::Upload)?;
+        }
+
+        Ok(Mesh {
+            vertex_layouts,
+            index_buffer,
+            vertex_buffer: buffer,
+            prim: self.prim,
+            len,
+        })
+    }
+}
+
+impl<'a> MeshBuilder<'a> {
+    /// Find vertex buffer containing attribute `A`.
+    /// Returns index of the buffer and offset of the attribute within a vertex.
+    fn find_attribute<A: AsAttribute>(&self) -> Option<(usize, usize)> {
+        let uuid = attribute_uuid(A::NAME, 0, A::FORMAT);
+        self.vertices.iter().enumerate().find_map(|(index, raw)| {
+            raw.format
+                .attributes
+                .iter()
+                .find(|attribute| attribute.uuid() == uuid)
+                .map(|attribute| (index, attribute.element().offset as usize))
+        })
+    }
+
+    /// Check if all attributes of two vertices are equal,
+    /// allowing components of float attributes to differ by `epsilon`.
+    fn vertices_match(&self, a: usize, b: usize, epsilon: f32) -> bool {
+        use rendy_core::hal::format::Format;
+
+        self.vertices
+            .iter()
+            .all(|RawVertices { vertices, format }| {
+                let stride = format.stride as usize;
+                let (a, b) = (
+                    &vertices[a * stride..(a + 1) * stride],
+                    &vertices[b * stride..(b + 1) * stride],
+                );
+                format.attributes.iter().all(|attribute| {
+                    let element = attribute.element();
+                    let offset = element.offset as usize;
+                    let components = match element.format {
+                        Format::R32Sfloat => 1,
+                        Format::Rg32Sfloat => 2,
+                        Format::Rgb32Sfloat => 3,
+                        Format::Rgba32Sfloat => 4,
+                        format => {
+                            let size = format.surface_desc().bits as usize / 8;
+                            return a[offset..offset + size] == b[offset..offset + size];
+                        }
+                    };
+                    (0..components).all(|component| {
+                        let start = offset + component * size_of::<f32>();
+                        let value = |bytes: &[u8]| {
+                            f32::from_ne_bytes([
+                                bytes[start],
+                                bytes[start + 1],
+                                bytes[start + 2],
+                                bytes[start + 3],
+                            ])
+                        };
+                        (value(a) - value(b)).abs() <= epsilon
+                    })
+                })
+            })
+    }
+
+    /// Replace vertices of every vertex buffer with the vertices at `order`.
+    /// All indices in `order` must reference existing vertices.
+    fn gather_vertices(&mut self, order: &[u32]) {
+        for RawVertices { vertices, format } in self.vertices.iter_mut() {
+            let stride = format.stride as usize;
+            let mut gathered = Vec::with_capacity(order.len() * stride);
+            for &index in order {
+                let start = index as usize * stride;
+                gathered.extend_from_slice(&vertices[start..start + stride]);
+            }
+            *vertices = Cow::Owned(gathered);
+        }
+    }
+
+    /// Append a vertex for each pair of `pairs` to every vertex buffer, with components
+    /// of 32-bit float attributes averaged between the two vertices
+    /// and other attributes copied from the first one.
+    /// All indices in `pairs` must reference existing vertices.
+    fn append_midpoints(&mut self, pairs: &[[u32; 2]]) {
+        use rendy_core::hal::format::Format;
+
+        for RawVertices { vertices, format } in self.vertices.iter_mut() {
+            let stride = format.stride as usize;
+            let vertices = vertices.to_mut();
+            vertices.reserve(pairs.len() * stride);
+            for &[a, b] in pairs {
+                let (a, b) = (a as usize * stride, b as usize * stride);
+                let mut vertex = vertices[a..a + stride].to_vec();
+                for attribute in &format.attributes {
+                    let element = attribute.element();
+                    let components = match element.format {
+                        Format::R32Sfloat => 1,
+                        Format::Rg32Sfloat => 2,
+                        Format::Rgb32Sfloat => 3,
+                        Format::Rgba32Sfloat => 4,
+                        _ => continue,
+                    };
+                    for component in 0..components {
+                        let start = element.offset as usize + component * size_of::<f32>();
+                        let value = |vertex: usize| {
+                            let bytes = &vertices[vertex + start..vertex + start + 4];
+                            f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                        };
+                        let average = (value(a) + value(b)) / 2.0;
+                        vertex[start..start + 4].copy_from_slice(&average.to_ne_bytes());
+                    }
+                }
+                vertices.extend_from_slice(&vertex);
+            }
+        }
+    }
+
+    /// Replace all values of attribute `A`,
+    /// or add a vertex buffer with them if no buffer contains the attribute.
+    fn write_attribute<A: AsAttribute>(&mut self, values: Vec<A>) {
+        let (index, offset) = match self.find_attribute::<A>() {
+            Some(found) => found,
+            None => {
+                self.add_vertices(values);
+                return;
+            }
+        };
+        let RawVertices { vertices, format } = &mut self.vertices[index];
+        let stride = format.stride as usize;
+        for (vertex, value) in vertices.to_mut().chunks_exact_mut(stride).zip(&values) {
+            // Attributes are plain data, so their bytes can be copied directly.
+            let value: *const A = value;
+            let bytes = unsafe { std::slice::from_raw_parts(value.cast::<u8>(), size_of::<A>()) };
+            vertex[offset..offset + size_of::<A>()].copy_from_slice(bytes);
+        }
+    }
+
+    /// Read indices converted to `u32`.
+    /// Returns `None` if the mesh has no index buffer.
+    pub(crate) fn read_indices(&self) -> Option<Vec<u32>> {
+        let RawIndices {
+            indices,
+            index_type,
+        } = self.indices.as_ref()?;
+        Some(match index_type {
+            rendy_core::hal::IndexType::U16 => indices
+                .chunks_exact(size_of::<u16>())
+                .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]) as u32)
+                .collect(),
+            rendy_core::hal::IndexType::U32 => indices
+                .chunks_exact(size_of::<u32>())
+                .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect(),
+        })
+    }
+
+    /// Replace indices, keeping the current index type.
+    /// Indices must fit into that type.
+    fn write_indices(&mut self, indices: Vec<u32>) {
+        let index_type = match &self.indices {
+            Some(raw) => raw.index_type,
+            None => rendy_core::hal::IndexType::U32,
+        };
+        let indices = match index_type {
+            rendy_core::hal::IndexType::U16 => {
+                cast_vec(indices.into_iter().map(|i| i as u16).collect::<Vec<u16>>())
             }
+            rendy_core::hal::IndexType::U32 => cast_vec(indices),
         };
+        self.indices = Some(RawIndices {
+            indices: Cow::Owned(indices),
+            index_type,
+        });
+    }
 
-        unsafe {
-            factory
-                .upload_from_staging_buffer(
-                    &mut buffer,
-                    0,
-                    staging,
-                    None,
-                    BufferState::new(queue)
-                        .with_access(rendy_core::hal::buffer::Access::VERTEX_BUFFER_READ)
-                        .with_stage(rendy_core::hal::pso::PipelineStage::VERTEX_INPUT),
-                )
-                .map_err(UploadError::Upload)?;
-        }
-
-        Ok(Mesh {
-            vertex_layouts,
-            index_buffer,
-            vertex_buffer: buffer,
-            prim: self.prim,
-            len,
-        })
+    /// Read all values of attribute `A`.
+    /// Returns `None` if no vertex buffer contains the attribute.
+    pub(crate) fn read_attribute<A: AsAttribute>(&self) -> Option<Vec<A>> {
+        let (index, offset) = self.find_attribute::<A>()?;
+        let RawVertices { vertices, format } = &self.vertices[index];
+        let element = offset..offset + size_of::<A>();
+        Some(
+            vertices
+                .chunks_exact(format.stride as usize)
+                .map(|vertex| {
+                    let bytes = &vertex[element.clone()];
+                    // Attributes are plain data and the slice has exactly the size of `A`.
+                    unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const A) }
+                })
+                .collect(),
+        )
     }
 }
 
@@ -346,6 +2817,27 @@ fn align_by(align: usize, value: usize) -> usize {
     ((value + align - 1) / align) * align
 }
 
+/// 64-bit FNV-1a hasher, used where hashes must not change between runs.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::hash::Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
 /// Single mesh is a collection of buffer ranges that provides available attributes.
 /// Usually exactly one mesh is used per draw call.
 #[derive(Debug)]
@@ -575,3 +3067,1263 @@ macro_rules! impl_builder_from_vec {
 }
 
 impl_builder_from_vec!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PosNorm;
+    use rendy_core::hal::IndexType;
+
+    #[test]
+    fn test_set_indices_auto() {
+        let mut builder = MeshBuilder::new();
+        builder.set_indices_auto(vec![0u32, 1, 65535]);
+        assert_eq!(builder.index_type(), Some(IndexType::U16));
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 65535]));
+
+        builder.set_indices_auto(vec![0u32, 1, 65536]);
+        assert_eq!(builder.index_type(), Some(IndexType::U32));
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 65536]));
+    }
+
+    #[test]
+    fn test_set_indices_u16() {
+        let mut builder = MeshBuilder::new();
+        assert!(builder.set_indices_u16(&[2, 1, 0]).is_ok());
+        assert_eq!(builder.index_type(), Some(IndexType::U16));
+
+        assert_eq!(
+            builder.set_indices_u16(&[0, 70000]).err(),
+            Some(IndexOverflow {
+                position: 1,
+                index: 70000,
+            })
+        );
+        assert_eq!(builder.read_indices(), Some(vec![2, 1, 0]));
+    }
+
+    #[test]
+    fn test_extend_indices() {
+        let mut builder = MeshBuilder::new().with_indices(vec![0u16, 1, 2]);
+        builder.extend_indices(vec![2, 1, 3]);
+        assert_eq!(builder.index_type(), Some(IndexType::U16));
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 2, 2, 1, 3]));
+
+        builder.extend_indices(Some(70000));
+        assert_eq!(builder.index_type(), Some(IndexType::U32));
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 2, 2, 1, 3, 70000]));
+
+        let mut builder = MeshBuilder::new();
+        builder.extend_indices(0..3).extend_indices(3..6);
+        assert_eq!(builder.index_type(), Some(IndexType::U32));
+        assert_eq!(builder.read_indices(), Some((0..6).collect()));
+    }
+
+    fn quad(x: f32) -> MeshBuilder<'static> {
+        MeshBuilder::new()
+            .with_indices(vec![0u16, 1, 2, 2, 1, 3])
+            .with_vertices(vec![
+                Position([x, 0.0, 0.0]),
+                Position([x + 1.0, 0.0, 0.0]),
+                Position([x, 1.0, 0.0]),
+                Position([x + 1.0, 1.0, 0.0]),
+            ])
+    }
+
+    /// Cube centered at origin with 8 shared vertices and outward facing triangles.
+    fn cube() -> MeshBuilder<'static> {
+        let positions = (0..8)
+            .map(|i| {
+                let coordinate = |bit: u32| if i & (1 << bit) == 0 { -1.0 } else { 1.0 };
+                Position([coordinate(0), coordinate(1), coordinate(2)])
+            })
+            .collect::<Vec<_>>();
+        let quads = [
+            [1u16, 3, 7, 5],
+            [0, 4, 6, 2],
+            [2, 6, 7, 3],
+            [0, 1, 5, 4],
+            [4, 5, 7, 6],
+            [0, 2, 3, 1],
+        ];
+        let indices = quads
+            .iter()
+            .flat_map(|q| vec![q[0], q[1], q[2], q[0], q[2], q[3]])
+            .collect::<Vec<_>>();
+        MeshBuilder::new()
+            .with_indices(indices)
+            .with_vertices(positions)
+    }
+
+    #[test]
+    fn test_recompute_normals_smooth() {
+        let mut cube = cube();
+        cube.recompute_normals(NormalSmoothing::Smooth);
+        assert_eq!(cube.read_indices().unwrap().len(), 36);
+        let positions = cube.read_attribute::<Position>().unwrap();
+        let normals = cube.read_attribute::<Normal>().unwrap();
+        assert_eq!(normals.len(), 8);
+        for (position, normal) in positions.iter().zip(&normals) {
+            let length = normal.0.iter().map(|c| c * c).sum::<f32>().sqrt();
+            assert!((length - 1.0).abs() < 1e-6);
+            // Each component points away from the center, like the corner itself.
+            for i in 0..3 {
+                assert!(normal.0[i] * position.0[i] > 0.0, "{:?}", normal);
+            }
+        }
+    }
+
+    #[test]
+    fn test_recompute_normals_flat() {
+        let mut cube = cube();
+        cube.recompute_normals(NormalSmoothing::Flat);
+        assert_eq!(cube.read_indices(), None);
+        assert_eq!(cube.validate(), Ok(()));
+        let positions = cube.read_attribute::<Position>().unwrap();
+        let normals = cube.read_attribute::<Normal>().unwrap();
+        assert_eq!(normals.len(), 36);
+        for (triangle, normals) in positions.chunks_exact(3).zip(normals.chunks_exact(3)) {
+            let normal = normals[0];
+            assert!(normals.iter().all(|&n| n == normal));
+            let axis = normal.0.iter().position(|c| c.abs() == 1.0).unwrap();
+            assert_eq!(normal.0.iter().filter(|&&c| c == 0.0).count(), 2);
+            assert!(triangle.iter().all(|p| p.0[axis] == normal.0[axis]));
+        }
+
+        // Existing interleaved normals are overwritten in place.
+        let mut builder = MeshBuilder::new().with_vertices(vec![
+            PosNorm {
+                position: Position([0.0, 0.0, 0.0]),
+                normal: Normal([1.0, 0.0, 0.0]),
+            },
+            PosNorm {
+                position: Position([1.0, 0.0, 0.0]),
+                normal: Normal([1.0, 0.0, 0.0]),
+            },
+            PosNorm {
+                position: Position([0.0, 1.0, 0.0]),
+                normal: Normal([1.0, 0.0, 0.0]),
+            },
+        ]);
+        builder.recompute_normals(NormalSmoothing::Flat);
+        assert_eq!(
+            builder.read_attribute::<Normal>(),
+            Some(vec![Normal([0.0, 0.0, 1.0]); 3])
+        );
+        assert_eq!(builder.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_recompute_tangents() {
+        // Plane in the xy plane facing +z, with the texture mirrored horizontally
+        // on the right half so the handedness flips.
+        let positions = vec![
+            Position([0.0, 0.0, 0.0]),
+            Position([1.0, 0.0, 0.0]),
+            Position([0.0, 1.0, 0.0]),
+            Position([1.0, 1.0, 0.0]),
+            Position([2.0, 0.0, 0.0]),
+            Position([2.0, 1.0, 0.0]),
+        ];
+        let tex_coords = vec![
+            TexCoord([0.0, 0.0]),
+            TexCoord([1.0, 0.0]),
+            TexCoord([0.0, 1.0]),
+            TexCoord([1.0, 1.0]),
+            TexCoord([0.0, 0.0]),
+            TexCoord([0.0, 1.0]),
+        ];
+        let mut plane = MeshBuilder::new()
+            .with_indices(vec![0u16, 1, 2, 2, 1, 3, 4, 5, 1, 1, 5, 3])
+            .with_vertices(positions)
+            .with_vertices(vec![Normal([0.0, 0.0, 1.0]); 6])
+            .with_vertices(tex_coords);
+
+        plane.recompute_tangents().unwrap();
+        let tangents = plane.read_attribute::<Tangent>().unwrap();
+        assert_eq!(tangents[0], Tangent([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(tangents[2], Tangent([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(tangents[4], Tangent([-1.0, 0.0, 0.0, -1.0]));
+        assert_eq!(tangents[5], Tangent([-1.0, 0.0, 0.0, -1.0]));
+        assert_eq!(plane.validate(), Ok(()));
+
+        // Recomputing replaces the existing tangents.
+        plane.recompute_tangents().unwrap();
+        assert_eq!(plane.read_attribute::<Tangent>(), Some(tangents));
+
+        let mut untextured = quad(0.0).with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4]);
+        assert_eq!(
+            untextured.recompute_tangents().err(),
+            Some(TangentError::MissingAttribute { name: "tex_coord" })
+        );
+    }
+
+    /// Cube from -1 to 1 with each face split into a grid of `n` by `n` quads.
+    /// Every face has vertices of its own with the face normal.
+    fn grid_cube(n: usize) -> MeshBuilder<'static> {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for axis in 0..3 {
+            for &side in &[-1.0f32, 1.0] {
+                let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                let (u, v) = if side > 0.0 { (u, v) } else { (v, u) };
+                let base = vertices.len() as u32;
+                for j in 0..=n {
+                    for i in 0..=n {
+                        let mut position = [0.0; 3];
+                        position[axis] = side;
+                        position[u] = i as f32 / n as f32 * 2.0 - 1.0;
+                        position[v] = j as f32 / n as f32 * 2.0 - 1.0;
+                        let mut normal = [0.0; 3];
+                        normal[axis] = side;
+                        vertices.push(PosNorm {
+                            position: Position(position),
+                            normal: Normal(normal),
+                        });
+                    }
+                }
+                let row = n as u32 + 1;
+                for j in 0..n as u32 {
+                    for i in 0..n as u32 {
+                        let corner = base + j * row + i;
+                        indices.extend_from_slice(&[
+                            corner,
+                            corner + 1,
+                            corner + row + 1,
+                            corner,
+                            corner + row + 1,
+                            corner + row,
+                        ]);
+                    }
+                }
+            }
+        }
+        MeshBuilder::new()
+            .with_vertices(vertices)
+            .with_indices(indices)
+    }
+
+    #[test]
+    fn test_simplify() {
+        let cube = grid_cube(3);
+        assert_eq!(cube.read_indices().unwrap().len(), 6 * 18 * 3);
+        let simplified = cube.simplify(0.25).unwrap();
+        let triangles = simplified.read_indices().unwrap().len() / 3;
+        assert!(triangles <= 27, "{} triangles", triangles);
+        assert_eq!(simplified.bounding_box(), cube.bounding_box());
+        assert_eq!(simplified.validate(), Ok(()));
+        assert_eq!(simplified.index_type(), cube.index_type());
+
+        // Faces stay flat, with their own normals.
+        let positions = simplified.read_attribute::<Position>().unwrap();
+        let normals = simplified.read_attribute::<Normal>().unwrap();
+        for triangle in simplified.read_indices().unwrap().chunks_exact(3) {
+            let normal = normals[triangle[0] as usize];
+            for &index in triangle {
+                assert_eq!(normals[index as usize], normal);
+                let along = (0..3)
+                    .map(|i| positions[index as usize].0[i] * normal.0[i])
+                    .sum::<f32>();
+                assert_eq!(along, 1.0);
+            }
+        }
+
+        // Only the corners of each face are needed.
+        let minimal = cube.simplify(0.0).unwrap();
+        assert_eq!(minimal.read_indices().unwrap().len(), 12 * 3);
+        assert_eq!(minimal.vertex_count(), 24);
+        assert_eq!(minimal.bounding_box(), cube.bounding_box());
+
+        // Less simplification keeps more triangles.
+        let ratio = cube.simplify(0.75).unwrap().read_indices().unwrap().len() / 3;
+        assert!(ratio > triangles);
+        assert!(ratio <= 6 * 18 * 3 / 4 + 1);
+        assert_eq!(
+            cube.simplify(1.0).unwrap().read_indices().unwrap().len(),
+            6 * 18 * 3
+        );
+    }
+
+    #[test]
+    fn test_subdivide() {
+        let positions = vec![
+            Position([1.0, 1.0, 1.0]),
+            Position([1.0, -1.0, -1.0]),
+            Position([-1.0, 1.0, -1.0]),
+            Position([-1.0, -1.0, 1.0]),
+        ];
+        let mut tetrahedron = MeshBuilder::new()
+            .with_indices(vec![0u16, 1, 2, 0, 3, 1, 0, 2, 3, 1, 3, 2])
+            .with_vertices(positions.clone());
+        tetrahedron.subdivide(SubdivisionScheme::Loop, 1).unwrap();
+        assert_eq!(tetrahedron.triangle_count(), 16);
+        assert_eq!(tetrahedron.vertex_count(), 10);
+        assert_eq!(tetrahedron.index_type(), Some(IndexType::U16));
+        assert_eq!(tetrahedron.validate(), Ok(()));
+        // Each original vertex keeps 1 - 3 * 3/16 of itself, its neighbours sum to minus itself.
+        let smoothed = tetrahedron.read_attribute::<Position>().unwrap();
+        for (original, smoothed) in positions.iter().zip(&smoothed) {
+            for i in 0..3 {
+                assert!((smoothed.0[i] - original.0[i] * 0.25).abs() < 1e-6);
+            }
+        }
+        // Edge vertices are 3/8 of each end plus 1/8 of each opposite vertex.
+        let edge = smoothed[4].0;
+        assert!((edge[0] - 0.5).abs() < 1e-6 && edge[1].abs() < 1e-6 && edge[2].abs() < 1e-6);
+
+        tetrahedron.subdivide(SubdivisionScheme::Loop, 2).unwrap();
+        assert_eq!(tetrahedron.triangle_count(), 256);
+
+        let mut triangle = MeshBuilder::new()
+            .with_vertices(vec![
+                Position([0.0, 0.0, 0.0]),
+                Position([4.0, 0.0, 0.0]),
+                Position([0.0, 4.0, 0.0]),
+            ])
+            .with_vertices(vec![
+                TexCoord([0.0, 0.0]),
+                TexCoord([1.0, 0.0]),
+                TexCoord([0.0, 1.0]),
+            ]);
+        triangle.subdivide(SubdivisionScheme::Loop, 1).unwrap();
+        assert_eq!(triangle.triangle_count(), 4);
+        assert_eq!(triangle.index_type(), Some(IndexType::U32));
+        let positions = triangle.read_attribute::<Position>().unwrap();
+        assert_eq!(positions[0], Position([0.5, 0.5, 0.0]));
+        assert_eq!(positions[3], Position([2.0, 0.0, 0.0]));
+        assert_eq!(
+            triangle.read_attribute::<TexCoord>().unwrap()[3],
+            TexCoord([0.5, 0.0])
+        );
+
+        let mut lines = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert!(lines.subdivide(SubdivisionScheme::Loop, 1).is_err());
+    }
+
+    #[test]
+    fn test_simplify_errors() {
+        let fin = MeshBuilder::new()
+            .with_vertices(vec![
+                Position([0.0, 0.0, 0.0]),
+                Position([1.0, 0.0, 0.0]),
+                Position([0.0, 1.0, 0.0]),
+                Position([0.0, -1.0, 0.0]),
+                Position([0.0, 0.0, 1.0]),
+            ])
+            .with_indices(vec![0u32, 1, 2, 1, 0, 3, 0, 1, 4]);
+        assert_eq!(
+            fin.simplify(0.5).unwrap_err(),
+            SimplifyError::NonManifold { edge: [0, 1] }
+        );
+        assert_eq!(
+            MeshBuilder::new()
+                .with_vertices(vec![TexCoord([0.0, 0.0]); 3])
+                .simplify(0.5)
+                .unwrap_err(),
+            SimplifyError::MissingAttribute { name: "position" }
+        );
+    }
+
+    #[test]
+    fn test_interleaved() {
+        let positions = vec![
+            PosNorm {
+                position: Position([0.0, 1.0, 2.0]),
+                normal: Normal([0.0, 0.0, 1.0]),
+            },
+            PosNorm {
+                position: Position([3.0, 4.0, 5.0]),
+                normal: Normal([1.0, 0.0, 0.0]),
+            },
+        ];
+        let tex_coords = vec![TexCoord([0.25, 0.5]), TexCoord([0.75, 1.0])];
+        let builder = MeshBuilder::new()
+            .with_vertices(tex_coords.clone())
+            .with_vertices(positions.clone());
+        let (bytes, format) = builder.interleaved();
+        assert_eq!(format.stride, 32);
+        assert_eq!(bytes.len(), 64);
+
+        let offset = |name: &str| {
+            format
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name() == name)
+                .map(|attribute| attribute.element().offset as usize)
+                .unwrap()
+        };
+        assert_eq!(offset("position"), 0);
+        assert_eq!(offset("normal"), 12);
+        assert_eq!(offset("tex_coord"), 24);
+        let floats = |start: usize, count: usize| {
+            (0..count)
+                .map(|i| {
+                    let at = start + i * 4;
+                    f32::from_ne_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+                })
+                .collect::<Vec<_>>()
+        };
+        for (vertex, (pos_norm, tex_coord)) in positions.iter().zip(&tex_coords).enumerate() {
+            let start = vertex * format.stride as usize;
+            assert_eq!(floats(start, 3), pos_norm.position.0);
+            assert_eq!(floats(start + 12, 3), pos_norm.normal.0);
+            assert_eq!(floats(start + 24, 2), tex_coord.0);
+        }
+
+        let (bytes, format) = MeshBuilder::new().interleaved();
+        assert!(bytes.is_empty());
+        assert_eq!(format.stride, 0);
+    }
+
+    #[test]
+    fn test_convert_attribute_to_f16() {
+        let tex_coords = vec![
+            TexCoord([0.0, 1.0]),
+            TexCoord([0.1234, 0.5678]),
+            TexCoord([0.999, 3.25]),
+        ];
+        let normals = vec![
+            Normal([0.0, 0.0, 1.0]),
+            Normal([0.6, 0.8, 0.0]),
+            Normal([1.0, 0.0, 0.0]),
+        ];
+        let pos_norm = normals
+            .iter()
+            .map(|&normal| PosNorm {
+                position: Position([1.0, 2.0, 3.0]),
+                normal,
+            })
+            .collect::<Vec<_>>();
+        let mut builder = MeshBuilder::new()
+            .with_vertices(tex_coords.clone())
+            .with_vertices(pos_norm);
+        builder
+            .convert_attribute_to_f16(AttributeKind::TexCoord)
+            .unwrap()
+            .convert_attribute_to_f16(AttributeKind::Position)
+            .unwrap();
+
+        let decode = |buffer: usize, name: &str, len: usize| {
+            let RawVertices { vertices, format } = &builder.vertices[buffer];
+            let offset = format
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name() == name)
+                .map(|attribute| attribute.element().offset as usize)
+                .unwrap();
+            vertices
+                .chunks_exact(format.stride as usize)
+                .map(|vertex| {
+                    (0..len)
+                        .map(|i| {
+                            let at = offset + i * 2;
+                            half::f16::from_bits(u16::from_ne_bytes([vertex[at], vertex[at + 1]]))
+                                .to_f32()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(builder.vertices[0].format.stride, 4);
+        for (decoded, tex_coord) in decode(0, "tex_coord", 2).iter().zip(&tex_coords) {
+            for (decoded, original) in decoded.iter().zip(&tex_coord.0) {
+                assert!((decoded - original).abs() <= original.abs() / 1024.0);
+            }
+        }
+
+        // Normals after the converted positions are moved to keep the buffer packed.
+        assert_eq!(builder.vertices[1].format.stride, 18);
+        assert_eq!(builder.read_attribute::<Normal>(), Some(normals));
+        assert_eq!(builder.read_attribute::<Position>(), None);
+        assert_eq!(decode(1, "position", 3)[0], [1.0, 2.0, 3.0]);
+
+        assert_eq!(
+            builder
+                .convert_attribute_to_f16(AttributeKind::TexCoord)
+                .unwrap_err(),
+            HalfFloatError::MissingAttribute { name: "tex_coord" }
+        );
+        let mut far = MeshBuilder::new().with_vertices(vec![Position([1e5, 0.0, 0.0])]);
+        assert_eq!(
+            far.convert_attribute_to_f16(AttributeKind::Position)
+                .unwrap_err(),
+            HalfFloatError::OutOfRange {
+                name: "position",
+                value: 1e5,
+            }
+        );
+    }
+
+    #[test]
+    fn test_quantize_positions() {
+        let positions = (0..50)
+            .map(|i| {
+                let t = i as f32 * 0.37;
+                Position([t.sin() * 3.0 - 1.0, t.cos() * 0.25, t * 0.5 + 100.0])
+            })
+            .collect::<Vec<_>>();
+        let normals = vec![Normal([0.0, 1.0, 0.0]); positions.len()];
+        let pos_norm = positions
+            .iter()
+            .zip(&normals)
+            .map(|(&position, &normal)| PosNorm { position, normal })
+            .collect::<Vec<_>>();
+        let mut builder = MeshBuilder::new().with_vertices(pos_norm);
+        let mut five_bits = builder.clone();
+        let params = builder.quantize_positions(16).unwrap();
+        assert_eq!(params.format, rendy_core::hal::format::Format::Rgb16Unorm);
+        assert_eq!(builder.read_attribute::<Position>(), None);
+        assert_eq!(builder.read_attribute::<Normal>(), Some(normals));
+        assert_eq!(builder.vertices[0].format.stride, 18);
+
+        let RawVertices { vertices, format } = &builder.vertices[0];
+        for (vertex, Position(original)) in vertices
+            .chunks_exact(format.stride as usize)
+            .zip(&positions)
+        {
+            for axis in 0..3 {
+                let step = u16::from_ne_bytes([vertex[axis * 2], vertex[axis * 2 + 1]]);
+                let normalized = step as f32 / u16::MAX as f32;
+                let decoded = params.offset[axis] + params.scale[axis] * normalized;
+                let bound =
+                    params.scale[axis] / u16::MAX as f32 / 2.0 + original[axis].abs() * 1e-6;
+                assert!((decoded - original[axis]).abs() <= bound);
+            }
+        }
+
+        // Fewer bits than the format has leave the upper steps unused.
+        let params = five_bits.quantize_positions(5).unwrap();
+        assert_eq!(params.format, rendy_core::hal::format::Format::Rgb8Unorm);
+        let RawVertices { vertices, format } = &five_bits.vertices[0];
+        for (vertex, Position(original)) in vertices
+            .chunks_exact(format.stride as usize)
+            .zip(&positions)
+        {
+            for axis in 0..3 {
+                assert!(vertex[axis] <= 31);
+                let normalized = vertex[axis] as f32 / u8::MAX as f32;
+                let decoded = params.offset[axis] + params.scale[axis] * normalized;
+                let step_size = params.scale[axis] / u8::MAX as f32;
+                let bound = step_size / 2.0 + original[axis].abs() * 1e-6;
+                assert!((decoded - original[axis]).abs() <= bound);
+            }
+        }
+
+        let mut flat = MeshBuilder::new().with_vertices(vec![Position([1.0, 2.0, 3.0]); 2]);
+        let params = flat.quantize_positions(8).unwrap();
+        assert_eq!(params.offset, [1.0, 2.0, 3.0]);
+        assert_eq!(params.scale, [0.0; 3]);
+        assert_eq!(&*flat.vertices[0].vertices, &[0u8; 6][..]);
+        assert_eq!(
+            flat.quantize_positions(8).unwrap_err(),
+            QuantizationError::MissingAttribute { name: "position" }
+        );
+        assert_eq!(
+            builder.quantize_positions(17).unwrap_err(),
+            QuantizationError::UnsupportedBits(17)
+        );
+    }
+
+    #[test]
+    fn test_weld_positions() {
+        // Two triangles sharing an edge whose vertices were exported slightly apart.
+        let triangles = || {
+            MeshBuilder::new().with_vertices(vec![
+                Position([0.0, 0.0, 0.0]),
+                Position([1.0, 0.0, 0.0]),
+                Position([0.0, 1.0, 0.0]),
+                Position([1.0, 1e-6, 0.0]),
+                Position([1.0, 1.0, 0.0]),
+                Position([0.0, 1.0 - 1e-6, 0.0]),
+            ])
+        };
+        let mut builder = triangles();
+        builder.weld_positions(1e-5);
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 2, 1, 3, 2]));
+        assert_eq!(
+            builder.read_attribute::<Position>(),
+            Some(vec![
+                Position([0.0, 0.0, 0.0]),
+                Position([1.0, 0.0, 0.0]),
+                Position([0.0, 1.0, 0.0]),
+                Position([1.0, 1.0, 0.0]),
+            ])
+        );
+        assert_eq!(builder.validate(), Ok(()));
+
+        // Too far apart for the tolerance.
+        let mut builder = triangles();
+        builder.weld_positions(1e-7);
+        assert_eq!(builder.read_indices(), Some((0..6).collect()));
+
+        // Differing texture coordinates keep the seam.
+        let mut builder = triangles().with_vertices(vec![
+            TexCoord([0.0, 0.0]),
+            TexCoord([1.0, 0.0]),
+            TexCoord([0.0, 1.0]),
+            TexCoord([0.0, 0.0]),
+            TexCoord([1.0, 1.0]),
+            TexCoord([0.0, 1.0]),
+        ]);
+        builder.weld_positions(1e-5);
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 2, 3, 4, 2]));
+        assert_eq!(builder.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_attribute_accessors() {
+        let builder = quad(2.0);
+        let positions = builder.positions().unwrap();
+        assert!(matches!(positions, Cow::Borrowed(_)));
+        assert_eq!(
+            &*positions,
+            &[
+                Position([2.0, 0.0, 0.0]),
+                Position([3.0, 0.0, 0.0]),
+                Position([2.0, 1.0, 0.0]),
+                Position([3.0, 1.0, 0.0]),
+            ]
+        );
+        assert_eq!(builder.normals(), None);
+        assert_eq!(builder.colors(), None);
+
+        // Interleaved attributes are copied out.
+        let builder = MeshBuilder::new().with_vertices(vec![PosNorm {
+            position: Position([1.0, 2.0, 3.0]),
+            normal: Normal([0.0, 1.0, 0.0]),
+        }]);
+        let normals = builder.normals().unwrap();
+        assert!(matches!(normals, Cow::Owned(_)));
+        assert_eq!(&*normals, &[Normal([0.0, 1.0, 0.0])]);
+        assert_eq!(&*builder.positions().unwrap(), &[Position([1.0, 2.0, 3.0])]);
+    }
+
+    #[test]
+    fn test_flip_winding() {
+        let mut builder = quad(1.0)
+            .with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4])
+            .with_vertices(vec![Tangent([1.0, 0.0, 0.0, 1.0]); 4]);
+        let indices = builder.read_indices().unwrap();
+        builder.flip_winding(false);
+        let flipped = builder.read_indices().unwrap();
+        for (triangle, flipped) in indices.chunks_exact(3).zip(flipped.chunks_exact(3)) {
+            assert_eq!(flipped, [triangle[0], triangle[2], triangle[1]]);
+        }
+        let normals = builder.read_attribute::<Normal>().unwrap();
+
+        builder.flip_winding(true);
+        assert_eq!(builder.read_indices(), Some(indices));
+        for (normal, negated) in normals
+            .iter()
+            .zip(builder.read_attribute::<Normal>().unwrap())
+        {
+            assert_eq!(negated.0, [-normal.0[0], -normal.0[1], -normal.0[2]]);
+        }
+        assert_eq!(
+            builder.read_attribute::<Tangent>().unwrap()[0],
+            Tangent([1.0, 0.0, 0.0, -1.0])
+        );
+
+        let positions = vec![
+            Position([0.0, 0.0, 0.0]),
+            Position([1.0, 0.0, 0.0]),
+            Position([0.0, 1.0, 0.0]),
+        ];
+        let mut unindexed = MeshBuilder::new().with_vertices(positions.clone());
+        unindexed.flip_winding(false);
+        assert_eq!(
+            unindexed.read_attribute::<Position>(),
+            Some(vec![positions[0], positions[2], positions[1]])
+        );
+    }
+
+    #[test]
+    fn test_unify_winding() {
+        let expected = cube().read_indices().unwrap();
+        let mut reversed = expected.clone();
+        reversed[15..18].swap(1, 2);
+        let mut builder = cube().with_indices(reversed);
+        builder.unify_winding().unwrap();
+        assert_eq!(builder.read_indices(), Some(expected));
+
+        // Strip of three quads with its ends joined after a half twist.
+        let mobius = vec![0u32, 3, 4, 0, 4, 1, 1, 4, 5, 1, 5, 2, 2, 5, 0, 2, 0, 3];
+        let mut builder = MeshBuilder::new().with_indices(mobius.clone());
+        assert!(matches!(
+            builder.unify_winding(),
+            Err(WindingError::NonOrientable { .. })
+        ));
+        assert_eq!(builder.read_indices(), Some(mobius));
+    }
+
+    #[test]
+    fn test_map_attributes() {
+        let mut builder = quad(0.0).with_vertices(vec![
+            TexCoord([0.0, 0.0]),
+            TexCoord([1.0, 0.0]),
+            TexCoord([0.0, 1.0]),
+            TexCoord([1.0, 1.0]),
+        ]);
+        builder.map_positions(|[x, y, z]| [x + 1.0, y, z]);
+        assert_eq!(
+            builder.bounding_box(),
+            Some(([1.0, 0.0, 0.0], [2.0, 1.0, 0.0]))
+        );
+        assert_eq!(builder.read_indices(), quad(0.0).read_indices());
+
+        builder.map_tex_coords(|[u, v]| [u * 0.5 + 0.5, v * 0.5]);
+        assert_eq!(
+            builder.read_attribute::<TexCoord>().unwrap(),
+            [
+                TexCoord([0.5, 0.0]),
+                TexCoord([1.0, 0.0]),
+                TexCoord([0.5, 0.5]),
+                TexCoord([1.0, 0.5]),
+            ]
+        );
+
+        let mut positions_only = quad(0.0);
+        positions_only.map_tex_coords(|_| unreachable!());
+        assert_eq!(positions_only.read_attribute::<TexCoord>(), None);
+    }
+
+    #[test]
+    fn test_prune_unused_vertices() {
+        // Vertex 1 is dangling and vertex 4 is past the last used one.
+        let mut builder = MeshBuilder::new()
+            .with_indices(vec![0u16, 2, 3, 3, 2, 5])
+            .with_vertices(
+                (0..6)
+                    .map(|i| Position([i as f32, 0.0, 0.0]))
+                    .collect::<Vec<_>>(),
+            )
+            .with_vertices(
+                (0..6)
+                    .map(|i| TexCoord([0.0, i as f32]))
+                    .collect::<Vec<_>>(),
+            );
+        builder.prune_unused_vertices();
+        assert_eq!(builder.validate(), Ok(()));
+        assert_eq!(builder.vertex_count(), 4);
+        assert_eq!(builder.index_type(), Some(IndexType::U16));
+        assert_eq!(builder.read_indices(), Some(vec![0, 1, 2, 2, 1, 3]));
+        let kept = [0.0, 2.0, 3.0, 5.0];
+        assert_eq!(
+            builder.read_attribute::<Position>(),
+            Some(kept.iter().map(|&x| Position([x, 0.0, 0.0])).collect())
+        );
+        assert_eq!(
+            builder.read_attribute::<TexCoord>(),
+            Some(kept.iter().map(|&v| TexCoord([0.0, v])).collect())
+        );
+
+        let mut unindexed = MeshBuilder::from(vec![Position([0.0; 3]); 3]);
+        unindexed.prune_unused_vertices();
+        assert_eq!(unindexed.vertex_count(), 3);
+    }
+
+    #[test]
+    fn test_split_connected_components() {
+        // Two disjoint triangles, the second listed before the first, and an unused vertex.
+        let builder = MeshBuilder::new()
+            .with_indices(vec![3u16, 4, 5, 0, 1, 2])
+            .with_vertices(
+                (0..7)
+                    .map(|i| Position([i as f32, 0.0, 0.0]))
+                    .collect::<Vec<_>>(),
+            );
+        let components = builder.split_connected_components();
+        assert_eq!(components.len(), 2);
+        for (component, first) in components.iter().zip(&[3.0, 0.0]) {
+            assert_eq!(component.validate(), Ok(()));
+            assert_eq!(component.index_type(), Some(IndexType::U16));
+            assert_eq!(component.read_indices(), Some(vec![0, 1, 2]));
+            assert_eq!(
+                component.read_attribute::<Position>(),
+                Some(
+                    (0..3)
+                        .map(|i| Position([first + i as f32, 0.0, 0.0]))
+                        .collect()
+                )
+            );
+        }
+
+        // Sharing a single vertex connects the triangles.
+        let connected = quad(0.0).split_connected_components();
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].read_indices(), quad(0.0).read_indices());
+
+        // Without indices every triangle has vertices of its own.
+        let unindexed = quad(0.0).into_unindexed().split_connected_components();
+        assert_eq!(unindexed.len(), 2);
+        assert!(unindexed
+            .iter()
+            .all(|component| component.read_indices().is_none() && component.vertex_count() == 3));
+    }
+
+    #[test]
+    fn test_vertex_layout() {
+        let builder = quad(0.0)
+            .with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4])
+            .with_vertices(vec![TexCoord([0.0, 0.0]); 4]);
+        let layout = builder.vertex_layout();
+        let attributes = layout
+            .attributes
+            .iter()
+            .map(|attribute| {
+                (
+                    attribute.name.as_str(),
+                    attribute.format,
+                    attribute.buffer,
+                    attribute.stride,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attributes,
+            [
+                (Position::NAME, Position::FORMAT, 0, 12),
+                (Normal::NAME, Normal::FORMAT, 1, 12),
+                (TexCoord::NAME, TexCoord::FORMAT, 2, 8),
+            ]
+        );
+
+        let interleaved = MeshBuilder::new().with_vertices(vec![PosNorm {
+            position: Position([0.0; 3]),
+            normal: Normal([0.0; 3]),
+        }]);
+        let layout = interleaved.vertex_layout();
+        assert_eq!(layout.attributes.len(), 2);
+        assert_eq!(layout.attributes[1].offset, 12);
+        assert_eq!(layout.attributes[1].stride, 24);
+        assert_eq!(MeshBuilder::new().vertex_layout(), VertexLayout::default());
+    }
+
+    #[test]
+    fn test_uv_seam_edges() {
+        // Cube with the four sides unwrapped into a strip and the caps into separate charts.
+        let ring = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+        let mut positions = Vec::new();
+        let mut tex_coords = Vec::new();
+        for side in 0..4 {
+            let [x0, y0] = ring[side];
+            let [x1, y1] = ring[(side + 1) % 4];
+            let (u0, u1) = (side as f32 / 4.0, (side + 1) as f32 / 4.0);
+            positions.extend_from_slice(&[
+                Position([x0, y0, -1.0]),
+                Position([x1, y1, -1.0]),
+                Position([x1, y1, 1.0]),
+                Position([x0, y0, 1.0]),
+            ]);
+            tex_coords.extend_from_slice(&[
+                TexCoord([u0, 0.0]),
+                TexCoord([u1, 0.0]),
+                TexCoord([u1, 0.5]),
+                TexCoord([u0, 0.5]),
+            ]);
+        }
+        for &(z, v) in &[(1.0, 0.6), (-1.0, 0.85)] {
+            for &[x, y] in &ring {
+                positions.push(Position([x, y, z]));
+                tex_coords.push(TexCoord([(x + 1.0) / 8.0, v + (y + 1.0) / 8.0]));
+            }
+        }
+        let indices = (0..6u32)
+            .flat_map(|face| {
+                let first = face * 4;
+                vec![first, first + 1, first + 2, first, first + 2, first + 3]
+            })
+            .collect::<Vec<_>>();
+        let builder = MeshBuilder::new()
+            .with_indices(indices)
+            .with_vertices(positions.clone())
+            .with_vertices(tex_coords);
+
+        let seams = builder.uv_seam_edges();
+        assert_eq!(seams.len(), 18);
+        for &(a, b) in &seams {
+            let [pa, pb] = [positions[a as usize].0, positions[b as usize].0];
+            let vertical = pa[0] == pb[0] && pa[1] == pb[1];
+            if vertical {
+                // Only the edge closing the strip of sides is a seam.
+                assert_eq!([pa[0], pa[1]], [-1.0, -1.0]);
+            } else {
+                // All edges around the caps are seams.
+                assert_eq!(pa[2], pb[2]);
+            }
+        }
+
+        let shared = quad(0.0).with_vertices(vec![TexCoord([0.0, 0.0]); 4]);
+        assert_eq!(shared.uv_seam_edges(), []);
+        assert_eq!(quad(0.0).uv_seam_edges(), []);
+    }
+
+    #[test]
+    fn test_build_adjacency_indices() {
+        assert_eq!(
+            quad(0.0).build_adjacency_indices(),
+            Ok(vec![0, 2, 1, 3, 2, 1, 2, 0, 1, 2, 3, 1])
+        );
+
+        let unindexed = quad(0.0).into_unindexed();
+        assert_eq!(
+            unindexed.build_adjacency_indices(),
+            Ok(vec![0, 2, 1, 0, 2, 1, 3, 5, 4, 3, 5, 4])
+        );
+
+        let strip = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::TriangleStrip);
+        assert!(strip.build_adjacency_indices().is_err());
+    }
+
+    #[test]
+    fn test_into_unindexed() {
+        let builder = quad(0.0).with_vertices(vec![
+            Normal([0.0, 0.0, 1.0]),
+            Normal([0.0, 1.0, 0.0]),
+            Normal([1.0, 0.0, 0.0]),
+            Normal([0.0, 0.0, -1.0]),
+        ]);
+        let indices = builder.read_indices().unwrap();
+        let positions = builder.read_attribute::<Position>().unwrap();
+        let normals = builder.read_attribute::<Normal>().unwrap();
+
+        let unindexed = builder.into_unindexed();
+        assert_eq!(unindexed.read_indices(), None);
+        assert_eq!(unindexed.validate(), Ok(()));
+        assert_eq!(
+            unindexed.read_attribute::<Position>(),
+            Some(indices.iter().map(|&i| positions[i as usize]).collect())
+        );
+        assert_eq!(
+            unindexed.read_attribute::<Normal>(),
+            Some(indices.iter().map(|&i| normals[i as usize]).collect())
+        );
+    }
+
+    #[test]
+    fn test_bounding_sphere() {
+        let corners = (0..8)
+            .map(|i| {
+                let coordinate = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                Position([coordinate(1), coordinate(2), coordinate(4)])
+            })
+            .collect::<Vec<_>>();
+        let (center, radius) = MeshBuilder::from(corners).bounding_sphere().unwrap();
+        assert_eq!(center, [0.0, 0.0, 0.0]);
+        assert!((radius - 3.0f32.sqrt()).abs() < f32::EPSILON);
+
+        assert_eq!(MeshBuilder::new().bounding_sphere(), None);
+    }
+
+    #[test]
+    fn test_surface_area_and_volume() {
+        let mut unit = cube();
+        unit.map_positions(|[x, y, z]| [x * 0.5 + 10.0, y * 0.5, z * 0.5 - 3.0]);
+        assert!((unit.surface_area() - 6.0).abs() < 1e-5);
+        assert!((unit.signed_volume() - 1.0).abs() < 1e-5);
+
+        unit.flip_winding(false);
+        assert!((unit.surface_area() - 6.0).abs() < 1e-5);
+        assert!((unit.signed_volume() + 1.0).abs() < 1e-5);
+
+        assert!((quad(0.0).surface_area() - 1.0).abs() < 1e-6);
+        let lines = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.surface_area(), 0.0);
+        assert_eq!(MeshBuilder::new().signed_volume(), 0.0);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut centered = quad(-0.5);
+        centered.map_positions(|[x, y, z]| [x, y - 0.5, z]);
+        assert_eq!(centered.centroid_vertices(), Some([0.0; 3]));
+        assert_eq!(centered.centroid_surface(), Some([0.0; 3]));
+
+        // A dangling vertex moves the average of the vertices but not the surface.
+        let mut positions = centered.read_attribute::<Position>().unwrap();
+        positions.push(Position([5.0, 0.0, 0.0]));
+        let indices = centered.read_indices().unwrap();
+        let dangling = MeshBuilder::new()
+            .with_indices(indices)
+            .with_vertices(positions);
+        assert_eq!(dangling.centroid_vertices(), Some([1.0, 0.0, 0.0]));
+        assert_eq!(dangling.centroid_surface(), Some([0.0; 3]));
+
+        let lines = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.centroid_surface(), None);
+        assert_eq!(MeshBuilder::new().centroid_vertices(), None);
+    }
+
+    #[test]
+    fn test_clone_with_attributes() {
+        // Positions are in two buffers, one shared with normals.
+        let pos_norm = quad(0.0)
+            .read_attribute::<Position>()
+            .unwrap()
+            .into_iter()
+            .map(|position| PosNorm {
+                position,
+                normal: Normal([0.0, 0.0, 1.0]),
+            })
+            .collect::<Vec<_>>();
+        let full = quad(0.0)
+            .with_vertices(vec![TexCoord([0.5, 0.5]); 4])
+            .with_vertices(pos_norm);
+
+        let positions = full.clone_with_attributes(AttributeMask::POSITION);
+        assert_eq!(
+            positions.read_attribute::<Position>(),
+            full.read_attribute::<Position>()
+        );
+        assert_eq!(positions.read_attribute::<Normal>(), None);
+        assert_eq!(positions.read_attribute::<TexCoord>(), None);
+        assert_eq!(positions.read_indices(), full.read_indices());
+        let layout = positions.vertex_layout();
+        assert_eq!(layout.attributes.len(), 2);
+        assert!(layout
+            .attributes
+            .iter()
+            .all(|attribute| attribute.name == "position" && attribute.stride == 12));
+
+        let none = full.clone_with_attributes(AttributeMask::NONE);
+        assert!(none.vertex_layout().attributes.is_empty());
+        assert_eq!(none.read_indices(), full.read_indices());
+        assert_eq!(
+            full.clone_with_attributes(AttributeMask::ALL)
+                .vertex_layout(),
+            full.vertex_layout()
+        );
+    }
+
+    #[test]
+    fn test_non_manifold_edges() {
+        let mut builder = cube();
+        assert_eq!(builder.non_manifold_edges(), Vec::new());
+
+        // A fin attached to an edge of the cube makes it shared by three triangles.
+        let mut indices = builder.read_indices().unwrap();
+        let (a, b) = (indices[0], indices[1]);
+        builder.add_vertices(vec![Position([5.0, 5.0, 5.0])]);
+        indices.extend_from_slice(&[a, b, 8]);
+        builder.set_indices(indices);
+        assert_eq!(builder.non_manifold_edges(), vec![(a.min(b), a.max(b))]);
+
+        let lines = builder.with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.non_manifold_edges(), Vec::new());
+    }
+
+    #[test]
+    fn test_laplacian_smooth() {
+        let size = 10;
+        let positions = (0..size * size)
+            .map(|i| {
+                let jitter = ((i * 7919) % 13) as f32 / 13.0 - 0.5;
+                Position([(i % size) as f32, (i / size) as f32, jitter * 0.2])
+            })
+            .collect::<Vec<_>>();
+        let mut indices = Vec::new();
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let a = y * size + x;
+                let (b, c, d) = (a + 1, a + size, a + size + 1);
+                indices.extend_from_slice(&[a, b, d, a, d, c]);
+            }
+        }
+        let mut builder = MeshBuilder::new()
+            .with_indices(indices)
+            .with_vertices(positions.clone());
+        builder.laplacian_smooth(5, 0.5);
+        let smoothed = builder.read_attribute::<Position>().unwrap();
+
+        let variance = |positions: &[Position]| {
+            let mean = positions.iter().map(|p| p.0[2]).sum::<f32>() / positions.len() as f32;
+            positions
+                .iter()
+                .map(|p| (p.0[2] - mean) * (p.0[2] - mean))
+                .sum::<f32>()
+                / positions.len() as f32
+        };
+        assert!(variance(&smoothed) < variance(&positions) / 2.0);
+        for (i, (smoothed, original)) in smoothed.iter().zip(&positions).enumerate() {
+            let (x, y) = (i as u32 % size, i as u32 / size);
+            if x == 0 || y == 0 || x == size - 1 || y == size - 1 {
+                assert_eq!(smoothed, original);
+            }
+        }
+
+        let mut lines = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        lines.laplacian_smooth(1, 1.0);
+        assert_eq!(
+            lines.read_attribute::<Position>(),
+            quad(0.0).read_attribute::<Position>()
+        );
+    }
+
+    #[test]
+    fn test_face_normals() {
+        assert_eq!(quad(0.0).face_normals(), Some(vec![[0.0, 0.0, 1.0]; 2]));
+
+        let builder = cube();
+        let positions = builder.read_attribute::<Position>().unwrap();
+        let indices = builder.read_indices().unwrap();
+        let normals = builder.face_normals().unwrap();
+        assert_eq!(normals.len(), 12);
+        for (triangle, normal) in indices.chunks_exact(3).zip(normals) {
+            let center = (0..3)
+                .map(|i| {
+                    triangle
+                        .iter()
+                        .map(|&index| positions[index as usize].0[i])
+                        .sum::<f32>()
+                })
+                .collect::<Vec<_>>();
+            let outward = (0..3).map(|i| center[i] * normal[i]).sum::<f32>();
+            assert!(outward > 0.0);
+        }
+
+        let lines = quad(0.0).with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.face_normals(), None);
+        assert_eq!(
+            MeshBuilder::new()
+                .with_indices(vec![0u32, 1, 2])
+                .face_normals(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = quad(0.0).content_hash();
+        assert_eq!(quad(0.0).content_hash(), hash);
+        assert_ne!(quad(1.0).content_hash(), hash);
+
+        let mut builder = quad(0.0).with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4]);
+        let with_normals = builder.content_hash();
+        assert_ne!(with_normals, hash);
+        let mut normals = builder.read_attribute::<Normal>().unwrap();
+        normals[3].0[1] = 0.5;
+        builder.write_attribute(normals);
+        assert_ne!(builder.content_hash(), with_normals);
+
+        // Buffers added in a different order hash the same.
+        let reordered = MeshBuilder::new()
+            .with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4])
+            .with_vertices(quad(0.0).read_attribute::<Position>().unwrap())
+            .with_indices(vec![0u16, 1, 2, 2, 1, 3]);
+        assert_eq!(reordered.content_hash(), with_normals);
+
+        // The hash is stored in caches, so its input is spelled out here byte by byte.
+        let mut expected = Fnv1a::default();
+        let bytes = [
+            &[0, 0][..],
+            &1u64.to_le_bytes(),
+            &12u32.to_le_bytes(),
+            &1u64.to_le_bytes(),
+            &8u64.to_le_bytes(),
+            b"position",
+            &[0],
+            &(rendy_core::hal::format::Format::Rgb32Sfloat as u32).to_le_bytes(),
+            &0u32.to_le_bytes(),
+            &12u64.to_le_bytes(),
+            &[0; 12],
+            &[1],
+            &1u64.to_le_bytes(),
+            &0u32.to_le_bytes(),
+        ];
+        for bytes in &bytes {
+            std::hash::Hasher::write(&mut expected, bytes);
+        }
+        assert_eq!(
+            MeshBuilder::new()
+                .with_vertices(vec![Position([0.0, 0.0, 0.0])])
+                .with_indices(vec![0u16])
+                .with_prim_type(rendy_core::hal::pso::Primitive::PointList)
+                .content_hash(),
+            std::hash::Hasher::finish(&expected)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(quad(0.0).validate(), Ok(()));
+        assert_eq!(MeshBuilder::new().validate(), Ok(()));
+
+        let mismatch = quad(0.0).with_vertices(vec![Normal([0.0, 0.0, 1.0]); 3]);
+        assert_eq!(
+            mismatch.validate(),
+            Err(ValidationError::VertexCountMismatch {
+                buffer: 1,
+                len: 3,
+                expected: 4,
+            })
+        );
+
+        let out_of_range = quad(0.0).with_indices(vec![0u16, 1, 4]);
+        assert_eq!(
+            out_of_range.validate(),
+            Err(ValidationError::IndexOutOfRange {
+                position: 2,
+                index: 4,
+                len: 4,
+            })
+        );
+
+        let partial = quad(0.0).with_indices(vec![0u16, 1, 2, 3]);
+        assert_eq!(
+            partial.validate(),
+            Err(ValidationError::PartialPrimitive {
+                len: 4,
+                vertices_per_primitive: 3,
+            })
+        );
+        let lines = partial.with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.validate(), Ok(()));
+        let strip = quad(0.0)
+            .with_indices(vec![0u16, 1, 2, 3])
+            .with_prim_type(rendy_core::hal::pso::Primitive::TriangleStrip);
+        assert_eq!(strip.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_counts() {
+        let builder = quad(0.0).with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4]);
+        assert_eq!(builder.vertex_count(), 4);
+        assert_eq!(builder.index_count(), 6);
+        assert_eq!(builder.triangle_count(), 2);
+
+        let unindexed = builder.clone().into_unindexed();
+        assert_eq!(unindexed.index_count(), 6);
+        assert_eq!(unindexed.triangle_count(), 2);
+
+        let lines = builder.with_prim_type(rendy_core::hal::pso::Primitive::LineList);
+        assert_eq!(lines.index_count(), 6);
+        assert_eq!(lines.triangle_count(), 0);
+
+        let empty = MeshBuilder::new();
+        assert_eq!(empty.vertex_count(), 0);
+        assert_eq!(empty.index_count(), 0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut builder = quad(0.0);
+        builder.merge(&quad(2.0)).unwrap();
+        assert_eq!(builder.index_type(), Some(IndexType::U16));
+        assert_eq!(
+            builder.read_indices(),
+            Some(vec![0, 1, 2, 2, 1, 3, 4, 5, 6, 6, 5, 7])
+        );
+        assert_eq!(builder.vertex_count(), 8);
+        assert_eq!(
+            builder.bounding_box(),
+            Some(([0.0, 0.0, 0.0], [3.0, 1.0, 0.0]))
+        );
+
+        let other = quad(0.0).with_vertices(vec![Normal([0.0, 0.0, 1.0]); 4]);
+        assert_eq!(
+            builder.merge(&other).err(),
+            Some(MergeError::VertexFormatMismatch)
+        );
+        assert_eq!(builder.vertex_count(), 8);
+    }
+}
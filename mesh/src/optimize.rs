@@ -0,0 +1,241 @@
+//!
+//! Reordering of index and vertex data for faster rendering.
+//!
+
+/// Size of the simulated vertex cache.
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+/// Score of a vertex given its position in the cache and the number of triangles
+/// still using it. Higher scores are emitted sooner.
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        None => 0.0,
+        // The most recent triangle gets a fixed score so that the optimizer doesn't
+        // favour triangles sharing an edge with it over those sharing a vertex.
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+    };
+    cache_score + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// Reorder triangles of an indexed triangle list for post-transform vertex cache locality.
+///
+/// Implements Tom Forsyth's "Linear-Speed Vertex Cache Optimisation".
+/// Only the order of triangles changes, the winding of each triangle is preserved.
+/// Trailing indices that don't form a complete triangle are kept at the end.
+pub(crate) fn optimize_vertex_cache(indices: &[u32]) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+    let vertex_count = indices.iter().map(|&i| i as usize + 1).max().unwrap_or(0);
+
+    // Triangles using each vertex, stored contiguously in `adjacency`.
+    // The first `remaining[v]` entries of a vertex's range are not yet emitted.
+    let mut remaining = vec![0usize; vertex_count];
+    for &index in &indices[..triangle_count * 3] {
+        remaining[index as usize] += 1;
+    }
+    let mut offsets = Vec::with_capacity(vertex_count);
+    let mut total = 0;
+    for &count in &remaining {
+        offsets.push(total);
+        total += count;
+    }
+    let mut adjacency = vec![0usize; total];
+    let mut filled = vec![0usize; vertex_count];
+    for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+        for &index in corners {
+            let vertex = index as usize;
+            adjacency[offsets[vertex] + filled[vertex]] = triangle;
+            filled[vertex] += 1;
+        }
+    }
+
+    let mut cache_position = vec![None; vertex_count];
+    let mut vertex_scores = (0..vertex_count)
+        .map(|vertex| vertex_score(None, remaining[vertex]))
+        .collect::<Vec<_>>();
+    let triangle_score = |scores: &[f32], triangle: usize| -> f32 {
+        indices[triangle * 3..triangle * 3 + 3]
+            .iter()
+            .map(|&index| scores[index as usize])
+            .sum()
+    };
+    let triangle_scores = (0..triangle_count)
+        .map(|triangle| triangle_score(&vertex_scores, triangle))
+        .collect::<Vec<_>>();
+    let mut emitted = vec![false; triangle_count];
+
+    let mut output = Vec::with_capacity(indices.len());
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut next_unemitted = 0;
+    let mut best = (0..triangle_count).max_by(|&a, &b| {
+        triangle_scores[a]
+            .partial_cmp(&triangle_scores[b])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    while let Some(triangle) = best {
+        emitted[triangle] = true;
+        let corners = &indices[triangle * 3..triangle * 3 + 3];
+        output.extend_from_slice(corners);
+
+        for &index in corners {
+            let vertex = index as usize;
+            let range = offsets[vertex]..offsets[vertex] + remaining[vertex];
+            let live = &mut adjacency[range];
+            if let Some(position) = live.iter().position(|&t| t == triangle) {
+                live.swap(position, live.len() - 1);
+                remaining[vertex] -= 1;
+            }
+        }
+
+        // Move the emitted vertices to the front, evicting the oldest ones.
+        let mut updated = Vec::with_capacity(CACHE_SIZE + 3);
+        for &index in corners.iter().chain(cache.iter()) {
+            if !updated.contains(&index) {
+                updated.push(index);
+            }
+        }
+        for &index in updated.iter().skip(CACHE_SIZE) {
+            cache_position[index as usize] = None;
+        }
+        let evicted = updated.split_off(CACHE_SIZE.min(updated.len()));
+        for (position, &index) in updated.iter().enumerate() {
+            cache_position[index as usize] = Some(position);
+        }
+        cache = updated;
+
+        for &index in cache.iter().chain(evicted.iter()) {
+            let vertex = index as usize;
+            vertex_scores[vertex] = vertex_score(cache_position[vertex], remaining[vertex]);
+        }
+
+        best = None;
+        let mut best_score = -1.0;
+        for &index in cache.iter().chain(evicted.iter()) {
+            let vertex = index as usize;
+            for &t in &adjacency[offsets[vertex]..offsets[vertex] + remaining[vertex]] {
+                let score = triangle_score(&vertex_scores, t);
+                if score > best_score {
+                    best_score = score;
+                    best = Some(t);
+                }
+            }
+        }
+
+        // Nothing adjacent to the cache is left, so continue with any other triangle.
+        if best.is_none() {
+            while next_unemitted < triangle_count && emitted[next_unemitted] {
+                next_unemitted += 1;
+            }
+            if next_unemitted < triangle_count {
+                best = Some(next_unemitted);
+            }
+        }
+    }
+
+    output.extend_from_slice(&indices[triangle_count * 3..]);
+    output
+}
+
+/// Renumber vertices in order of their first use by `indices`.
+///
+/// Rewrites `indices` in place and returns the old index of each new vertex.
+/// Vertices not referenced by any index are moved to the end in their original order.
+/// All indices must be smaller than `vertex_count`.
+pub(crate) fn optimize_vertex_fetch(indices: &mut [u32], vertex_count: usize) -> Vec<u32> {
+    let mut remap = vec![None; vertex_count];
+    let mut order = Vec::with_capacity(vertex_count);
+    for index in indices.iter_mut() {
+        let new = *remap[*index as usize].get_or_insert_with(|| {
+            order.push(*index);
+            order.len() as u32 - 1
+        });
+        *index = new;
+    }
+    order.extend(
+        remap
+            .iter()
+            .enumerate()
+            .filter(|(_, new)| new.is_none())
+            .map(|(old, _)| old as u32),
+    );
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Average number of vertex transforms per triangle with a FIFO cache.
+    fn average_cache_miss_ratio(indices: &[u32], cache_size: usize) -> f32 {
+        let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+        let mut misses = 0;
+        for index in indices {
+            if !cache.contains(index) {
+                misses += 1;
+                if cache.len() == cache_size {
+                    cache.pop_front();
+                }
+                cache.push_back(*index);
+            }
+        }
+        misses as f32 / (indices.len() / 3) as f32
+    }
+
+    fn sorted_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut triangles = indices
+            .chunks_exact(3)
+            .map(|t| {
+                // Rotate so the smallest index comes first, which keeps the winding.
+                let start = (0..3).min_by_key(|&i| t[i]).unwrap();
+                [t[start], t[(start + 1) % 3], t[(start + 2) % 3]]
+            })
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    }
+
+    /// Triangulated grid with triangles in a scattered order.
+    fn grid(size: u32) -> Vec<u32> {
+        let mut triangles = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * (size + 1) + x;
+                triangles.push([i, i + 1, i + size + 1]);
+                triangles.push([i + 1, i + size + 2, i + size + 1]);
+            }
+        }
+        let count = triangles.len();
+        (0..count)
+            .flat_map(|i| triangles[(i * 7919) % count].to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_optimize_vertex_cache() {
+        let indices = grid(32);
+        let optimized = optimize_vertex_cache(&indices);
+        assert_eq!(sorted_triangles(&indices), sorted_triangles(&optimized));
+        let before = average_cache_miss_ratio(&indices, 16);
+        let after = average_cache_miss_ratio(&optimized, 16);
+        assert!(after < before, "{} is not less than {}", after, before);
+    }
+
+    #[test]
+    fn test_optimize_vertex_fetch() {
+        let mut indices = vec![3, 1, 0, 0, 1, 4];
+        let order = optimize_vertex_fetch(&mut indices, 6);
+        assert_eq!(indices, [0, 1, 2, 2, 1, 3]);
+        assert_eq!(order, [3, 1, 0, 4, 2, 5]);
+    }
+}
@@ -0,0 +1,416 @@
+//!
+//! Simplification of triangle meshes by edge collapse.
+//!
+
+use {
+    crate::tangent::{cross, dot, sub},
+    std::{
+        cmp::Reverse,
+        collections::{BinaryHeap, HashMap, HashSet},
+    },
+};
+
+/// Weight of the planes holding boundary edges in place, relative to the planes of faces.
+const BOUNDARY_WEIGHT: f64 = 10.0;
+
+/// Sum of squared distances to a set of weighted planes,
+/// stored as the upper triangle of a symmetric 4x4 matrix.
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Quadric of the plane with unit `normal` passing through `point`.
+    fn plane(normal: [f32; 3], point: [f32; 3], weight: f64) -> Self {
+        let [a, b, c] = [
+            f64::from(normal[0]),
+            f64::from(normal[1]),
+            f64::from(normal[2]),
+        ];
+        let d = -(a * f64::from(point[0]) + b * f64::from(point[1]) + c * f64::from(point[2]));
+        let w = weight;
+        Quadric([
+            a * a * w,
+            a * b * w,
+            a * c * w,
+            a * d * w,
+            b * b * w,
+            b * c * w,
+            b * d * w,
+            c * c * w,
+            c * d * w,
+            d * d * w,
+        ])
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(&other.0) {
+            *value += other;
+        }
+    }
+
+    /// Weighted sum of squared distances from `point` to the planes.
+    fn error(&self, point: [f32; 3]) -> f64 {
+        let q = &self.0;
+        let [x, y, z] = [
+            f64::from(point[0]),
+            f64::from(point[1]),
+            f64::from(point[2]),
+        ];
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+fn length(a: [f32; 3]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Collapse edges of a triangle list until at most `target` triangles remain,
+/// or no edge can be collapsed without damaging the mesh.
+///
+/// Vertices with equal positions form a single node of the surface.
+/// Each collapse moves all vertices of one node onto the vertices of a neighbouring node,
+/// picking the cheapest collapse by the quadric error metric, so remaining vertices
+/// keep their positions and other data. Seams between vertices of a node
+/// are only collapsed along themselves, and boundary edges only along the boundary.
+///
+/// Returns indices of the remaining triangles into the original vertices.
+/// Fails with the vertices of an edge shared by more than two triangles
+/// or by two triangles with the same orientation.
+/// Triangles referencing the same position twice are dropped.
+pub(crate) fn simplify(
+    positions: &[[f32; 3]],
+    indices: &[u32],
+    target: usize,
+) -> Result<Vec<u32>, [u32; 2]> {
+    let mut simplifier = Simplifier::new(positions, indices)?;
+    let mut remaining = simplifier.triangles.len();
+    let mut heap = BinaryHeap::new();
+    for node in 0..simplifier.nodes.len() {
+        simplifier.push_candidates(node, &mut heap);
+    }
+
+    while remaining > target {
+        let Reverse((_, from, to, from_version, to_version)) = match heap.pop() {
+            Some(candidate) => candidate,
+            None => break,
+        };
+        let (from, to) = (from as usize, to as usize);
+        if simplifier.nodes[from].removed
+            || simplifier.nodes[to].removed
+            || simplifier.nodes[from].version != from_version
+            || simplifier.nodes[to].version != to_version
+        {
+            continue;
+        }
+        if let Some(removed) = simplifier.collapse(from, to) {
+            remaining -= removed;
+            simplifier.push_candidates(to, &mut heap);
+        }
+    }
+
+    Ok(simplifier
+        .triangles
+        .iter()
+        .zip(&simplifier.alive)
+        .filter(|&(_, &alive)| alive)
+        .flat_map(|(triangle, _)| triangle.iter().copied())
+        .collect())
+}
+
+/// Vertices sharing a position.
+#[derive(Debug, Default)]
+struct Node {
+    position: [f32; 3],
+    quadric: Quadric,
+    /// Triangles using the node. Some of them may have been removed.
+    triangles: Vec<usize>,
+    /// Incremented whenever the node changes, invalidating queued collapses.
+    version: u32,
+    removed: bool,
+}
+
+/// Queued collapse: cost bits, node moved, node moved onto and their versions.
+type Candidate = Reverse<(u64, u32, u32, u32, u32)>;
+
+#[derive(Debug)]
+struct Simplifier {
+    /// Node of each vertex.
+    node_of: Vec<usize>,
+    nodes: Vec<Node>,
+    triangles: Vec<[u32; 3]>,
+    alive: Vec<bool>,
+}
+
+impl Simplifier {
+    fn new(positions: &[[f32; 3]], indices: &[u32]) -> Result<Self, [u32; 2]> {
+        let mut keys = HashMap::new();
+        let mut nodes = Vec::new();
+        let node_of = positions
+            .iter()
+            .map(|&position| {
+                // Adding zero turns negative zeros positive, so they share a node.
+                let key = [
+                    (position[0] + 0.0).to_bits(),
+                    (position[1] + 0.0).to_bits(),
+                    (position[2] + 0.0).to_bits(),
+                ];
+                *keys.entry(key).or_insert_with(|| {
+                    nodes.push(Node {
+                        position,
+                        ..Node::default()
+                    });
+                    nodes.len() - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .filter(|triangle| {
+                let [a, b, c] = [
+                    node_of[triangle[0] as usize],
+                    node_of[triangle[1] as usize],
+                    node_of[triangle[2] as usize],
+                ];
+                a != b && b != c && c != a
+            })
+            .collect::<Vec<_>>();
+
+        // Directed edges between nodes, and the number of triangles using each edge.
+        let mut edges = HashSet::new();
+        let mut shared = HashMap::new();
+        for triangle in &triangles {
+            for corner in 0..3 {
+                let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                let (node_a, node_b) = (node_of[a as usize], node_of[b as usize]);
+                if !edges.insert((node_a, node_b)) {
+                    return Err([a, b]);
+                }
+                let count = shared
+                    .entry((node_a.min(node_b), node_a.max(node_b)))
+                    .or_insert(0);
+                *count += 1;
+                if *count > 2 {
+                    return Err([a, b]);
+                }
+            }
+        }
+
+        let mut simplifier = Simplifier {
+            alive: vec![true; triangles.len()],
+            node_of,
+            nodes,
+            triangles,
+        };
+        for index in 0..simplifier.triangles.len() {
+            let [a, b, c] = simplifier.positions(index);
+            let normal = cross(sub(b, a), sub(c, a));
+            let double_area = length(normal);
+            if double_area == 0.0 {
+                continue;
+            }
+            let unit = [
+                normal[0] / double_area,
+                normal[1] / double_area,
+                normal[2] / double_area,
+            ];
+            let face = Quadric::plane(unit, a, f64::from(double_area) / 2.0);
+            for corner in 0..3 {
+                let node = simplifier.node(index, corner);
+                simplifier.nodes[node].quadric.add(&face);
+                simplifier.nodes[node].triangles.push(index);
+            }
+
+            // Edges without a twin are on the boundary. Planes perpendicular
+            // to the face through them keep the boundary from moving inwards.
+            for corner in 0..3 {
+                let (from, to) = (
+                    simplifier.node(index, corner),
+                    simplifier.node(index, (corner + 1) % 3),
+                );
+                if edges.contains(&(to, from)) {
+                    continue;
+                }
+                let edge = sub(
+                    simplifier.nodes[to].position,
+                    simplifier.nodes[from].position,
+                );
+                let perpendicular = cross(edge, unit);
+                let edge_length = length(perpendicular);
+                if edge_length == 0.0 {
+                    continue;
+                }
+                let perpendicular = [
+                    perpendicular[0] / edge_length,
+                    perpendicular[1] / edge_length,
+                    perpendicular[2] / edge_length,
+                ];
+                let weight = BOUNDARY_WEIGHT * f64::from(edge_length) * f64::from(edge_length);
+                let boundary =
+                    Quadric::plane(perpendicular, simplifier.nodes[from].position, weight);
+                simplifier.nodes[from].quadric.add(&boundary);
+                simplifier.nodes[to].quadric.add(&boundary);
+            }
+        }
+        Ok(simplifier)
+    }
+
+    fn node(&self, triangle: usize, corner: usize) -> usize {
+        self.node_of[self.triangles[triangle][corner] as usize]
+    }
+
+    fn positions(&self, triangle: usize) -> [[f32; 3]; 3] {
+        [
+            self.nodes[self.node(triangle, 0)].position,
+            self.nodes[self.node(triangle, 1)].position,
+            self.nodes[self.node(triangle, 2)].position,
+        ]
+    }
+
+    /// Corner of `triangle` at `node`.
+    fn corner(&self, triangle: usize, node: usize) -> Option<usize> {
+        (0..3).find(|&corner| self.node(triangle, corner) == node)
+    }
+
+    /// Remaining triangles using `node`.
+    fn triangles_of(&self, node: usize) -> Vec<usize> {
+        self.nodes[node]
+            .triangles
+            .iter()
+            .copied()
+            .filter(|&triangle| self.alive[triangle])
+            .collect()
+    }
+
+    /// Nodes connected to `node` by an edge, with the number of triangles using each edge.
+    fn neighbours(&self, node: usize) -> Vec<(usize, usize)> {
+        let mut neighbours = self
+            .triangles_of(node)
+            .into_iter()
+            .flat_map(|triangle| (0..3).map(move |corner| (triangle, corner)))
+            .map(|(triangle, corner)| self.node(triangle, corner))
+            .filter(|&other| other != node)
+            .collect::<Vec<_>>();
+        neighbours.sort_unstable();
+        let mut counted: Vec<(usize, usize)> = Vec::new();
+        for neighbour in neighbours {
+            match counted.last_mut() {
+                Some((last, count)) if *last == neighbour => *count += 1,
+                _ => counted.push((neighbour, 1)),
+            }
+        }
+        counted
+    }
+
+    /// Queue collapses of all edges of `node` in both directions.
+    fn push_candidates(&self, node: usize, heap: &mut BinaryHeap<Candidate>) {
+        for (neighbour, _) in self.neighbours(node) {
+            for &(from, to) in &[(node, neighbour), (neighbour, node)] {
+                let mut quadric = self.nodes[from].quadric;
+                quadric.add(&self.nodes[to].quadric);
+                // Bits of non-negative floats are ordered like the floats.
+                let cost = quadric.error(self.nodes[to].position).max(0.0);
+                heap.push(Reverse((
+                    cost.to_bits(),
+                    from as u32,
+                    to as u32,
+                    self.nodes[from].version,
+                    self.nodes[to].version,
+                )));
+            }
+        }
+    }
+
+    /// Move node `from` onto node `to` if that keeps the surface intact.
+    /// Returns the number of removed triangles.
+    fn collapse(&mut self, from: usize, to: usize) -> Option<usize> {
+        let triangles = self.triangles_of(from);
+        let (shared, kept): (Vec<usize>, Vec<usize>) = triangles
+            .into_iter()
+            .partition(|&triangle| self.corner(triangle, to).is_some());
+        if shared.is_empty() {
+            return None;
+        }
+
+        // Boundary nodes may only move along the boundary,
+        // otherwise the boundary would get pinched.
+        let from_neighbours = self.neighbours(from);
+        let from_boundary = from_neighbours.iter().any(|&(_, count)| count == 1);
+        if from_boundary && shared.len() != 1 {
+            return None;
+        }
+
+        // The only nodes connected to both must be the opposite corners of the shared
+        // triangles, otherwise the collapse would fold the surface onto itself.
+        let to_neighbours = self.neighbours(to);
+        let common = from_neighbours
+            .iter()
+            .filter(|&&(node, _)| to_neighbours.iter().any(|&(other, _)| other == node))
+            .count();
+        if common != shared.len() {
+            return None;
+        }
+        if from_neighbours.len() + to_neighbours.len() - common < 5 {
+            // Too few nodes would remain around `to` to form a surface.
+            return None;
+        }
+
+        // Vertices of `from` must map to vertices of `to` consistently across the shared
+        // triangles, so that seams of other data are only collapsed along themselves.
+        let mut map: Vec<(u32, u32)> = Vec::new();
+        for &triangle in &shared {
+            let vertex = self.triangles[triangle][self.corner(triangle, from).unwrap()];
+            let target = self.triangles[triangle][self.corner(triangle, to).unwrap()];
+            match map.iter().find(|&&(mapped, _)| mapped == vertex) {
+                Some(&(_, mapped)) if mapped != target => return None,
+                Some(_) => {}
+                None => map.push((vertex, target)),
+            }
+        }
+        let mut rewrites = Vec::with_capacity(kept.len());
+        let to_position = self.nodes[to].position;
+        for &triangle in &kept {
+            let corner = self.corner(triangle, from).unwrap();
+            let vertex = self.triangles[triangle][corner];
+            let target = map.iter().find(|&&(mapped, _)| mapped == vertex)?.1;
+
+            // Reject triangles that would flip or become degenerate.
+            let [a, b, c] = self.positions(triangle);
+            let before = cross(sub(b, a), sub(c, a));
+            let mut moved = [a, b, c];
+            moved[corner] = to_position;
+            let [a, b, c] = moved;
+            let after = cross(sub(b, a), sub(c, a));
+            if dot(before, after) <= 1e-3 * length(before) * length(after) {
+                return None;
+            }
+            rewrites.push((triangle, corner, target));
+        }
+
+        for &triangle in &shared {
+            self.alive[triangle] = false;
+        }
+        for (triangle, corner, target) in rewrites {
+            self.triangles[triangle][corner] = target;
+            self.nodes[to].triangles.push(triangle);
+        }
+        let quadric = self.nodes[from].quadric;
+        let from_node = &mut self.nodes[from];
+        from_node.removed = true;
+        from_node.triangles.clear();
+        let to_node = &mut self.nodes[to];
+        to_node.quadric.add(&quadric);
+        to_node.version += 1;
+        Some(shared.len())
+    }
+}
@@ -0,0 +1,150 @@
+//!
+//! Loop subdivision of triangle meshes.
+//!
+
+use {
+    crate::tangent::{add, scale},
+    std::collections::HashMap,
+};
+
+/// Result of one level of subdivision.
+#[derive(Debug)]
+pub(crate) struct Subdivision {
+    /// Pairs of vertices whose midpoints are the new vertices,
+    /// which are numbered after the original ones.
+    pub(crate) midpoints: Vec<[u32; 2]>,
+    /// Positions of the original vertices followed by the new ones.
+    pub(crate) positions: Vec<[f32; 3]>,
+    /// Four triangles for each original triangle.
+    pub(crate) indices: Vec<u32>,
+}
+
+/// Weight of each neighbour of an interior vertex with `valence` neighbours,
+/// as proposed by Charles Loop.
+fn neighbour_weight(valence: usize) -> f32 {
+    let k = valence as f32;
+    let c = 3.0 / 8.0 + (2.0 * std::f32::consts::PI / k).cos() / 4.0;
+    (5.0 / 8.0 - c * c) / k
+}
+
+/// Split every triangle into four, adding a vertex at each edge,
+/// and smooth positions with the Loop subdivision masks.
+///
+/// Vertices with equal positions are treated as one, so seams between other attributes
+/// don't open. Edges used by a single triangle, or by more than two, follow
+/// the boundary rules. Vertices with other than two such edges stay in place.
+pub(crate) fn loop_subdivide(positions: &[[f32; 3]], indices: &[u32]) -> Subdivision {
+    // Vertices sharing a position form a node, ordered by first use.
+    let mut nodes = HashMap::new();
+    let mut node_positions = Vec::new();
+    let node_of = positions
+        .iter()
+        .map(|&[x, y, z]| {
+            // Adding zero turns negative zero into positive zero.
+            let key = [
+                (x + 0.0).to_bits(),
+                (y + 0.0).to_bits(),
+                (z + 0.0).to_bits(),
+            ];
+            *nodes.entry(key).or_insert_with(|| {
+                node_positions.push([x, y, z]);
+                node_positions.len() - 1
+            })
+        })
+        .collect::<Vec<_>>();
+    let edge_key = |a: usize, b: usize| [a.min(b), a.max(b)];
+
+    // Node opposite to each edge of nodes in the triangles using it.
+    let mut opposites = HashMap::<[usize; 2], Vec<usize>>::new();
+    for triangle in indices.chunks_exact(3) {
+        for corner in 0..3 {
+            let a = node_of[triangle[corner] as usize];
+            let b = node_of[triangle[(corner + 1) % 3] as usize];
+            if a != b {
+                opposites
+                    .entry(edge_key(a, b))
+                    .or_default()
+                    .push(node_of[triangle[(corner + 2) % 3] as usize]);
+            }
+        }
+    }
+
+    let mut neighbours = vec![Vec::new(); node_positions.len()];
+    let mut boundary = vec![Vec::new(); node_positions.len()];
+    for (&[a, b], opposite) in &opposites {
+        neighbours[a].push(b);
+        neighbours[b].push(a);
+        if opposite.len() != 2 {
+            boundary[a].push(b);
+            boundary[b].push(a);
+        }
+    }
+    let sum = |nodes: &mut Vec<usize>| {
+        // Sorting makes the sum independent of the order of the hash map.
+        nodes.sort_unstable();
+        nodes
+            .iter()
+            .fold([0.0; 3], |sum, &node| add(sum, node_positions[node]))
+    };
+    let smoothed = (0..node_positions.len())
+        .map(|node| {
+            let position = node_positions[node];
+            match (neighbours[node].len(), boundary[node].len()) {
+                (0, _) => position,
+                (valence, 0) => {
+                    let weight = neighbour_weight(valence);
+                    add(
+                        scale(position, 1.0 - valence as f32 * weight),
+                        scale(sum(&mut neighbours[node]), weight),
+                    )
+                }
+                (_, 2) => add(
+                    scale(position, 3.0 / 4.0),
+                    scale(sum(&mut boundary[node]), 1.0 / 8.0),
+                ),
+                _ => position,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut subdivision = Subdivision {
+        midpoints: Vec::new(),
+        positions: node_of.iter().map(|&node| smoothed[node]).collect(),
+        indices: Vec::with_capacity(indices.len() * 4),
+    };
+    let mut midpoint_of = HashMap::new();
+    for triangle in indices.chunks_exact(3) {
+        let mut midpoints = [0; 3];
+        for corner in 0..3 {
+            let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+            let Subdivision {
+                midpoints: pairs,
+                positions: new_positions,
+                ..
+            } = &mut subdivision;
+            midpoints[corner] = *midpoint_of.entry([a.min(b), a.max(b)]).or_insert_with(|| {
+                let (node_a, node_b) = (node_of[a as usize], node_of[b as usize]);
+                let ends = add(node_positions[node_a], node_positions[node_b]);
+                let position = match opposites.get(&edge_key(node_a, node_b)) {
+                    Some(opposite) if opposite.len() == 2 => add(
+                        scale(ends, 3.0 / 8.0),
+                        scale(
+                            add(node_positions[opposite[0]], node_positions[opposite[1]]),
+                            1.0 / 8.0,
+                        ),
+                    ),
+                    _ => scale(ends, 0.5),
+                };
+                pairs.push([a, b]);
+                new_positions.push(position);
+                (new_positions.len() - 1) as u32
+            });
+        }
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        let [ab, bc, ca] = midpoints;
+        subdivision
+            .indices
+            .extend_from_slice(&[a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]);
+    }
+    subdivision
+}
@@ -0,0 +1,250 @@
+//! Computation of vertex tangents and the vector math it relies on.
+
+use crate::{Normal, Position, Tangent, TexCoord};
+
+/// Method of computing vertex tangents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TangentMode {
+    /// Sum tangents of the triangles sharing a vertex.
+    /// The `w` component is always `1.0`, so mirrored texture coordinates aren't handled.
+    #[default]
+    Simple,
+    /// Follow the MikkTSpace conventions expected by most normal maps.
+    ///
+    /// Triangle tangents and bitangents are projected onto the plane of the vertex normal
+    /// and weighted by the angle of the triangle corner. The `w` component is the sign
+    /// of the bitangent relative to `cross(normal, tangent)`.
+    /// Unlike the reference implementation, vertices aren't split when the triangles
+    /// sharing them have different handedness.
+    MikkTSpace,
+}
+
+/// Compute per-vertex tangents for indexed triangle list.
+///
+/// Tangents of all triangles sharing a vertex are accumulated into it
+/// and then orthogonalized against the vertex normal and normalized.
+/// Vertices whose triangles don't provide usable texture coordinates
+/// get an arbitrary tangent perpendicular to the normal.
+///
+/// With the `parallel` feature the tangents of triangles and vertices are computed
+/// in parallel. Triangle tangents are still accumulated in triangle order,
+/// so the result is bit-for-bit the same as without the feature.
+pub(crate) fn compute_tangents(
+    positions: &[Position],
+    normals: &[Normal],
+    tex_coords: &[TexCoord],
+    indices: &[u32],
+    mode: TangentMode,
+) -> Vec<Tangent> {
+    let indices = &indices[..indices.len() / 3 * 3];
+    let corners =
+        |triangle: &[u32]| triangle_tangents(positions, normals, tex_coords, triangle, mode);
+    let mut accumulated = vec![[0.0f32; 3]; positions.len()];
+    let mut accumulated_bitangents = vec![[0.0f32; 3]; positions.len()];
+    let mut accumulate = |triangle: &[u32], tangents: CornerTangents| {
+        for (&i, (tangent, bitangent)) in triangle.iter().zip(tangents.iter()) {
+            let i = i as usize;
+            if let Some(tangent) = tangent {
+                accumulated[i] = add(accumulated[i], *tangent);
+            }
+            if let Some(bitangent) = bitangent {
+                accumulated_bitangents[i] = add(accumulated_bitangents[i], *bitangent);
+            }
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        let triangles = indices
+            .par_chunks(3)
+            .map(|triangle| (triangle, corners(triangle)))
+            .collect::<Vec<_>>();
+        for (triangle, tangents) in triangles {
+            if let Some(tangents) = tangents {
+                accumulate(triangle, tangents);
+            }
+        }
+    }
+    #[cfg(not(feature = "parallel"))]
+    for triangle in indices.chunks(3) {
+        if let Some(tangents) = corners(triangle) {
+            accumulate(triangle, tangents);
+        }
+    }
+
+    let vertex_tangent = |((tangent, bitangent), normal): (([f32; 3], [f32; 3]), &Normal)| {
+        let normal = normal.0;
+        // Gram-Schmidt orthogonalize
+        let tangent = sub(tangent, scale(normal, dot(normal, tangent)));
+        let [x, y, z] = normalize(tangent).unwrap_or_else(|| perpendicular(normal));
+        let w = match mode {
+            TangentMode::Simple => 1.0,
+            TangentMode::MikkTSpace if dot(cross(normal, [x, y, z]), bitangent) < 0.0 => -1.0,
+            TangentMode::MikkTSpace => 1.0,
+        };
+        Tangent([x, y, z, w])
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        accumulated
+            .into_par_iter()
+            .zip(accumulated_bitangents)
+            .zip(normals)
+            .map(vertex_tangent)
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    accumulated
+        .into_iter()
+        .zip(accumulated_bitangents)
+        .zip(normals)
+        .map(vertex_tangent)
+        .collect()
+}
+
+/// Tangent and bitangent added to each corner of a triangle.
+/// `None` values are skipped rather than added as zero.
+type CornerTangents = [(Option<[f32; 3]>, Option<[f32; 3]>); 3];
+
+/// Tangents a triangle contributes to its corners.
+/// Returns `None` if the triangle doesn't have usable texture coordinates.
+fn triangle_tangents(
+    positions: &[Position],
+    normals: &[Normal],
+    tex_coords: &[TexCoord],
+    triangle: &[u32],
+    mode: TangentMode,
+) -> Option<CornerTangents> {
+    let [i0, i1, i2] = [
+        triangle[0] as usize,
+        triangle[1] as usize,
+        triangle[2] as usize,
+    ];
+    let triangle_positions = [positions[i0].0, positions[i1].0, positions[i2].0];
+    let (tangent, bitangent) = compute_tangent(
+        triangle_positions,
+        [tex_coords[i0].0, tex_coords[i1].0, tex_coords[i2].0],
+    )?;
+    let mut corners = [(None, None); 3];
+    for (corner, (&i, contribution)) in [i0, i1, i2].iter().zip(&mut corners).enumerate() {
+        *contribution = match mode {
+            TangentMode::Simple => (Some(tangent), None),
+            TangentMode::MikkTSpace => {
+                let normal = normals[i].0;
+                let project = |v: [f32; 3]| normalize(sub(v, scale(normal, dot(normal, v))));
+                let weight = corner_angle(triangle_positions, corner);
+                (
+                    project(tangent).map(|tangent| scale(tangent, weight)),
+                    project(bitangent).map(|bitangent| scale(bitangent, weight)),
+                )
+            }
+        };
+    }
+    Some(corners)
+}
+
+/// Angle of the triangle at `corner`.
+pub(crate) fn corner_angle(positions: [[f32; 3]; 3], corner: usize) -> f32 {
+    let at = positions[corner];
+    let edges = (
+        normalize(sub(positions[(corner + 1) % 3], at)),
+        normalize(sub(positions[(corner + 2) % 3], at)),
+    );
+    match edges {
+        (Some(a), Some(b)) => dot(a, b).clamp(-1.0, 1.0).acos(),
+        _ => 0.0,
+    }
+}
+
+/// Compute tangent and bitangent of a single triangle from its positions and texture coordinates.
+/// Returns `None` if texture coordinates are degenerate.
+///
+/// Degeneracy is judged relative to the texture coordinate edges, so that triangles
+/// mapped to a small part of the texture, such as an atlas cell, still get tangents.
+fn compute_tangent(
+    positions: [[f32; 3]; 3],
+    tex_coords: [[f32; 2]; 3],
+) -> Option<([f32; 3], [f32; 3])> {
+    let delta_pos_1 = sub(positions[1], positions[0]);
+    let delta_pos_2 = sub(positions[2], positions[0]);
+    let delta_uv_1 = [
+        tex_coords[1][0] - tex_coords[0][0],
+        tex_coords[1][1] - tex_coords[0][1],
+    ];
+    let delta_uv_2 = [
+        tex_coords[2][0] - tex_coords[0][0],
+        tex_coords[2][1] - tex_coords[0][1],
+    ];
+    let tspace_det = delta_uv_1[0] * delta_uv_2[1] - delta_uv_1[1] * delta_uv_2[0];
+    let uv_length = |[u, v]: [f32; 2]| (u * u + v * v).sqrt();
+    if tspace_det.abs() <= f32::EPSILON * uv_length(delta_uv_1) * uv_length(delta_uv_2) {
+        return None;
+    }
+
+    let tangent = scale(
+        sub(
+            scale(delta_pos_1, delta_uv_2[1]),
+            scale(delta_pos_2, delta_uv_1[1]),
+        ),
+        1.0 / tspace_det,
+    );
+    let bitangent = scale(
+        sub(
+            scale(delta_pos_2, delta_uv_1[0]),
+            scale(delta_pos_1, delta_uv_2[0]),
+        ),
+        1.0 / tspace_det,
+    );
+
+    if tangent.iter().chain(&bitangent).all(|c| c.is_finite()) {
+        Some((tangent, bitangent))
+    } else {
+        None
+    }
+}
+
+/// Deterministic unit vector perpendicular to `normal`.
+fn perpendicular(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    normalize(sub(axis, scale(normal, dot(normal, axis)))).unwrap_or(axis)
+}
+
+pub(crate) fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+    let len = dot(a, a).sqrt();
+    if len > f32::EPSILON && len.is_finite() {
+        Some(scale(a, 1.0 / len))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,97 @@
+//!
+//! Making winding order of triangles consistent across shared edges.
+//!
+
+use std::collections::HashMap;
+
+/// Flip triangles of a triangle list so that triangles sharing an edge traverse it
+/// in opposite directions, as they do when they all face the same side of the surface.
+///
+/// Orientation spreads from a seed triangle across edges shared by exactly two triangles.
+/// Edges used by more than two triangles are not followed. Afterwards each connected
+/// group of triangles is flipped as a whole if that keeps more triangles unchanged.
+///
+/// Returns index of the triangle found to need both orientations when the surface
+/// is not orientable, leaving indices unchanged.
+pub(crate) fn unify_winding(indices: &mut [u32]) -> Result<(), usize> {
+    let triangle_count = indices.len() / 3;
+    let directed_edge = |triangle: usize, corner: usize| {
+        (
+            indices[triangle * 3 + corner],
+            indices[triangle * 3 + (corner + 1) % 3],
+        )
+    };
+
+    // Triangles using each undirected edge, with whether they go from its lower vertex.
+    let mut edges: HashMap<[u32; 2], Vec<(usize, bool)>> = HashMap::new();
+    for triangle in 0..triangle_count {
+        for corner in 0..3 {
+            let (a, b) = directed_edge(triangle, corner);
+            if a != b {
+                edges
+                    .entry([a.min(b), a.max(b)])
+                    .or_default()
+                    .push((triangle, a < b));
+            }
+        }
+    }
+
+    let mut flipped = vec![None; triangle_count];
+    let mut stack = Vec::new();
+    let mut group = Vec::new();
+    for seed in 0..triangle_count {
+        if flipped[seed].is_some() {
+            continue;
+        }
+        flipped[seed] = Some(false);
+        stack.push(seed);
+        group.clear();
+
+        while let Some(triangle) = stack.pop() {
+            group.push(triangle);
+            let flip = flipped[triangle] == Some(true);
+            for corner in 0..3 {
+                let (a, b) = directed_edge(triangle, corner);
+                if a == b {
+                    continue;
+                }
+                let users = &edges[&[a.min(b), a.max(b)]];
+                if users.len() != 2 {
+                    continue;
+                }
+                for &(other, forward) in users {
+                    if other == triangle {
+                        continue;
+                    }
+                    // After flipping, the neighbour must go along the edge the other way.
+                    let other_flip = (a < b) ^ flip ^ forward ^ true;
+                    match flipped[other] {
+                        None => {
+                            flipped[other] = Some(other_flip);
+                            stack.push(other);
+                        }
+                        Some(existing) if existing != other_flip => return Err(other),
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+
+        let flip_count = group
+            .iter()
+            .filter(|&&triangle| flipped[triangle] == Some(true))
+            .count();
+        if flip_count * 2 > group.len() {
+            for &triangle in &group {
+                flipped[triangle] = flipped[triangle].map(|flip| !flip);
+            }
+        }
+    }
+
+    for (triangle, flip) in indices.chunks_exact_mut(3).zip(flipped) {
+        if flip == Some(true) {
+            triangle.swap(1, 2);
+        }
+    }
+    Ok(())
+}